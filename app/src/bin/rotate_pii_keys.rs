@@ -0,0 +1,24 @@
+// Re-encrypts SEP-12 customer PII under PII_ACTIVE_KEY_ID and recomputes the email and
+// phone blind indexes under PII_BLIND_INDEX_KEY.
+//
+// Usage: cargo run --bin rotate_pii_keys [batch_size]
+//
+// Keep the retiring key in PII_MASTER_KEYS until this has finished.
+use services::sep12::sep12::rotate_pii_keys;
+
+fn main() {
+    dotenv::dotenv().ok();
+
+    let batch_size = std::env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse::<i64>().ok())
+        .unwrap_or(500);
+
+    match rotate_pii_keys(batch_size) {
+        Ok(count) => println!("Re-encrypted {} customer records", count),
+        Err(e) => {
+            eprintln!("Key rotation failed: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
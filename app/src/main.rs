@@ -1,6 +1,7 @@
 #[macro_use]
 extern crate rocket;
 use app::routes;
+use rocket::fairing::AdHoc;
 
 #[launch]
 fn rocket() -> _ {
@@ -16,6 +17,9 @@ fn rocket() -> _ {
                 routes::sep38::routes::get_sep38_price_route,
                 routes::sep38::routes::create_sep38_quote_route,
                 routes::sep38::routes::get_sep38_quote_route,
+                routes::sep38::routes::get_sep38_best_price_route,
+                routes::sep38::routes::create_sep38_best_quote_route,
+                routes::sep38::routes::get_sep38_history_route,
             ],
         )
         .mount(
@@ -23,9 +27,15 @@ fn rocket() -> _ {
             routes![
                 routes::sep6::sep6::withdraw,
                 routes::sep6::sep6::withdraw_exchange,
+                routes::sep6::sep6::estimate,
+                routes::sep6::sep6::fee,
                 routes::sep6::sep6::anchorinfo,
+                routes::sep6::sep6::form_schema,
                 routes::sep6::sep6::transactions,
                 routes::sep6::sep6::transaction,
+                routes::sep6::sep6::pending_actions,
+                routes::sep6::sep6::update_transaction,
+                routes::sep6::sep6::refunds,
             ],
         )
         .mount(
@@ -35,6 +45,16 @@ fn rocket() -> _ {
                 routes::sep12::sep12::create_kyc,
                 routes::sep12::sep12::update_kyc,
                 routes::sep12::sep12::delete_customer,
+                routes::sep12::sep12::get_customer_files,
+                routes::sep12::sep12::get_customer_file,
+                routes::sep12::sep12::submit_verification,
+                routes::sep12::sep12::set_callback,
+                routes::sep12::sep12::receive_callback,
+                routes::sep12::sep12::get_requirements,
+                routes::sep12::sep12::get_submissions,
+                routes::sep12::sep12::grant_consent,
+                routes::sep12::sep12::revoke_consent,
+                routes::sep12::sep12::onboard_customer,
             ],
         )
     
@@ -46,6 +66,70 @@ fn rocket() -> _ {
                 routes::sep31::routes::get_sep31_transaction_route,
                 routes::sep31::routes::update_sep31_transaction_route,
                 routes::sep31::routes::set_sep31_transaction_callback_route,
+                routes::sep31::routes::submit_sep31_payment_route,
+                routes::sep31::routes::register_sep31_customers_route,
             ],
         )
+        .mount(
+            "/v1/webhooks",
+            routes![
+                routes::webhook::routes::create_subscription_route,
+                routes::webhook::routes::get_subscriptions_route,
+                routes::webhook::routes::delete_subscription_route,
+                routes::webhook::routes::get_deliveries_route,
+                routes::webhook::routes::replay_delivery_route,
+            ],
+        )
+        .mount(
+            "/v1/admin/reconciliation",
+            routes![
+                routes::reconciliation::routes::run_reconciliation_route,
+                routes::reconciliation::routes::get_report_route,
+                routes::reconciliation::routes::resolve_issue_route,
+            ],
+        )
+        .mount(
+            "/v1/exchange/alerts",
+            routes![
+                routes::rate_alert::routes::create_alert_route,
+                routes::rate_alert::routes::list_alerts_route,
+                routes::rate_alert::routes::cancel_alert_route,
+            ],
+        )
+        .mount(
+            "/v1/admin/data-subject",
+            routes![
+                routes::data_subject::routes::export_route,
+                routes::data_subject::routes::erase_route,
+                routes::data_subject::routes::erasures_route,
+                routes::data_subject::routes::verify_erasures_route,
+            ],
+        )
+        .attach(AdHoc::on_liftoff("Webhook delivery worker", |_| {
+            Box::pin(async {
+                rocket::tokio::spawn(services::webhook::webhook::run_delivery_worker());
+            })
+        }))
+        .attach(AdHoc::on_liftoff("Withdrawal reconciliation job", |_| {
+            Box::pin(async {
+                rocket::tokio::spawn(
+                    services::reconciliation::reconciliation::run_reconciliation_worker(),
+                );
+            })
+        }))
+        .attach(AdHoc::on_liftoff("Quote expiry sweeper", |_| {
+            Box::pin(async {
+                rocket::tokio::spawn(services::sep38::sep38::run_quote_expiry_worker());
+            })
+        }))
+        .attach(AdHoc::on_liftoff("SEP-38 asset catalogue refresh", |_| {
+            Box::pin(async {
+                rocket::tokio::spawn(services::sep38::sep38::run_asset_refresh_worker());
+            })
+        }))
+        .attach(AdHoc::on_liftoff("SEP-38 price sampler", |_| {
+            Box::pin(async {
+                rocket::tokio::spawn(services::sep38::sep38::run_price_sampler());
+            })
+        }))
 }
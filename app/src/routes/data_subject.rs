@@ -0,0 +1,90 @@
+pub mod routes {
+    use controllers::api::api::{ApiResponse, failure, success};
+    use controllers::data_subject::form::form::{DataErasureForm, DataSubjectForm};
+    use controllers::data_subject::{
+        erase_data_subject, export_data_subject, get_data_subject_erasures,
+        verify_data_erasure_log,
+    };
+    use models::data_subject::DataErasureLogEntry;
+    use rocket::{get, http::Status, post, response::status, serde::json::Json};
+    use services::data_subject::data_subject::{
+        DataExport, DataSubjectError, ErasureReport, LogVerification,
+    };
+
+    #[post("/export", data = "<form>")]
+    pub async fn export_route(
+        form: Json<DataSubjectForm>,
+    ) -> Result<status::Custom<Json<ApiResponse<DataExport>>>, status::Custom<Json<ApiResponse<()>>>>
+    {
+        let export = export_data_subject(form).await.map_err(|e| {
+            eprintln!("Error exporting data subject: {:?}", e);
+            failure("Failed to export account data", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Account data exported successfully",
+            export,
+            Status::Ok,
+        ))
+    }
+
+    #[post("/erase", data = "<form>")]
+    pub async fn erase_route(
+        form: Json<DataErasureForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<ErasureReport>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let report = erase_data_subject(form).await.map_err(|e| {
+            eprintln!("Error erasing data subject: {:?}", e);
+            match e {
+                DataSubjectError::InvalidRequest(_) => {
+                    failure("Erasure must be confirmed", Status::BadRequest)
+                }
+                _ => failure("Failed to erase account data", Status::InternalServerError),
+            }
+        })?;
+
+        Ok(success(
+            "Account data erased successfully",
+            report,
+            Status::Ok,
+        ))
+    }
+
+    #[post("/erasures", data = "<form>")]
+    pub async fn erasures_route(
+        form: Json<DataSubjectForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<Vec<DataErasureLogEntry>>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let entries = get_data_subject_erasures(form).await.map_err(|e| {
+            eprintln!("Error fetching erasure log: {:?}", e);
+            failure("Failed to fetch erasure log", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Erasure log retrieved successfully",
+            entries,
+            Status::Ok,
+        ))
+    }
+
+    #[get("/erasures/verify")]
+    pub async fn verify_erasures_route() -> Result<
+        status::Custom<Json<ApiResponse<LogVerification>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let verification = verify_data_erasure_log().await.map_err(|e| {
+            eprintln!("Error verifying erasure log: {:?}", e);
+            failure("Failed to verify erasure log", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Erasure log verified",
+            verification,
+            Status::Ok,
+        ))
+    }
+}
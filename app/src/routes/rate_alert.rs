@@ -0,0 +1,54 @@
+pub mod routes {
+    use controllers::api::api::{ApiResponse, failure, success};
+    use controllers::rate_alert::form::form::{
+        RateAlertCancelForm, RateAlertForm, RateAlertListForm,
+    };
+    use controllers::rate_alert::{cancel_rate_alert, create_rate_alert, get_rate_alerts};
+    use models::sep38::Sep38RateAlert;
+    use rocket::{delete, http::Status, post, response::status, serde::json::Json};
+
+    #[post("/", data = "<form>")]
+    pub async fn create_alert_route(
+        form: Json<RateAlertForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<Sep38RateAlert>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let alert = create_rate_alert(form).await.map_err(|e| {
+            eprintln!("Error creating rate alert: {:?}", e);
+            failure("Failed to create rate alert", Status::BadRequest)
+        })?;
+
+        Ok(success("Rate alert created successfully", alert, Status::Created))
+    }
+
+    #[post("/list", data = "<form>")]
+    pub async fn list_alerts_route(
+        form: Json<RateAlertListForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<Vec<Sep38RateAlert>>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let alerts = get_rate_alerts(form).await.map_err(|e| {
+            eprintln!("Error fetching rate alerts: {:?}", e);
+            failure("Failed to fetch rate alerts", Status::InternalServerError)
+        })?;
+
+        Ok(success("Rate alerts fetched successfully", alerts, Status::Ok))
+    }
+
+    #[delete("/", data = "<form>")]
+    pub async fn cancel_alert_route(
+        form: Json<RateAlertCancelForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<Sep38RateAlert>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let alert = cancel_rate_alert(form).await.map_err(|e| {
+            eprintln!("Error cancelling rate alert: {:?}", e);
+            failure("Failed to cancel rate alert", Status::BadRequest)
+        })?;
+
+        Ok(success("Rate alert cancelled successfully", alert, Status::Ok))
+    }
+}
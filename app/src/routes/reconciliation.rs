@@ -0,0 +1,67 @@
+pub mod routes {
+    use controllers::api::api::{ApiResponse, failure, success};
+    use controllers::reconciliation::form::form::{
+        ReconciliationReportForm, ReconciliationResolveForm,
+    };
+    use controllers::reconciliation::{
+        get_reconciliation_report, resolve_reconciliation_issue, run_reconciliation,
+    };
+    use models::reconciliation::ReconciliationIssue;
+    use rocket::{http::Status, post, response::status, serde::json::Json};
+    use services::reconciliation::reconciliation::{ReconciliationReport, ReconciliationSummary};
+
+    #[post("/run")]
+    pub async fn run_reconciliation_route() -> Result<
+        status::Custom<Json<ApiResponse<ReconciliationSummary>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let summary = run_reconciliation().await.map_err(|e| {
+            eprintln!("Error running reconciliation: {:?}", e);
+            failure("Failed to run reconciliation", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Reconciliation completed successfully",
+            summary,
+            Status::Ok,
+        ))
+    }
+
+    #[post("/report", data = "<form>")]
+    pub async fn get_report_route(
+        form: Json<ReconciliationReportForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<ReconciliationReport>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let report = get_reconciliation_report(form).await.map_err(|e| {
+            eprintln!("Error fetching reconciliation report: {:?}", e);
+            failure("Failed to fetch reconciliation report", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Reconciliation report fetched successfully",
+            report,
+            Status::Ok,
+        ))
+    }
+
+    #[post("/issues/resolve", data = "<form>")]
+    pub async fn resolve_issue_route(
+        form: Json<ReconciliationResolveForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<ReconciliationIssue>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let issue = resolve_reconciliation_issue(form).await.map_err(|e| {
+            eprintln!("Error resolving reconciliation issue: {:?}", e);
+            failure("Failed to resolve reconciliation issue", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Reconciliation issue resolved successfully",
+            issue,
+            Status::Ok,
+        ))
+    }
+}
@@ -2,16 +2,23 @@ pub mod sep12 {
     use controllers::{
         api::api::{failure, success, ApiResponse},
         sep12::form::form::{
-            Sep12KycStatusForm, Sep12UpdateKycForm, Sep12DeleteKycForm,Sep12FieldsAndFiles
+            Sep12KycStatusForm, Sep12UpdateKycForm, Sep12DeleteKycForm,Sep12FieldsAndFiles,
+            Sep12CustomerFilesForm, Sep12VerificationForm, Sep12CallbackForm,
+            Sep12CallbackSignature, Sep12RequirementsForm, Sep12AnchorForm,
 
         },
         sep12::{
             create_sep12_kyc, delete_sep12_kyc, get_sep12_kyc_status, 
-            update_sep12_kyc,
+            update_sep12_kyc, get_sep12_customer_file, get_sep12_customer_files,
+            submit_sep12_verification, set_sep12_callback, receive_sep12_callback,
+            get_sep12_requirements, get_sep12_submissions, set_sep12_consent,
+            onboard_sep12_customer,
         },
     };
-    use services::sep12::sep12::Customer;
+    use models::sep12::{Sep12AnchorSubmission, Sep12CustomerFile};
+    use services::sep12::sep12::{Customer, RequirementsDiff, Sep12Error};
     use rocket::form::Form;
+    use rocket::http::ContentType;
     use rocket::{delete, get, http::Status, post, put, response::status, serde::json::Json};
 
     
@@ -33,6 +40,24 @@ pub mod sep12 {
         ))
     }
     
+    #[get("/customer/requirements", data = "<form>")]
+    pub async fn get_requirements(
+        form: Json<Sep12RequirementsForm>,
+    ) -> Result<status::Custom<Json<ApiResponse<RequirementsDiff>>>, status::Custom<Json<ApiResponse<()>>>> {
+        let diff = get_sep12_requirements(form)
+            .await
+            .map_err(|e| {
+                eprintln!("Error getting SEP-12 requirements: {:?}", e);
+                failure("Failed to get KYC requirements", Status::InternalServerError)
+            })?;
+
+        Ok(success(
+            "Customer KYC requirements retrieved successfully",
+            diff,
+            Status::Ok,
+        ))
+    }
+
     #[put("/customer", data = "<data>", format = "multipart/form-data")]
     pub async fn create_kyc(
         data: Form<Sep12FieldsAndFiles<'_>>,
@@ -71,16 +96,76 @@ pub mod sep12 {
     }
     
     
-    #[put("/customer/callback")]
+    #[put("/customer/verification", data = "<form>")]
+    pub async fn submit_verification(
+        form: Json<Sep12VerificationForm>,
+    ) -> Result<status::Custom<Json<ApiResponse<Customer>>>, status::Custom<Json<ApiResponse<()>>>> {
+        let customer = submit_sep12_verification(form)
+            .await
+            .map_err(|e| {
+                eprintln!("Error submitting SEP-12 verification: {:?}", e);
+                match e {
+                    Sep12Error::CustomerNotFound => failure("Customer not found", Status::NotFound),
+                    Sep12Error::InvalidRequest(_) | Sep12Error::VerificationFailed(_) => {
+                        failure("Verification rejected", Status::BadRequest)
+                    }
+                    _ => failure("Failed to submit verification", Status::InternalServerError),
+                }
+            })?;
+
+        Ok(success(
+            "Customer verification submitted successfully",
+            customer,
+            Status::Ok,
+        ))
+    }
+
+    #[put("/customer/callback", data = "<form>")]
     pub async fn set_callback(
-    ) -> Result<status::Custom<Json<ApiResponse<()>>>, status::Custom<Json<ApiResponse<()>>>> {
+        form: Json<Sep12CallbackForm>,
+    ) -> Result<status::Custom<Json<ApiResponse<String>>>, status::Custom<Json<ApiResponse<()>>>> {
+        let url = set_sep12_callback(form)
+            .await
+            .map_err(|e| {
+                eprintln!("Error setting SEP-12 callback: {:?}", e);
+                failure("Failed to set callback", Status::InternalServerError)
+            })?;
+
         Ok(success(
-            "Callback set successfully", 
-            (), 
+            "Callback set successfully",
+            url,
             Status::Ok
         ))
     }
 
+    // Anchors post customer status changes here
+    #[post("/customer/callback/<slug>", data = "<body>")]
+    pub async fn receive_callback(
+        slug: &str,
+        signature: Sep12CallbackSignature,
+        body: String,
+    ) -> Result<status::Custom<Json<ApiResponse<()>>>, status::Custom<Json<ApiResponse<()>>>> {
+        receive_sep12_callback(slug, signature.0.as_deref(), &body)
+            .await
+            .map_err(|e| {
+                eprintln!("Error handling SEP-12 callback: {:?}", e);
+                match e {
+                    Sep12Error::VerificationFailed(_) | Sep12Error::AuthFailed => {
+                        failure("Invalid callback signature", Status::Unauthorized)
+                    }
+                    Sep12Error::CustomerNotFound => failure("Customer not found", Status::NotFound),
+                    Sep12Error::InvalidRequest(_) => failure("Invalid callback", Status::BadRequest),
+                    _ => failure("Failed to handle callback", Status::InternalServerError),
+                }
+            })?;
+
+        Ok(success(
+            "Callback received",
+            (),
+            Status::Ok,
+        ))
+    }
+
     #[delete("/customer", data = "<form>")]
     pub async fn delete_customer(
         form: Json<Sep12DeleteKycForm>,
@@ -99,4 +184,121 @@ pub mod sep12 {
         ))
     }
 
-}
\ No newline at end of file
+    #[post("/customer/files", data = "<form>")]
+    pub async fn get_customer_files(
+        form: Json<Sep12CustomerFilesForm>,
+    ) -> Result<status::Custom<Json<ApiResponse<Vec<Sep12CustomerFile>>>>, status::Custom<Json<ApiResponse<()>>>> {
+        let files = get_sep12_customer_files(form)
+            .await
+            .map_err(|e| {
+                eprintln!("Error listing SEP-12 customer files: {:?}", e);
+                failure("Failed to list customer files", Status::InternalServerError)
+            })?;
+
+        Ok(success(
+            "Customer files retrieved successfully",
+            files,
+            Status::Ok,
+        ))
+    }
+
+    #[get("/customer/file/<file_id>")]
+    pub async fn get_customer_file(
+        file_id: &str,
+    ) -> Result<(ContentType, Vec<u8>), status::Custom<Json<ApiResponse<()>>>> {
+        let (file, bytes) = get_sep12_customer_file(file_id)
+            .await
+            .map_err(|e| {
+                eprintln!("Error fetching SEP-12 customer file: {:?}", e);
+                match e {
+                    Sep12Error::FileNotFound => failure("File not found", Status::NotFound),
+                    _ => failure("Failed to fetch customer file", Status::InternalServerError),
+                }
+            })?;
+
+        let content_type = ContentType::parse_flexible(&file.content_type).unwrap_or(ContentType::Binary);
+
+        Ok((content_type, bytes))
+    }
+
+    #[post("/customer/submissions", data = "<form>")]
+    pub async fn get_submissions(
+        form: Json<Sep12CustomerFilesForm>,
+    ) -> Result<status::Custom<Json<ApiResponse<Vec<Sep12AnchorSubmission>>>>, status::Custom<Json<ApiResponse<()>>>> {
+        let submissions = get_sep12_submissions(form)
+            .await
+            .map_err(|e| {
+                eprintln!("Error listing SEP-12 anchor submissions: {:?}", e);
+                failure("Failed to list anchor submissions", Status::InternalServerError)
+            })?;
+
+        Ok(success(
+            "Anchor submissions retrieved successfully",
+            submissions,
+            Status::Ok,
+        ))
+    }
+
+    fn consent_failure(e: Sep12Error) -> status::Custom<Json<ApiResponse<()>>> {
+        eprintln!("Error updating SEP-12 consent: {:?}", e);
+        match e {
+            Sep12Error::CustomerNotFound => failure("Customer not found", Status::NotFound),
+            Sep12Error::ConsentRequired(_) => failure("No consent to withdraw", Status::NotFound),
+            Sep12Error::InvalidRequest(_) => failure("Invalid consent request", Status::BadRequest),
+            _ => failure("Failed to update consent", Status::InternalServerError),
+        }
+    }
+
+    #[post("/customer/consent", data = "<form>")]
+    pub async fn grant_consent(
+        form: Json<Sep12AnchorForm>,
+    ) -> Result<status::Custom<Json<ApiResponse<Sep12AnchorSubmission>>>, status::Custom<Json<ApiResponse<()>>>> {
+        let submission = set_sep12_consent(form, true).await.map_err(consent_failure)?;
+
+        Ok(success(
+            "Consent recorded successfully",
+            submission,
+            Status::Ok,
+        ))
+    }
+
+    #[delete("/customer/consent", data = "<form>")]
+    pub async fn revoke_consent(
+        form: Json<Sep12AnchorForm>,
+    ) -> Result<status::Custom<Json<ApiResponse<Sep12AnchorSubmission>>>, status::Custom<Json<ApiResponse<()>>>> {
+        let submission = set_sep12_consent(form, false).await.map_err(consent_failure)?;
+
+        Ok(success(
+            "Consent withdrawn successfully",
+            submission,
+            Status::Ok,
+        ))
+    }
+
+    #[post("/customer/onboard", data = "<form>")]
+    pub async fn onboard_customer(
+        form: Json<Sep12AnchorForm>,
+    ) -> Result<status::Custom<Json<ApiResponse<Sep12AnchorSubmission>>>, status::Custom<Json<ApiResponse<()>>>> {
+        let submission = onboard_sep12_customer(form)
+            .await
+            .map_err(|e| {
+                eprintln!("Error onboarding SEP-12 customer: {:?}", e);
+                match e {
+                    Sep12Error::CustomerNotFound => failure("Customer not found", Status::NotFound),
+                    Sep12Error::ConsentRequired(_) => {
+                        failure("Customer has not consented to this anchor", Status::Forbidden)
+                    }
+                    Sep12Error::InvalidRequest(_) | Sep12Error::InvalidField(_) => {
+                        failure("Anchor rejected the customer profile", Status::BadRequest)
+                    }
+                    _ => failure("Failed to onboard customer", Status::InternalServerError),
+                }
+            })?;
+
+        Ok(success(
+            "Customer submitted to anchor successfully",
+            submission,
+            Status::Accepted,
+        ))
+    }
+}
@@ -1,13 +1,15 @@
 pub mod routes {
     use controllers::api::api::{ApiResponse, failure, success};
     use controllers::sep31::{
-        get_sep31_info, create_sep31_transaction, get_sep31_transaction, update_sep31_transaction, set_sep31_transaction_callback,
+        get_sep31_info, create_sep31_transaction, get_sep31_transaction, update_sep31_transaction, set_sep31_transaction_callback, submit_sep31_payment,
+        register_sep31_customers,
     };
     use controllers::sep31::form::form::{
-        Sep31InfoForm, Sep31TransactionRequestForm, Sep31GetTransactionForm, Sep31UpdateTransactionForm, Sep31SetTransactionCallbackForm
+        Sep31InfoForm, Sep31TransactionRequestForm, Sep31GetTransactionForm, Sep31UpdateTransactionForm, Sep31SetTransactionCallbackForm, Sep31SubmitPaymentForm,
+        Sep31CustomersForm,
     };
     use rocket::{http::Status, post, response::status, serde::json::Json};
-    use services::sep31::sep31::{InfoResponse, TransactionResponse, Transaction};
+    use services::sep31::sep31::{CustomersResponse, InfoResponse, PaymentResponse, TransactionResponse, Transaction};
 
 
 
@@ -105,4 +107,42 @@ pub mod routes {
             Status::Ok,
         ))
     }
-}
\ No newline at end of file
+
+    #[post("/payment", data = "<form>")]
+    pub async fn submit_sep31_payment_route(
+        form: Json<Sep31SubmitPaymentForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<PaymentResponse>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let payment_response = submit_sep31_payment(form).await.map_err(|e| {
+            eprintln!("Error submitting SEP-31 payment: {:?}", e);
+            failure("Failed to submit SEP-31 payment", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "SEP-31 payment submitted successfully",
+            payment_response,
+            Status::Ok,
+        ))
+    }
+
+    #[post("/customers", data = "<form>")]
+    pub async fn register_sep31_customers_route(
+        form: Json<Sep31CustomersForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<CustomersResponse>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let customers_response = register_sep31_customers(form).await.map_err(|e| {
+            eprintln!("Error registering SEP-31 customers: {:?}", e);
+            failure("Failed to register SEP-31 customers", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "SEP-31 customers registered successfully",
+            customers_response,
+            Status::Ok,
+        ))
+    }
+}
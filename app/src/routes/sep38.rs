@@ -1,13 +1,18 @@
 pub mod routes {
     use controllers::api::api::{ApiResponse, failure, success};
     use controllers::sep38::form::form::{
-        Sep38GetQuoteForm, Sep38InfoForm, Sep38PriceForm, Sep38QuoteForm,
+        Sep38BestPriceForm, Sep38BestQuoteForm, Sep38GetQuoteForm, Sep38HistoryForm, Sep38InfoForm,
+        Sep38PriceForm, Sep38QuoteForm,
     };
     use controllers::sep38::{
-        create_sep38_quote, get_sep38_info, get_sep38_price, get_sep38_quote,
+        create_sep38_best_quote, create_sep38_quote, get_sep38_best_price, get_sep38_history,
+        get_sep38_info, get_sep38_price, get_sep38_quote,
+    };
+    use rocket::{get, http::Status, post, response::status, serde::json::Json};
+    use services::sep38::sep38::{
+        AssetInfo, BestPriceResponse, BestQuoteResponse, PriceHistory, PriceResponse,
+        QuoteResponse,
     };
-    use rocket::{http::Status, post, response::status, serde::json::Json};
-    use services::sep38::sep38::{AssetInfo, PriceResponse, QuoteResponse};
 
     #[post("/info", data = "<form>")]
     pub async fn get_sep38_info_route(
@@ -84,4 +89,61 @@ pub mod routes {
             Status::Ok,
         ))
     }
+
+    #[post("/best-price", data = "<form>")]
+    pub async fn get_sep38_best_price_route(
+        form: Json<Sep38BestPriceForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<BestPriceResponse>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let best_prices = get_sep38_best_price(form).await.map_err(|e| {
+            eprintln!("Error getting best SEP-38 price: {:?}", e);
+            failure("Failed to get best SEP-38 price", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Best SEP-38 prices retrieved successfully",
+            best_prices,
+            Status::Ok,
+        ))
+    }
+
+    #[post("/best-quote", data = "<form>")]
+    pub async fn create_sep38_best_quote_route(
+        form: Json<Sep38BestQuoteForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<BestQuoteResponse>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let best_quote = create_sep38_best_quote(form).await.map_err(|e| {
+            eprintln!("Error creating best SEP-38 quote: {:?}", e);
+            failure("Failed to create best SEP-38 quote", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "SEP-38 quote created with the best-priced anchor",
+            best_quote,
+            Status::Created,
+        ))
+    }
+
+    #[get("/history?<form..>")]
+    pub async fn get_sep38_history_route(
+        form: Sep38HistoryForm,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<PriceHistory>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let history = get_sep38_history(form).map_err(|e| {
+            eprintln!("Error getting SEP-38 price history: {:?}", e);
+            failure("Failed to get SEP-38 price history", Status::BadRequest)
+        })?;
+
+        Ok(success(
+            "SEP-38 price history retrieved successfully",
+            history,
+            Status::Ok,
+        ))
+    }
 }
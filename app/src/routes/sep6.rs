@@ -2,17 +2,57 @@ pub mod sep6 {
     use controllers::{
         api::api::{ApiResponse, failure, success},
         sep6::form::form::{
-            Sep6InfoForm, Sep6TransactionForm, Sep6TransactionsForm, Sep6WithdrawExchangeForm,
-            Sep6WithdrawForm,
+            Sep6EstimateForm, Sep6FeeForm, Sep6FormSchemaForm, Sep6InfoForm,
+            Sep6PendingActionsForm, Sep6RefundsForm, Sep6TransactionForm, Sep6TransactionUpdateForm,
+            Sep6TransactionsForm, Sep6WithdrawExchangeForm, Sep6WithdrawForm,
         },
         sep6::{
-            get_sep6_info, get_sep6_transaction, get_sep6_transactions, get_sep6_withdraw,
-            get_sep6_withdraw_exchange,
+            get_sep6_estimate, get_sep6_fee, get_sep6_form_schema, get_sep6_info,
+            get_sep6_pending_actions, get_sep6_refunds, update_sep6_transaction, get_sep6_transaction, get_sep6_transactions,
+            get_sep6_withdraw, get_sep6_withdraw_exchange,
         },
     };
     use models::sep6::Sep6Transaction;
-    use rocket::{http::Status, post, response::status, serde::json::Json};
-    use services::sep6::sep6::{InfoResponse, WithdrawResponse};
+    use rocket::{http::Status, patch, post, response::status, serde::json::Json};
+    use services::sep6::sep6::{
+        CustomerInfoStatus, FeeQuote, InfoResponse, PendingInfoUpdate, Sep6Error,
+        TransactionRefunds, WithdrawEstimate, WithdrawFormSchema, WithdrawResponse,
+    };
+
+    // The anchor's KYC answers go back as 403s, with missing fields listed in `errors` and
+    // the customer_info_status details in `data`
+    fn withdraw_failure(
+        e: &(dyn std::error::Error + 'static),
+        message: &str,
+    ) -> status::Custom<Json<ApiResponse<CustomerInfoStatus>>> {
+        let (status, body) = match e.downcast_ref::<Sep6Error>() {
+            Some(Sep6Error::CustomerInfoNeeded(fields)) => (
+                Status::Forbidden,
+                ApiResponse::validation_fail(fields.clone()),
+            ),
+            Some(Sep6Error::CustomerInfoStatus(info)) => (
+                Status::Forbidden,
+                ApiResponse::failure_with_data(
+                    format!("Customer information is {} with the anchor", info.status),
+                    info.clone(),
+                ),
+            ),
+            Some(Sep6Error::PaymentAlreadySubmitted(hash)) => (
+                Status::Conflict,
+                ApiResponse::failure(format!("Payment already submitted in {}", hash)),
+            ),
+            // Keep the hash in the response; the payment went out even though we lost track of it
+            Some(Sep6Error::PaymentNotRecorded { hash, .. }) => (
+                Status::InternalServerError,
+                ApiResponse::failure(format!("Payment submitted in {} but not recorded", hash)),
+            ),
+            _ => (
+                Status::InternalServerError,
+                ApiResponse::failure(message.to_string()),
+            ),
+        };
+        status::Custom(status, body)
+    }
 
     #[post("/info", data = "<form>")]
     pub async fn anchorinfo<'r>(
@@ -33,16 +73,38 @@ pub mod sep6 {
         ))
     }
 
+    #[post("/form-schema", data = "<form>")]
+    pub async fn form_schema<'r>(
+        form: Json<Sep6FormSchemaForm<'r>>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<Vec<WithdrawFormSchema>>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let response = get_sep6_form_schema(form).await.map_err(|e| {
+            eprintln!("Error building withdrawal form schema: {:?}", e);
+            failure(
+                "Failed to build withdrawal form schema",
+                Status::InternalServerError,
+            )
+        })?;
+
+        Ok(success(
+            "Withdrawal form schema fetched successfully",
+            response,
+            Status::Ok,
+        ))
+    }
+
     #[post("/withdraw", data = "<form>")]
     pub async fn withdraw<'r>(
         form: Json<Sep6WithdrawForm<'r>>,
     ) -> Result<
         status::Custom<Json<ApiResponse<WithdrawResponse>>>,
-        status::Custom<Json<ApiResponse<()>>>,
+        status::Custom<Json<ApiResponse<CustomerInfoStatus>>>,
     > {
         let response = get_sep6_withdraw(form).await.map_err(|e| {
             eprintln!("Error getting withdrawal info: {:?}", e);
-            failure("Failed to get withdrawal info", Status::InternalServerError)
+            withdraw_failure(e.as_ref(), "Failed to get withdrawal info")
         })?;
 
         Ok(success(
@@ -57,14 +119,11 @@ pub mod sep6 {
         form: Json<Sep6WithdrawExchangeForm<'r>>,
     ) -> Result<
         status::Custom<Json<ApiResponse<WithdrawResponse>>>,
-        status::Custom<Json<ApiResponse<()>>>,
+        status::Custom<Json<ApiResponse<CustomerInfoStatus>>>,
     > {
         let response = get_sep6_withdraw_exchange(form).await.map_err(|e| {
             eprintln!("Error getting exchange withdrawal info: {:?}", e);
-            failure(
-                "Failed to get exchange withdrawal info",
-                Status::InternalServerError,
-            )
+            withdraw_failure(e.as_ref(), "Failed to get exchange withdrawal info")
         })?;
 
         Ok(success(
@@ -74,6 +133,40 @@ pub mod sep6 {
         ))
     }
 
+    #[post("/fee", data = "<form>")]
+    pub async fn fee<'r>(
+        form: Json<Sep6FeeForm<'r>>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<FeeQuote>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let response = get_sep6_fee(form).await.map_err(|e| {
+            eprintln!("Error fetching fee: {:?}", e);
+            failure("Failed to fetch fee", Status::InternalServerError)
+        })?;
+
+        Ok(success("Fee fetched successfully", response, Status::Ok))
+    }
+
+    #[post("/estimate", data = "<form>")]
+    pub async fn estimate<'r>(
+        form: Json<Sep6EstimateForm<'r>>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<WithdrawEstimate>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let response = get_sep6_estimate(form).await.map_err(|e| {
+            eprintln!("Error estimating withdrawal: {:?}", e);
+            failure("Failed to estimate withdrawal", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Withdrawal estimate computed successfully",
+            response,
+            Status::Ok,
+        ))
+    }
+
     #[post("/transactions", data = "<form>")]
     pub async fn transactions<'r>(
         form: Json<Sep6TransactionsForm<'r>>,
@@ -117,5 +210,64 @@ pub mod sep6 {
         ))
     }
 
+    #[post("/pending-actions", data = "<form>")]
+    pub async fn pending_actions<'r>(
+        form: Json<Sep6PendingActionsForm<'r>>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<Vec<PendingInfoUpdate>>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let pending = get_sep6_pending_actions(form).await.map_err(|e| {
+            eprintln!("Error fetching pending actions: {:?}", e);
+            failure("Failed to fetch pending actions", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Pending actions fetched successfully",
+            pending,
+            Status::Ok,
+        ))
+    }
+
+    #[patch("/transaction", data = "<form>")]
+    pub async fn update_transaction<'r>(
+        form: Json<Sep6TransactionUpdateForm<'r>>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<Sep6Transaction>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let transaction = update_sep6_transaction(form).await.map_err(|e| {
+            eprintln!("Error updating transaction: {:?}", e);
+            match e.downcast_ref::<Sep6Error>() {
+                Some(Sep6Error::TransactionNotFound) => {
+                    failure("Transaction not found", Status::NotFound)
+                }
+                Some(Sep6Error::InvalidRequest(message)) => failure(message, Status::BadRequest),
+                _ => failure("Failed to update transaction", Status::InternalServerError),
+            }
+        })?;
+
+        Ok(success(
+            "Transaction updated successfully",
+            transaction,
+            Status::Ok,
+        ))
+    }
+
+    #[post("/refunds", data = "<form>")]
+    pub async fn refunds<'r>(
+        form: Json<Sep6RefundsForm<'r>>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<Vec<TransactionRefunds>>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let refunds = get_sep6_refunds(form).await.map_err(|e| {
+            eprintln!("Error fetching refunds: {:?}", e);
+            failure("Failed to fetch refunds", Status::InternalServerError)
+        })?;
+
+        Ok(success("Refunds fetched successfully", refunds, Status::Ok))
+    }
+
     // Add additional routes for deposit, info, and other SEP-6 endpoints as needed
 }
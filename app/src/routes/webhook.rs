@@ -0,0 +1,104 @@
+pub mod routes {
+    use controllers::api::api::{ApiResponse, failure, success};
+    use controllers::webhook::form::form::{
+        WebhookDeleteSubscriptionForm, WebhookDeliveriesForm, WebhookReplayForm,
+        WebhookSubscriptionForm,
+    };
+    use controllers::webhook::{
+        create_webhook_subscription, delete_webhook_subscription, get_webhook_deliveries,
+        get_webhook_subscriptions, replay_webhook_delivery,
+    };
+    use models::webhook::{WebhookDelivery, WebhookSubscription};
+    use rocket::{delete, get, http::Status, post, response::status, serde::json::Json};
+    use services::webhook::webhook::CreatedSubscription;
+
+    #[post("/subscription", data = "<form>")]
+    pub async fn create_subscription_route(
+        form: Json<WebhookSubscriptionForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<CreatedSubscription>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let subscription = create_webhook_subscription(form).await.map_err(|e| {
+            eprintln!("Error creating webhook subscription: {:?}", e);
+            failure("Failed to create webhook subscription", Status::BadRequest)
+        })?;
+
+        Ok(success(
+            "Webhook subscription created successfully",
+            subscription,
+            Status::Created,
+        ))
+    }
+
+    #[get("/subscriptions")]
+    pub async fn get_subscriptions_route() -> Result<
+        status::Custom<Json<ApiResponse<Vec<WebhookSubscription>>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let subscriptions = get_webhook_subscriptions().await.map_err(|e| {
+            eprintln!("Error fetching webhook subscriptions: {:?}", e);
+            failure("Failed to fetch webhook subscriptions", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Webhook subscriptions fetched successfully",
+            subscriptions,
+            Status::Ok,
+        ))
+    }
+
+    #[delete("/subscription", data = "<form>")]
+    pub async fn delete_subscription_route(
+        form: Json<WebhookDeleteSubscriptionForm>,
+    ) -> Result<status::Custom<Json<ApiResponse<()>>>, status::Custom<Json<ApiResponse<()>>>> {
+        delete_webhook_subscription(form).await.map_err(|e| {
+            eprintln!("Error deleting webhook subscription: {:?}", e);
+            failure("Failed to delete webhook subscription", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Webhook subscription deleted successfully",
+            (),
+            Status::Ok,
+        ))
+    }
+
+    #[post("/deliveries", data = "<form>")]
+    pub async fn get_deliveries_route(
+        form: Json<WebhookDeliveriesForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<Vec<WebhookDelivery>>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let deliveries = get_webhook_deliveries(form).await.map_err(|e| {
+            eprintln!("Error fetching webhook deliveries: {:?}", e);
+            failure("Failed to fetch webhook deliveries", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Webhook deliveries fetched successfully",
+            deliveries,
+            Status::Ok,
+        ))
+    }
+
+    #[post("/deliveries/replay", data = "<form>")]
+    pub async fn replay_delivery_route(
+        form: Json<WebhookReplayForm>,
+    ) -> Result<
+        status::Custom<Json<ApiResponse<WebhookDelivery>>>,
+        status::Custom<Json<ApiResponse<()>>>,
+    > {
+        let delivery = replay_webhook_delivery(form).await.map_err(|e| {
+            eprintln!("Error replaying webhook delivery: {:?}", e);
+            failure("Failed to replay webhook delivery", Status::InternalServerError)
+        })?;
+
+        Ok(success(
+            "Webhook delivery queued for replay",
+            delivery,
+            Status::Accepted,
+        ))
+    }
+}
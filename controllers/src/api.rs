@@ -30,6 +30,15 @@ pub mod api {
             })
         }
 
+        pub fn failure_with_data(message: String, data: T) -> Json<ApiResponse<T>> {
+            Json(ApiResponse {
+                success: false,
+                message: Some(message),
+                data: Some(data),
+                errors: None,
+            })
+        }
+
         pub fn validation_fail(errors: Vec<String>) -> Json<ApiResponse<T>> {
             Json(ApiResponse {
                 success: false,
@@ -52,6 +61,17 @@ pub mod api {
         status::Custom(status, ApiResponse::<()>::failure(message.to_string()))
     }
 
+    pub fn failure_with_data<T: Serialize>(
+        message: &str,
+        data: T,
+        status: Status,
+    ) -> status::Custom<Json<ApiResponse<T>>> {
+        status::Custom(
+            status,
+            ApiResponse::failure_with_data(message.to_string(), data),
+        )
+    }
+
     pub fn validation_fail(
         errors: Vec<String>,
         status: Status,
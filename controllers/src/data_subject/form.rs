@@ -0,0 +1,18 @@
+pub mod form {
+    use rocket::serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct DataSubjectForm {
+        pub account: String,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct DataErasureForm {
+        pub account: String,
+        /// Must be true; erasure can't be undone
+        #[serde(default)]
+        pub confirm: bool,
+    }
+}
@@ -0,0 +1,37 @@
+use rocket::serde::json::Json;
+
+use form::form::{DataErasureForm, DataSubjectForm};
+use models::data_subject::DataErasureLogEntry;
+use services::data_subject::data_subject::{
+    DataExport, DataSubjectError, ErasureReport, LogVerification, erase_account, export_account,
+    get_erasure_log, verify_erasure_log,
+};
+
+pub mod form;
+
+pub async fn export_data_subject(
+    data: Json<DataSubjectForm>,
+) -> Result<DataExport, Box<dyn std::error::Error>> {
+    Ok(export_account(&data.account).await?)
+}
+
+pub async fn erase_data_subject(
+    data: Json<DataErasureForm>,
+) -> Result<ErasureReport, DataSubjectError> {
+    if !data.confirm {
+        return Err(DataSubjectError::InvalidRequest(
+            "Erasure must be confirmed".to_string(),
+        ));
+    }
+    erase_account(&data.account).await
+}
+
+pub async fn get_data_subject_erasures(
+    data: Json<DataSubjectForm>,
+) -> Result<Vec<DataErasureLogEntry>, Box<dyn std::error::Error>> {
+    Ok(get_erasure_log(&data.account)?)
+}
+
+pub async fn verify_data_erasure_log() -> Result<LogVerification, Box<dyn std::error::Error>> {
+    Ok(verify_erasure_log()?)
+}
@@ -3,3 +3,7 @@ pub mod sep12;
 pub mod sep38;
 pub mod sep6;
 pub mod sep31;
+pub mod webhook;
+pub mod reconciliation;
+pub mod data_subject;
+pub mod rate_alert;
@@ -0,0 +1,42 @@
+pub mod form {
+    use models::money::Amount;
+    use rocket::serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct RateAlertForm {
+        pub account: String,
+        pub slug: String,
+        pub sell_asset: String,
+        pub buy_asset: String,
+        #[serde(default)]
+        pub sell_delivery_method: Option<String>,
+        #[serde(default)]
+        pub buy_delivery_method: Option<String>,
+        /// `above` or `below` the SEP-38 price (sell units per buy unit)
+        pub direction: String,
+        pub threshold: Amount,
+        pub sell_amount: Amount,
+        #[serde(default)]
+        pub expires_at: Option<String>,
+        #[serde(default)]
+        pub auto_execute: bool,
+        #[serde(default)]
+        pub funding_method: Option<String>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct RateAlertListForm {
+        pub account: String,
+        #[serde(default)]
+        pub status: Option<String>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct RateAlertCancelForm {
+        pub account: String,
+        pub alert_id: String,
+    }
+}
@@ -0,0 +1,41 @@
+use rocket::serde::json::Json;
+
+use form::form::{RateAlertCancelForm, RateAlertForm, RateAlertListForm};
+use models::sep38::Sep38RateAlert;
+use services::rate_alert::rate_alert::{
+    RateAlertRequest, cancel_alert, create_alert, list_alerts,
+};
+
+pub mod form;
+
+pub async fn create_rate_alert(
+    data: Json<RateAlertForm>,
+) -> Result<Sep38RateAlert, Box<dyn std::error::Error>> {
+    let data = data.into_inner();
+    Ok(create_alert(RateAlertRequest {
+        account: data.account,
+        slug: data.slug,
+        sell_asset: data.sell_asset,
+        buy_asset: data.buy_asset,
+        sell_delivery_method: data.sell_delivery_method,
+        buy_delivery_method: data.buy_delivery_method,
+        direction: data.direction,
+        threshold: data.threshold,
+        sell_amount: data.sell_amount,
+        expires_at: data.expires_at,
+        auto_execute: data.auto_execute,
+        funding_method: data.funding_method,
+    })?)
+}
+
+pub async fn get_rate_alerts(
+    data: Json<RateAlertListForm>,
+) -> Result<Vec<Sep38RateAlert>, Box<dyn std::error::Error>> {
+    Ok(list_alerts(&data.account, data.status.as_deref())?)
+}
+
+pub async fn cancel_rate_alert(
+    data: Json<RateAlertCancelForm>,
+) -> Result<Sep38RateAlert, Box<dyn std::error::Error>> {
+    Ok(cancel_alert(&data.account, &data.alert_id)?)
+}
@@ -0,0 +1,20 @@
+pub mod form {
+    use rocket::serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct ReconciliationReportForm {
+        #[serde(default)]
+        pub issue_type: Option<String>,
+        #[serde(default)]
+        pub include_resolved: Option<bool>,
+        #[serde(default)]
+        pub limit: Option<i64>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct ReconciliationResolveForm {
+        pub issue_id: String,
+    }
+}
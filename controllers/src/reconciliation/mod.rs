@@ -0,0 +1,32 @@
+use rocket::serde::json::Json;
+
+use form::form::{ReconciliationReportForm, ReconciliationResolveForm};
+use helpers::horizon::HttpHorizonClient;
+use models::reconciliation::ReconciliationIssue;
+use services::reconciliation::reconciliation::{
+    ReconciliationReport, ReconciliationSummary, get_report, reconcile_withdrawals,
+    resolve_issue,
+};
+
+pub mod form;
+
+pub async fn run_reconciliation() -> Result<ReconciliationSummary, Box<dyn std::error::Error>> {
+    let horizon = HttpHorizonClient::from_env()?;
+    Ok(reconcile_withdrawals(&horizon).await?)
+}
+
+pub async fn get_reconciliation_report(
+    data: Json<ReconciliationReportForm>,
+) -> Result<ReconciliationReport, Box<dyn std::error::Error>> {
+    Ok(get_report(
+        data.issue_type.as_deref(),
+        data.include_resolved.unwrap_or(false),
+        data.limit,
+    )?)
+}
+
+pub async fn resolve_reconciliation_issue(
+    data: Json<ReconciliationResolveForm>,
+) -> Result<ReconciliationIssue, Box<dyn std::error::Error>> {
+    Ok(resolve_issue(&data.issue_id)?)
+}
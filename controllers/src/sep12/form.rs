@@ -2,6 +2,7 @@ pub mod form {
 
     use rocket::form::FromForm;
     use rocket::fs::TempFile;
+    use rocket::request::{FromRequest, Outcome, Request};
     use rocket::serde::{Deserialize, Serialize};
 
     #[derive(Deserialize, Serialize)]
@@ -15,6 +16,20 @@ pub mod form {
         pub customer_type: Option<String>,
     }
 
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep12RequirementsForm {
+        pub slug: String,
+        pub account: String,
+        #[serde(default)]
+        pub memo: Option<String>,
+        #[serde(default)]
+        pub customer_type: Option<String>,
+        /// Send fields we already hold to the anchor before reporting what's left
+        #[serde(default)]
+        pub auto_submit: bool,
+    }
+
     #[derive(Deserialize, Serialize)]
     #[serde(crate = "rocket::serde")]
     pub struct Sep12DeleteKycForm {
@@ -24,6 +39,58 @@ pub mod form {
         pub memo: Option<String>,
     }
 
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep12CustomerFilesForm {
+        pub customer_id: String,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep12AnchorForm {
+        pub customer_id: String,
+        /// The anchor to share the profile with
+        pub slug: String,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep12VerificationForm {
+        pub slug: String,
+        pub account: String,
+        pub customer_id: String,
+        /// A SEP-9 field with a `_verification` suffix, e.g. `mobile_number_verification`
+        pub field: String,
+        pub value: String,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep12CallbackForm {
+        pub slug: String,
+        pub account: String,
+        pub customer_id: String,
+        #[serde(default)]
+        pub callback_url: Option<String>,
+    }
+
+    /// The anchor's `Signature` header on callback requests (`X-Stellar-Signature` on older anchors).
+    pub struct Sep12CallbackSignature(pub Option<String>);
+
+    #[rocket::async_trait]
+    impl<'r> FromRequest<'r> for Sep12CallbackSignature {
+        type Error = ();
+
+        async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+            let headers = request.headers();
+            let signature = headers
+                .get_one("Signature")
+                .or_else(|| headers.get_one("X-Stellar-Signature"))
+                .map(|s| s.to_string());
+            Outcome::Success(Sep12CallbackSignature(signature))
+        }
+    }
+
     #[derive(FromForm)]
     pub struct Sep12FileField<'v> {
         #[field(name = "unused")]
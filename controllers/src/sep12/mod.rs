@@ -1,11 +1,17 @@
-use form::form::{Sep12DeleteKycForm, Sep12FieldsAndFiles, Sep12KycStatusForm, Sep12UpdateKycForm};
+use form::form::{
+    Sep12AnchorForm, Sep12CallbackForm, Sep12CustomerFilesForm, Sep12DeleteKycForm, Sep12FieldsAndFiles,
+    Sep12KycStatusForm, Sep12RequirementsForm, Sep12UpdateKycForm, Sep12VerificationForm,
+};
 
 use rocket::serde::json::Json;
 use tokio::io::AsyncReadExt;
 
+use models::sep12::{Sep12AnchorSubmission, Sep12CustomerFile};
 use services::sep12::sep12::{
-    Customer, Sep12Error, create_account_kyc, delete_account_kyc, get_account_kyc,
-    update_account_kyc,
+    Customer, RequirementsDiff, Sep12Error, VerificationRequest, create_account_kyc,
+    delete_account_kyc, get_account_kyc, get_customer_file, get_requirements_diff,
+    handle_callback, list_customer_files, list_submissions, onboard_to_anchor, register_callback,
+    set_anchor_consent, submit_verification, update_account_kyc,
 };
 
 pub mod form;
@@ -92,3 +98,78 @@ pub async fn delete_sep12_kyc(
 ) -> Result<(), Box<dyn std::error::Error>> {
     Ok(delete_account_kyc(&data.slug, &data.account, data.memo.as_deref()).await?)
 }
+
+pub async fn get_sep12_customer_files(
+    data: Json<Sep12CustomerFilesForm>,
+) -> Result<Vec<Sep12CustomerFile>, Box<dyn std::error::Error>> {
+    Ok(list_customer_files(&data.customer_id)?)
+}
+
+pub async fn get_sep12_customer_file(
+    file_id: &str,
+) -> Result<(Sep12CustomerFile, Vec<u8>), Sep12Error> {
+    get_customer_file(file_id).await
+}
+
+pub async fn submit_sep12_verification(
+    data: Json<Sep12VerificationForm>,
+) -> Result<Customer, Sep12Error> {
+    let request = VerificationRequest {
+        id: data.customer_id.clone(),
+        field: data.field.clone(),
+        value: data.value.clone(),
+    };
+    submit_verification(&data.slug, &data.account, &request).await
+}
+
+pub async fn set_sep12_callback(
+    data: Json<Sep12CallbackForm>,
+) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(register_callback(
+        &data.slug,
+        &data.account,
+        &data.customer_id,
+        data.callback_url.as_deref(),
+    )
+    .await?)
+}
+
+pub async fn receive_sep12_callback(
+    slug: &str,
+    signature: Option<&str>,
+    body: &str,
+) -> Result<(), Sep12Error> {
+    handle_callback(slug, signature, body).await
+}
+
+pub async fn get_sep12_requirements(
+    data: Json<Sep12RequirementsForm>,
+) -> Result<RequirementsDiff, Box<dyn std::error::Error>> {
+    Ok(get_requirements_diff(
+        &data.slug,
+        &data.account,
+        data.memo.as_deref(),
+        data.customer_type.as_deref(),
+        data.auto_submit,
+    )
+    .await?)
+}
+
+pub async fn get_sep12_submissions(
+    data: Json<Sep12CustomerFilesForm>,
+) -> Result<Vec<Sep12AnchorSubmission>, Box<dyn std::error::Error>> {
+    Ok(list_submissions(&data.customer_id)?)
+}
+
+pub async fn set_sep12_consent(
+    data: Json<Sep12AnchorForm>,
+    granted: bool,
+) -> Result<Sep12AnchorSubmission, Sep12Error> {
+    set_anchor_consent(&data.customer_id, &data.slug, granted)
+}
+
+pub async fn onboard_sep12_customer(
+    data: Json<Sep12AnchorForm>,
+) -> Result<Sep12AnchorSubmission, Sep12Error> {
+    onboard_to_anchor(&data.customer_id, &data.slug).await
+}
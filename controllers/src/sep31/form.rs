@@ -1,4 +1,5 @@
 pub mod form {
+    use models::money::Amount;
     use rocket::serde::{Deserialize, Serialize};
 
     #[derive(Deserialize, Serialize)]
@@ -12,7 +13,7 @@ pub mod form {
     pub struct Sep31TransactionRequestForm {
         pub slug: String,
         pub account: String,
-        pub amount: String,
+        pub amount: Amount,
         pub asset_code: String,
         #[serde(default)]
         pub asset_issuer: Option<String>,
@@ -55,4 +56,38 @@ pub mod form {
         pub transaction_id: String,
         pub callback_url: String,
     }
-}
\ No newline at end of file
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep31SubmitPaymentForm {
+        pub slug: String,
+        pub account: String,
+        pub transaction_id: String,
+    }
+
+    #[derive(Deserialize, Serialize, Default)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep31CustomerForm {
+        #[serde(default)]
+        pub customer_id: Option<String>,
+        #[serde(default)]
+        pub customer_type: Option<String>,
+        #[serde(default)]
+        pub memo: Option<String>,
+        /// SEP-9 fields, e.g. {"first_name": "Jane"}
+        #[serde(default)]
+        pub fields: std::collections::BTreeMap<String, String>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep31CustomersForm {
+        pub slug: String,
+        pub account: String,
+        pub asset_code: String,
+        #[serde(default)]
+        pub sender: Sep31CustomerForm,
+        #[serde(default)]
+        pub receiver: Sep31CustomerForm,
+    }
+}
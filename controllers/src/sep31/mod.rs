@@ -1,7 +1,9 @@
+use helpers::stellartoml::AnchorService;
 use rocket::serde::json::Json;
 
-use services::sep31::sep31::{get_info, create_transaction, get_transaction, update_transaction, set_transaction_callback, InfoResponse, TransactionResponse, TransactionRequest, Transaction};
-use form::form::{ Sep31InfoForm, Sep31TransactionRequestForm, Sep31GetTransactionForm, Sep31UpdateTransactionForm, Sep31SetTransactionCallbackForm};
+use services::common::get_anchor_horizon_client;
+use services::sep31::sep31::{get_info, create_transaction, get_transaction, update_transaction, set_transaction_callback, submit_transaction_payment, register_customers, CustomerDetails, CustomersResponse, InfoResponse, PaymentResponse, TransactionResponse, TransactionRequest, Transaction};
+use form::form::{ Sep31InfoForm, Sep31TransactionRequestForm, Sep31GetTransactionForm, Sep31UpdateTransactionForm, Sep31SetTransactionCallbackForm, Sep31SubmitPaymentForm, Sep31CustomerForm, Sep31CustomersForm};
 pub mod form;
 
 pub async fn get_sep31_info(
@@ -19,7 +21,7 @@ pub async fn create_sep31_transaction(
         data.slug.clone().as_str(),
         data.account.clone().as_str(),
         TransactionRequest {
-            amount: data.amount.parse().unwrap(),
+            amount: data.amount.clone(),
             asset_code: data.asset_code.clone(),
             asset_issuer: data.asset_issuer.clone(),
             destination_asset: data.destination_asset.clone(),
@@ -65,3 +67,36 @@ pub async fn set_sep31_transaction_callback(
         data.callback_url.clone().as_str(),
     ).await?)
 }
+
+pub async fn submit_sep31_payment(
+    data: Json<Sep31SubmitPaymentForm>,
+) -> Result<PaymentResponse, Box<dyn std::error::Error>> {
+    let horizon = get_anchor_horizon_client(&AnchorService::new(), &data.slug).await?;
+    Ok(submit_transaction_payment(
+        &horizon,
+        data.slug.clone().as_str(),
+        data.account.clone().as_str(),
+        data.transaction_id.clone().as_str(),
+    ).await?)
+}
+
+fn customer_details(form: &Sep31CustomerForm) -> CustomerDetails {
+    CustomerDetails {
+        customer_id: form.customer_id.clone(),
+        customer_type: form.customer_type.clone(),
+        memo: form.memo.clone(),
+        fields: form.fields.clone().into_iter().collect(),
+    }
+}
+
+pub async fn register_sep31_customers(
+    data: Json<Sep31CustomersForm>,
+) -> Result<CustomersResponse, Box<dyn std::error::Error>> {
+    Ok(register_customers(
+        data.slug.clone().as_str(),
+        data.account.clone().as_str(),
+        data.asset_code.clone().as_str(),
+        customer_details(&data.sender),
+        customer_details(&data.receiver),
+    ).await?)
+}
@@ -1,5 +1,7 @@
 
     pub mod form {
+        use models::money::{Amount, empty_as_none};
+        use rocket::form::FromForm;
         use rocket::serde::{Deserialize, Serialize};
     
         #[derive(Deserialize, Serialize)]
@@ -8,8 +10,10 @@
             pub slug: String,
             pub sell_asset: String,
             pub buy_asset: String,
-            pub sell_amount: String,
-            pub buy_amount: String,
+            #[serde(default, deserialize_with = "empty_as_none")]
+            pub sell_amount: Option<Amount>,
+            #[serde(default, deserialize_with = "empty_as_none")]
+            pub buy_amount: Option<Amount>,
             #[serde(default)]
             pub sell_delivery_method: Option<String>,
             #[serde(default)]
@@ -26,10 +30,10 @@
             pub account: String,
             pub sell_asset: String,
             pub buy_asset: String,
-            #[serde(default)]
-            pub sell_amount: Option<String>,
-            #[serde(default)]
-            pub buy_amount: Option<String>,
+            #[serde(default, deserialize_with = "empty_as_none")]
+            pub sell_amount: Option<Amount>,
+            #[serde(default, deserialize_with = "empty_as_none")]
+            pub buy_amount: Option<Amount>,
             #[serde(default)]
             pub expire_after: Option<String>,
             #[serde(default)]
@@ -55,4 +59,52 @@
             pub slug: String,
         }
 
-}
\ No newline at end of file
+        #[derive(Deserialize, Serialize)]
+        #[serde(crate = "rocket::serde")]
+        pub struct Sep38BestPriceForm {
+            pub sell_asset: String,
+            pub buy_asset: String,
+            #[serde(default, deserialize_with = "empty_as_none")]
+            pub sell_amount: Option<Amount>,
+            #[serde(default, deserialize_with = "empty_as_none")]
+            pub buy_amount: Option<Amount>,
+            #[serde(default)]
+            pub sell_delivery_method: Option<String>,
+            #[serde(default)]
+            pub buy_delivery_method: Option<String>,
+            #[serde(default)]
+            pub country_code: Option<String>,
+            pub context: String,
+        }
+
+        #[derive(Deserialize, Serialize)]
+        #[serde(crate = "rocket::serde")]
+        pub struct Sep38BestQuoteForm {
+            pub account: String,
+            pub sell_asset: String,
+            pub buy_asset: String,
+            #[serde(default, deserialize_with = "empty_as_none")]
+            pub sell_amount: Option<Amount>,
+            #[serde(default, deserialize_with = "empty_as_none")]
+            pub buy_amount: Option<Amount>,
+            #[serde(default)]
+            pub expire_after: Option<String>,
+            #[serde(default)]
+            pub sell_delivery_method: Option<String>,
+            #[serde(default)]
+            pub buy_delivery_method: Option<String>,
+            #[serde(default)]
+            pub country_code: Option<String>,
+            pub context: String,
+        }
+
+        #[derive(FromForm)]
+        pub struct Sep38HistoryForm {
+            pub pair: String,
+            pub from: Option<String>,
+            pub to: Option<String>,
+            pub interval: Option<String>,
+            pub slug: Option<String>,
+        }
+
+}
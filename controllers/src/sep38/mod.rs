@@ -1,7 +1,7 @@
 use rocket::serde::json::Json;
 
-use services::sep38::sep38::{get_exchange_info,get_exchange_prices,quote_exchange_price,get_quote,AssetInfo,PriceResponse,QuoteResponse, };
-use form::form::{ Sep38PriceForm, Sep38QuoteForm, Sep38GetQuoteForm , Sep38InfoForm};
+use services::sep38::sep38::{get_exchange_info,get_exchange_prices,quote_exchange_price,get_quote,get_best_prices,quote_best_price,get_price_history,PriceHistory,AssetInfo,PriceResponse,QuoteResponse,PriceRequest,QuoteRequest,BestPriceResponse,BestQuoteResponse, };
+use form::form::{ Sep38PriceForm, Sep38QuoteForm, Sep38GetQuoteForm , Sep38InfoForm, Sep38BestPriceForm, Sep38BestQuoteForm, Sep38HistoryForm};
 pub mod form;
 
 pub async fn get_sep38_info(
@@ -53,4 +53,52 @@ pub async fn get_sep38_quote(
         data.account.clone(),
         data.quote_id.clone(),
     ).await?)
-}
\ No newline at end of file
+}
+
+pub async fn get_sep38_best_price(
+    data: Json<Sep38BestPriceForm>,
+) -> Result<BestPriceResponse, Box<dyn std::error::Error>> {
+    let data = data.into_inner();
+    Ok(get_best_prices(PriceRequest {
+        sell_asset: data.sell_asset,
+        buy_asset: data.buy_asset,
+        sell_amount: data.sell_amount,
+        buy_amount: data.buy_amount,
+        sell_delivery_method: data.sell_delivery_method,
+        buy_delivery_method: data.buy_delivery_method,
+        country_code: data.country_code,
+        context: data.context,
+    }).await?)
+}
+
+pub async fn create_sep38_best_quote(
+    data: Json<Sep38BestQuoteForm>,
+) -> Result<BestQuoteResponse, Box<dyn std::error::Error>> {
+    let data = data.into_inner();
+    Ok(quote_best_price(
+        data.account,
+        QuoteRequest {
+            sell_asset: data.sell_asset,
+            buy_asset: data.buy_asset,
+            sell_amount: data.sell_amount,
+            buy_amount: data.buy_amount,
+            expire_after: data.expire_after,
+            sell_delivery_method: data.sell_delivery_method,
+            buy_delivery_method: data.buy_delivery_method,
+            country_code: data.country_code,
+            context: data.context,
+        },
+    ).await?)
+}
+
+pub fn get_sep38_history(
+    data: Sep38HistoryForm,
+) -> Result<PriceHistory, Box<dyn std::error::Error>> {
+    Ok(get_price_history(
+        &data.pair,
+        data.from.as_deref(),
+        data.to.as_deref(),
+        data.interval.as_deref(),
+        data.slug.as_deref(),
+    )?)
+}
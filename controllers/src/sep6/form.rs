@@ -1,5 +1,7 @@
 pub mod form {
+    use models::money::Amount;
     use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
 
     #[derive(Deserialize, Serialize)]
     #[serde(crate = "rocket::serde")]
@@ -10,10 +12,13 @@ pub mod form {
         pub funding_method: &'r str,
         pub memo: Option<&'r str>,
         pub on_change_callback: Option<&'r str>,
-        pub amount: Option<&'r str>,
+        pub amount: Option<Amount>,
         pub country_code: Option<&'r str>,
         pub refund_memo: Option<&'r str>,
         pub refund_memo_type: Option<&'r str>,
+        pub asset_issuer: Option<&'r str>,
+        pub execute: Option<bool>,
+        pub collect_kyc: Option<bool>,
     }
 
     #[derive(Deserialize, Serialize)]
@@ -22,6 +27,14 @@ pub mod form {
         pub slug: &'r str,
     }
 
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep6FormSchemaForm<'r> {
+        pub slug: &'r str,
+        pub asset_code: Option<&'r str>,
+        pub funding_method: Option<&'r str>,
+    }
+
     #[derive(Deserialize, Serialize)]
     #[serde(crate = "rocket::serde")]
     pub struct Sep6WithdrawExchangeForm<'r> {
@@ -29,7 +42,7 @@ pub mod form {
         pub account: &'r str,
         pub source_asset: &'r str,
         pub destination_asset: &'r str,
-        pub amount: &'r str,
+        pub amount: Amount,
         pub quote_id: Option<&'r str>,
         pub funding_method: &'r str,
         pub memo: Option<&'r str>,
@@ -37,6 +50,33 @@ pub mod form {
         pub country_code: Option<&'r str>,
         pub refund_memo: Option<&'r str>,
         pub refund_memo_type: Option<&'r str>,
+        pub collect_kyc: Option<bool>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep6EstimateForm<'r> {
+        pub slug: &'r str,
+        pub account: &'r str,
+        pub asset_code: &'r str,
+        pub asset_issuer: Option<&'r str>,
+        pub amount: Amount,
+        pub funding_method: Option<&'r str>,
+        pub destination_asset: Option<&'r str>,
+        pub buy_delivery_method: Option<&'r str>,
+        pub country_code: Option<&'r str>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep6FeeForm<'r> {
+        pub slug: &'r str,
+        pub account: &'r str,
+        pub operation: &'r str,
+        #[serde(rename = "type")]
+        pub kind: Option<&'r str>,
+        pub asset_code: &'r str,
+        pub amount: Amount,
     }
 
     #[derive(Deserialize, Serialize)]
@@ -60,4 +100,26 @@ pub mod form {
         pub stellar_transaction_id: Option<&'r str>,
         pub external_transaction_id: Option<&'r str>,
     }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep6PendingActionsForm<'r> {
+        pub account: &'r str,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep6TransactionUpdateForm<'r> {
+        pub slug: &'r str,
+        pub account: &'r str,
+        pub id: &'r str,
+        pub fields: HashMap<String, String>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct Sep6RefundsForm<'r> {
+        pub account: &'r str,
+        pub transaction_id: Option<&'r str>,
+    }
 }
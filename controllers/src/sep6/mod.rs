@@ -1,11 +1,17 @@
 use form::form::{
-    Sep6InfoForm, Sep6TransactionForm, Sep6TransactionsForm, Sep6WithdrawExchangeForm,
-    Sep6WithdrawForm,
+    Sep6EstimateForm, Sep6FeeForm, Sep6FormSchemaForm, Sep6InfoForm, Sep6PendingActionsForm,
+    Sep6RefundsForm, Sep6TransactionForm, Sep6TransactionUpdateForm, Sep6TransactionsForm,
+    Sep6WithdrawExchangeForm, Sep6WithdrawForm,
 };
+use helpers::stellartoml::AnchorService;
 use rocket::serde::json::Json;
+use services::common::get_anchor_horizon_client;
 use services::sep6::sep6::{
-    InfoResponse, WithdrawResponse, get_anchor_info, get_transaction, get_transactions,
-    get_withdraw, get_withdraw_exchange,
+    FeeQuote, InfoResponse, PendingInfoUpdate, Sep6Error, TransactionRefunds, WithdrawEstimate,
+    WithdrawFormSchema, WithdrawResponse, estimate_withdrawal, get_anchor_info, get_fee,
+    get_transaction, get_transactions, get_withdraw, get_withdraw_exchange,
+    get_withdraw_form_schema, list_pending_info_updates, list_refunds, submit_customer_info, submit_withdraw_payment,
+    update_transaction_info,
 };
 
 pub mod form;
@@ -16,40 +22,125 @@ pub async fn get_sep6_info(
     Ok(get_anchor_info(data.slug).await?)
 }
 
+pub async fn get_sep6_form_schema(
+    data: Json<Sep6FormSchemaForm<'_>>,
+) -> Result<Vec<WithdrawFormSchema>, Box<dyn std::error::Error>> {
+    Ok(get_withdraw_form_schema(data.slug, data.asset_code, data.funding_method).await?)
+}
+
 pub async fn get_sep6_withdraw(
     data: Json<Sep6WithdrawForm<'_>>,
 ) -> Result<WithdrawResponse, Box<dyn std::error::Error>> {
-    Ok(get_withdraw(
+    let execute = data.execute.unwrap_or(false);
+    if execute && data.amount.is_none() {
+        return Err(Box::new(Sep6Error::InvalidRequest(
+            "amount is required to execute a withdrawal".to_string(),
+        )));
+    }
+
+    let horizon = get_anchor_horizon_client(&AnchorService::new(), data.slug).await?;
+
+    // When asked to, answer one customer_info_needed with the KYC we hold and retry
+    let mut kyc_sent = !data.collect_kyc.unwrap_or(false);
+    let mut response = loop {
+        match get_withdraw(
+            &horizon,
+            data.slug,
+            data.account,
+            data.asset_code,
+            data.asset_issuer,
+            data.funding_method,
+            data.memo,
+            data.on_change_callback,
+            data.amount.as_ref(),
+            data.country_code,
+            data.refund_memo,
+            data.refund_memo_type,
+        )
+        .await
+        {
+            Err(Sep6Error::CustomerInfoNeeded(fields)) if !kyc_sent => {
+                submit_customer_info(data.slug, data.account, data.memo, &fields).await?;
+                kyc_sent = true;
+            }
+            result => break result?,
+        }
+    };
+
+    if let (true, Some(amount)) = (execute, &data.amount) {
+        let hash = submit_withdraw_payment(
+            &horizon,
+            data.slug,
+            data.account,
+            data.asset_code,
+            data.asset_issuer,
+            amount,
+            &response,
+        )
+        .await?;
+        response.stellar_transaction_id = Some(hash);
+    }
+
+    Ok(response)
+}
+
+pub async fn get_sep6_withdraw_exchange(
+    data: Json<Sep6WithdrawExchangeForm<'_>>,
+) -> Result<WithdrawResponse, Box<dyn std::error::Error>> {
+    let mut kyc_sent = !data.collect_kyc.unwrap_or(false);
+    loop {
+        match get_withdraw_exchange(
+            data.slug,
+            data.account,
+            data.source_asset,
+            data.destination_asset,
+            &data.amount,
+            data.quote_id,
+            data.funding_method,
+            data.memo,
+            data.on_change_callback,
+            data.country_code,
+            data.refund_memo,
+            data.refund_memo_type,
+        )
+        .await
+        {
+            Err(Sep6Error::CustomerInfoNeeded(fields)) if !kyc_sent => {
+                submit_customer_info(data.slug, data.account, data.memo, &fields).await?;
+                kyc_sent = true;
+            }
+            result => return Ok(result?),
+        }
+    }
+}
+
+pub async fn get_sep6_estimate(
+    data: Json<Sep6EstimateForm<'_>>,
+) -> Result<WithdrawEstimate, Box<dyn std::error::Error>> {
+    Ok(estimate_withdrawal(
         data.slug,
         data.account,
         data.asset_code,
+        data.asset_issuer,
+        &data.amount,
         data.funding_method,
-        data.memo,
-        data.on_change_callback,
-        data.amount,
+        data.destination_asset,
+        data.buy_delivery_method,
         data.country_code,
-        data.refund_memo,
-        data.refund_memo_type,
     )
     .await?)
 }
 
-pub async fn get_sep6_withdraw_exchange(
-    data: Json<Sep6WithdrawExchangeForm<'_>>,
-) -> Result<WithdrawResponse, Box<dyn std::error::Error>> {
-    Ok(get_withdraw_exchange(
+pub async fn get_sep6_fee(
+    data: Json<Sep6FeeForm<'_>>,
+) -> Result<FeeQuote, Box<dyn std::error::Error>> {
+    Ok(get_fee(
         data.slug,
         data.account,
-        data.source_asset,
-        data.destination_asset,
-        data.amount,
-        data.quote_id,
-        data.funding_method,
-        data.memo,
-        data.on_change_callback,
-        data.country_code,
-        data.refund_memo,
-        data.refund_memo_type,
+        data.operation,
+        data.kind,
+        data.asset_code,
+        &data.amount,
     )
     .await?)
 }
@@ -82,3 +173,21 @@ pub async fn get_sep6_transaction(
     )
     .await?)
 }
+
+pub async fn get_sep6_pending_actions(
+    data: Json<Sep6PendingActionsForm<'_>>,
+) -> Result<Vec<PendingInfoUpdate>, Box<dyn std::error::Error>> {
+    Ok(list_pending_info_updates(data.account)?)
+}
+
+pub async fn update_sep6_transaction(
+    data: Json<Sep6TransactionUpdateForm<'_>>,
+) -> Result<models::sep6::Sep6Transaction, Box<dyn std::error::Error>> {
+    Ok(update_transaction_info(data.slug, data.account, data.id, &data.fields).await?)
+}
+
+pub async fn get_sep6_refunds(
+    data: Json<Sep6RefundsForm<'_>>,
+) -> Result<Vec<TransactionRefunds>, Box<dyn std::error::Error>> {
+    Ok(list_refunds(data.account, data.transaction_id)?)
+}
@@ -0,0 +1,35 @@
+pub mod form {
+    use rocket::serde::{Deserialize, Serialize};
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct WebhookSubscriptionForm {
+        pub url: String,
+        #[serde(default)]
+        pub secret: Option<String>,
+        pub event_types: Vec<String>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct WebhookDeleteSubscriptionForm {
+        pub subscription_id: String,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct WebhookDeliveriesForm {
+        #[serde(default)]
+        pub status: Option<String>,
+        #[serde(default)]
+        pub subscription_id: Option<String>,
+        #[serde(default)]
+        pub limit: Option<i64>,
+    }
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(crate = "rocket::serde")]
+    pub struct WebhookReplayForm {
+        pub delivery_id: String,
+    }
+}
@@ -0,0 +1,50 @@
+use rocket::serde::json::Json;
+
+use form::form::{
+    WebhookDeleteSubscriptionForm, WebhookDeliveriesForm, WebhookReplayForm,
+    WebhookSubscriptionForm,
+};
+use models::webhook::{WebhookDelivery, WebhookSubscription};
+use services::webhook::webhook::{
+    CreatedSubscription, create_subscription, delete_subscription, list_deliveries,
+    list_subscriptions, replay_delivery,
+};
+
+pub mod form;
+
+pub async fn create_webhook_subscription(
+    data: Json<WebhookSubscriptionForm>,
+) -> Result<CreatedSubscription, Box<dyn std::error::Error>> {
+    Ok(create_subscription(
+        &data.url,
+        data.secret.as_deref(),
+        data.event_types.clone(),
+    )?)
+}
+
+pub async fn get_webhook_subscriptions() -> Result<Vec<WebhookSubscription>, Box<dyn std::error::Error>>
+{
+    Ok(list_subscriptions()?)
+}
+
+pub async fn delete_webhook_subscription(
+    data: Json<WebhookDeleteSubscriptionForm>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    Ok(delete_subscription(&data.subscription_id)?)
+}
+
+pub async fn get_webhook_deliveries(
+    data: Json<WebhookDeliveriesForm>,
+) -> Result<Vec<WebhookDelivery>, Box<dyn std::error::Error>> {
+    Ok(list_deliveries(
+        data.status.as_deref(),
+        data.subscription_id.as_deref(),
+        data.limit,
+    )?)
+}
+
+pub async fn replay_webhook_delivery(
+    data: Json<WebhookReplayForm>,
+) -> Result<WebhookDelivery, Box<dyn std::error::Error>> {
+    Ok(replay_delivery(&data.delivery_id)?)
+}
@@ -1,11 +1,13 @@
 use crate::stellartoml::AnchorService;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 use chrono::Utc;
 use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use stellar_base::signature::Signature;
 use stellar_base::xdr::{XDRDeserialize, XDRSerialize};
-use stellar_base::{KeyPair, Network, Operation, transaction::TransactionEnvelope};
-use stellar_sdk::Keypair;
+use stellar_base::{KeyPair, Network, Operation, PublicKey, transaction::TransactionEnvelope};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -128,6 +130,58 @@ pub fn verify_jwt(token: &str, jwt_secret: &str, slug: &str) -> Result<String, A
     Ok(token_data.claims.sub)
 }
 
+/// Verifies the `Signature` header an anchor sends with callback requests.
+///
+/// The header looks like `t=<unix timestamp>, s=<base64 signature>`, where the signature
+/// covers `<timestamp>.<host>.<body>` and is made with the anchor's `SIGNING_KEY`.
+/// Requests older than `max_age_secs` are rejected to limit replays.
+pub fn verify_callback_signature(
+    signing_key: &str,
+    header: &str,
+    host: &str,
+    body: &str,
+    max_age_secs: i64,
+) -> Result<(), AuthError> {
+    let mut timestamp = None;
+    let mut signature = None;
+    for part in header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = Some(value),
+            Some(("s", value)) => signature = Some(value),
+            _ => {}
+        }
+    }
+
+    let (Some(timestamp), Some(signature)) = (timestamp, signature) else {
+        return Err(AuthError::AuthFailed("Malformed signature header".into()));
+    };
+
+    let signed_at: i64 = timestamp
+        .parse()
+        .map_err(|_| AuthError::AuthFailed("Invalid signature timestamp".into()))?;
+    if (Utc::now().timestamp() - signed_at).abs() > max_age_secs {
+        return Err(AuthError::AuthFailed("Signature expired".into()));
+    }
+
+    let signature = STANDARD
+        .decode(signature)
+        .map_err(|_| AuthError::AuthFailed("Signature is not base64".into()))?;
+
+    let anchor_key = PublicKey::from_account_id(signing_key)
+        .map_err(|e| AuthError::ConfigError(format!("Invalid anchor key: {}", e)))?;
+
+    // stellar_sdk's Keypair::verify reports any well-formed signature as valid, so check
+    // the ed25519 signature through stellar_base
+    let payload = format!("{}.{}.{}", timestamp, host, body);
+    let valid = Signature::from_slice(&signature)
+        .is_ok_and(|signature| signature.verify(&anchor_key, payload.as_bytes()));
+    if !valid {
+        return Err(AuthError::SignatureError);
+    }
+
+    Ok(())
+}
+
 fn verify_challenge_structure(
     challenge_xdr: &str,
     client_account: &str,
@@ -153,7 +207,7 @@ fn verify_challenge_structure(
         ));
     }
 
-    let server_keypair = Keypair::from_public_key(signing_key)
+    let server_key = PublicKey::from_account_id(signing_key)
         .map_err(|e| AuthError::ConfigError(format!("Invalid server key: {}", e)))?;
 
     let signature_data = transaction
@@ -163,7 +217,7 @@ fn verify_challenge_structure(
     let signature_valid = transaction
         .signatures()
         .iter()
-        .any(|sig| server_keypair.verify(&signature_data, sig.signature().as_bytes()));
+        .any(|sig| sig.signature().verify(&server_key, &signature_data));
 
     if !signature_valid {
         return Err(AuthError::SignatureError);
@@ -304,3 +358,119 @@ pub fn generate_service_jwt(
     )
     .map_err(AuthError::JwtError)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use stellar_sdk::Keypair;
+
+    const HOST: &str = "offramp.example.com";
+    const BODY: &str = r#"{"id":"customer-1","status":"ACCEPTED"}"#;
+
+    fn signed_header(anchor: &Keypair, timestamp: i64, host: &str, body: &str) -> String {
+        let payload = format!("{}.{}.{}", timestamp, host, body);
+        let signature = anchor.sign(payload.as_bytes()).unwrap();
+        format!("t={}, s={}", timestamp, STANDARD.encode(signature))
+    }
+
+    #[test]
+    fn accepts_a_fresh_valid_signature() {
+        let anchor = Keypair::random().unwrap();
+        let header = signed_header(&anchor, Utc::now().timestamp(), HOST, BODY);
+
+        assert!(verify_callback_signature(&anchor.public_key(), &header, HOST, BODY, 120).is_ok());
+    }
+
+    #[test]
+    fn rejects_an_expired_timestamp() {
+        let anchor = Keypair::random().unwrap();
+        let header = signed_header(&anchor, Utc::now().timestamp() - 121, HOST, BODY);
+
+        let result = verify_callback_signature(&anchor.public_key(), &header, HOST, BODY, 120);
+        assert!(matches!(result, Err(AuthError::AuthFailed(m)) if m == "Signature expired"));
+    }
+
+    #[test]
+    fn rejects_a_timestamp_from_the_future() {
+        let anchor = Keypair::random().unwrap();
+        let header = signed_header(&anchor, Utc::now().timestamp() + 600, HOST, BODY);
+
+        let result = verify_callback_signature(&anchor.public_key(), &header, HOST, BODY, 120);
+        assert!(matches!(result, Err(AuthError::AuthFailed(_))));
+    }
+
+    #[test]
+    fn rejects_a_signature_from_another_key() {
+        let anchor = Keypair::random().unwrap();
+        let impostor = Keypair::random().unwrap();
+        let header = signed_header(&impostor, Utc::now().timestamp(), HOST, BODY);
+
+        let result = verify_callback_signature(&anchor.public_key(), &header, HOST, BODY, 120);
+        assert!(matches!(result, Err(AuthError::SignatureError)));
+    }
+
+    #[test]
+    fn rejects_a_tampered_body_or_host() {
+        let anchor = Keypair::random().unwrap();
+        let header = signed_header(&anchor, Utc::now().timestamp(), HOST, BODY);
+        let tampered = BODY.replace("ACCEPTED", "REJECTED");
+
+        let key = anchor.public_key();
+        assert!(matches!(
+            verify_callback_signature(&key, &header, HOST, &tampered, 120),
+            Err(AuthError::SignatureError)
+        ));
+        assert!(matches!(
+            verify_callback_signature(&key, &header, "evil.example.com", BODY, 120),
+            Err(AuthError::SignatureError)
+        ));
+    }
+
+    #[test]
+    fn rejects_a_signature_with_a_replaced_timestamp() {
+        let anchor = Keypair::random().unwrap();
+        let now = Utc::now().timestamp();
+        let header = signed_header(&anchor, now - 60, HOST, BODY);
+        let replayed = header.replacen(&format!("t={}", now - 60), &format!("t={}", now), 1);
+
+        let result = verify_callback_signature(&anchor.public_key(), &replayed, HOST, BODY, 120);
+        assert!(matches!(result, Err(AuthError::SignatureError)));
+    }
+
+    #[test]
+    fn rejects_malformed_headers() {
+        let key = Keypair::random().unwrap().public_key();
+        let now = Utc::now().timestamp();
+
+        for header in [
+            String::new(),
+            "garbage".to_string(),
+            format!("t={}", now),
+            "s=c2lnbmF0dXJl".to_string(),
+        ] {
+            let result = verify_callback_signature(&key, &header, HOST, BODY, 120);
+            assert!(
+                matches!(result, Err(AuthError::AuthFailed(m)) if m == "Malformed signature header"),
+                "{header:?}"
+            );
+        }
+
+        let result = verify_callback_signature(&key, "t=soon, s=c2ln", HOST, BODY, 120);
+        assert!(
+            matches!(result, Err(AuthError::AuthFailed(m)) if m == "Invalid signature timestamp")
+        );
+
+        let header = format!("t={}, s=not base64!", now);
+        let result = verify_callback_signature(&key, &header, HOST, BODY, 120);
+        assert!(matches!(result, Err(AuthError::AuthFailed(m)) if m == "Signature is not base64"));
+    }
+
+    #[test]
+    fn rejects_an_invalid_anchor_key() {
+        let anchor = Keypair::random().unwrap();
+        let header = signed_header(&anchor, Utc::now().timestamp(), HOST, BODY);
+
+        let result = verify_callback_signature("GNOTAKEY", &header, HOST, BODY, 120);
+        assert!(matches!(result, Err(AuthError::ConfigError(_))));
+    }
+}
@@ -0,0 +1,333 @@
+use mockall::automock;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum HorizonError {
+    #[error("HTTP request failed: {0}")]
+    HttpError(#[from] reqwest::Error),
+    #[error("Account not found: {0}")]
+    AccountNotFound(String),
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+    #[error("Transaction not found: {0}")]
+    TransactionNotFound(String),
+    #[error("Transaction failed: {0}")]
+    TransactionFailed(String),
+    #[error("Invalid configuration: {0}")]
+    ConfigError(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitTransactionResponse {
+    pub hash: String,
+    pub ledger: Option<i64>,
+    pub successful: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountResponse {
+    pub id: String,
+    pub sequence: String,
+    pub balances: Vec<Balance>,
+    pub signers: Vec<Signer>,
+    pub thresholds: Thresholds,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Balance {
+    pub balance: String,
+    pub asset_type: String,
+    pub asset_code: Option<String>,
+    pub asset_issuer: Option<String>,
+    pub limit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Signer {
+    pub key: String,
+    pub weight: i32,
+    #[serde(rename = "type")]
+    pub signer_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Thresholds {
+    pub low_threshold: u8,
+    pub med_threshold: u8,
+    pub high_threshold: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentRecord {
+    pub id: String,
+    pub paging_token: String,
+    #[serde(rename = "type")]
+    pub payment_type: String,
+    pub created_at: String,
+    pub transaction_hash: String,
+    pub transaction_successful: Option<bool>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub amount: Option<String>,
+    pub asset_type: Option<String>,
+    pub asset_code: Option<String>,
+    pub asset_issuer: Option<String>,
+    pub transaction: Option<TransactionRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionRecord {
+    pub id: String,
+    pub hash: String,
+    pub ledger: i64,
+    pub created_at: String,
+    pub source_account: String,
+    pub fee_charged: Option<String>,
+    pub memo_type: Option<String>,
+    pub memo: Option<String>,
+    pub successful: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaymentsPage {
+    pub records: Vec<PaymentRecord>,
+    /// Paging token to pass back in for the following page, if any records were returned.
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Order {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Order::Asc => "asc",
+            Order::Desc => "desc",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    #[serde(rename = "_embedded")]
+    embedded: Embedded<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Embedded<T> {
+    records: Vec<T>,
+}
+
+impl AccountResponse {
+    pub fn sequence_number(&self) -> Result<i64, HorizonError> {
+        self.sequence
+            .parse::<i64>()
+            .map_err(|e| HorizonError::InvalidResponse(format!("Invalid sequence: {}", e)))
+    }
+
+    /// Finds the balance line for an asset; `native`/`XLM` without an issuer means lumens.
+    pub fn balance_for(&self, asset_code: &str, asset_issuer: Option<&str>) -> Option<&Balance> {
+        let native = asset_issuer.is_none()
+            && (asset_code.eq_ignore_ascii_case("native") || asset_code == "XLM");
+
+        self.balances.iter().find(|balance| {
+            if native {
+                return balance.asset_type == "native";
+            }
+            balance.asset_code.as_deref() == Some(asset_code)
+                && asset_issuer.is_none_or(|issuer| balance.asset_issuer.as_deref() == Some(issuer))
+        })
+    }
+
+    pub fn has_trustline(&self, asset_code: &str, asset_issuer: Option<&str>) -> bool {
+        self.balance_for(asset_code, asset_issuer).is_some()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct SubmitErrorResponse {
+    title: Option<String>,
+    extras: Option<SubmitErrorExtras>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubmitErrorExtras {
+    result_codes: Option<serde_json::Value>,
+}
+
+/// Operations this service needs from Horizon.
+///
+/// Services take a generic `H: HorizonClient` so tests can swap in `MockHorizonClient`.
+#[automock]
+#[allow(async_fn_in_trait)]
+pub trait HorizonClient {
+    async fn load_account(&self, account_id: &str) -> Result<AccountResponse, HorizonError>;
+
+    async fn fetch_account_sequence(&self, account_id: &str) -> Result<i64, HorizonError>;
+
+    /// Pages through payments for an account, starting after `cursor`.
+    async fn payments_for_account(
+        &self,
+        account_id: &str,
+        cursor: Option<String>,
+        limit: u32,
+        order: Order,
+    ) -> Result<PaymentsPage, HorizonError>;
+
+    async fn fetch_transaction(&self, hash: &str) -> Result<TransactionRecord, HorizonError>;
+
+    async fn submit_transaction(
+        &self,
+        envelope_xdr: &str,
+    ) -> Result<SubmitTransactionResponse, HorizonError>;
+}
+
+pub struct HttpHorizonClient {
+    client: Client,
+    base_url: String,
+}
+
+impl HttpHorizonClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Builds a client for `STELLAR_HORIZON_URL`.
+    pub fn from_env() -> Result<Self, HorizonError> {
+        dotenv::dotenv().ok();
+
+        let base_url = std::env::var("STELLAR_HORIZON_URL")
+            .map_err(|_| HorizonError::ConfigError("STELLAR_HORIZON_URL must be set".into()))?;
+
+        Ok(Self::new(&base_url))
+    }
+
+    /// Prefers the anchor's advertised `HORIZON_URL`, falling back to `STELLAR_HORIZON_URL`.
+    pub fn for_anchor(horizon_url: Option<&str>) -> Result<Self, HorizonError> {
+        match horizon_url {
+            Some(url) if !url.is_empty() => Ok(Self::new(url)),
+            _ => Self::from_env(),
+        }
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+        not_found: impl FnOnce() -> HorizonError,
+    ) -> Result<T, HorizonError> {
+        let response = self.client.get(url).query(query).send().await?;
+
+        if response.status() == 404 {
+            return Err(not_found());
+        }
+
+        if !response.status().is_success() {
+            return Err(HorizonError::InvalidResponse(format!(
+                "Status: {}",
+                response.status()
+            )));
+        }
+
+        Ok(response.json().await?)
+    }
+}
+
+impl HorizonClient for HttpHorizonClient {
+    async fn load_account(&self, account_id: &str) -> Result<AccountResponse, HorizonError> {
+        self.get_json(
+            &format!("{}/accounts/{}", self.base_url, account_id),
+            &[],
+            || HorizonError::AccountNotFound(account_id.to_string()),
+        )
+        .await
+    }
+
+    async fn fetch_account_sequence(&self, account_id: &str) -> Result<i64, HorizonError> {
+        self.load_account(account_id).await?.sequence_number()
+    }
+
+    async fn payments_for_account(
+        &self,
+        account_id: &str,
+        cursor: Option<String>,
+        limit: u32,
+        order: Order,
+    ) -> Result<PaymentsPage, HorizonError> {
+        let mut query = vec![
+            ("order", order.as_str().to_string()),
+            ("limit", limit.to_string()),
+            ("join", "transactions".to_string()),
+        ];
+        if let Some(cursor) = cursor {
+            query.push(("cursor", cursor));
+        }
+
+        let page: Page<PaymentRecord> = self
+            .get_json(
+                &format!("{}/accounts/{}/payments", self.base_url, account_id),
+                &query,
+                || HorizonError::AccountNotFound(account_id.to_string()),
+            )
+            .await?;
+
+        let next_cursor = page
+            .embedded
+            .records
+            .last()
+            .map(|record| record.paging_token.clone());
+
+        Ok(PaymentsPage {
+            records: page.embedded.records,
+            next_cursor,
+        })
+    }
+
+    async fn fetch_transaction(&self, hash: &str) -> Result<TransactionRecord, HorizonError> {
+        self.get_json(
+            &format!("{}/transactions/{}", self.base_url, hash),
+            &[],
+            || HorizonError::TransactionNotFound(hash.to_string()),
+        )
+        .await
+    }
+
+    async fn submit_transaction(
+        &self,
+        envelope_xdr: &str,
+    ) -> Result<SubmitTransactionResponse, HorizonError> {
+        let response = self
+            .client
+            .post(format!("{}/transactions", self.base_url))
+            .form(&[("tx", envelope_xdr)])
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            return Ok(response.json().await?);
+        }
+
+        let status = response.status();
+        let error: SubmitErrorResponse = response
+            .json()
+            .await
+            .map_err(|_| HorizonError::TransactionFailed(format!("Status: {}", status)))?;
+
+        let detail = error
+            .extras
+            .and_then(|extras| extras.result_codes)
+            .map(|codes| codes.to_string())
+            .or(error.title)
+            .unwrap_or_else(|| format!("Status: {}", status));
+
+        Err(HorizonError::TransactionFailed(detail))
+    }
+}
@@ -25,3 +25,31 @@ pub enum KeyPairError {
 pub fn generate_keypair(account_secret: &str) -> Result<KeyPair, KeyPairError> {
     KeyPair::from_secret_seed(account_secret).map_err(|_| KeyPairError::GenerationFailed)
 }
+
+/// The `G...` address for an account secret. Send and store this, never the secret itself.
+pub fn public_account_id(account_secret: &str) -> Result<String, KeyPairError> {
+    Ok(generate_keypair(account_secret)?.public_key().account_id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn public_account_id_matches_the_seed() {
+        let keypair = KeyPair::random().unwrap();
+        let seed = keypair.secret_key().secret_seed();
+
+        let account = public_account_id(&seed).unwrap();
+        assert_eq!(account, keypair.public_key().account_id());
+        assert!(account.starts_with('G'));
+        assert_eq!(account.len(), 56);
+    }
+
+    #[test]
+    fn public_account_id_rejects_public_keys() {
+        let account = KeyPair::random().unwrap().public_key().account_id();
+        assert!(public_account_id(&account).is_err());
+        assert!(public_account_id("").is_err());
+    }
+}
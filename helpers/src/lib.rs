@@ -8,3 +8,5 @@ pub mod stellartoml;
 pub mod auth;
 pub mod keypair;
 
+pub mod horizon;
+pub mod payment;
@@ -0,0 +1,195 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use stellar_base::amount::{Amount, Stroops};
+use stellar_base::time_bounds::TimeBounds;
+use stellar_base::transaction::{MIN_BASE_FEE, Transaction};
+use stellar_base::xdr::XDRSerialize;
+use stellar_base::{Asset, KeyPair, Memo, Network, Operation, PublicKey};
+use std::str::FromStr;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PaymentError {
+    #[error("Invalid destination: {0}")]
+    InvalidDestination(String),
+    #[error("Invalid asset: {0}")]
+    InvalidAsset(String),
+    #[error("Invalid amount: {0}")]
+    InvalidAmount(String),
+    #[error("Invalid memo: {0}")]
+    InvalidMemo(String),
+    #[error("Transaction build failed: {0}")]
+    BuildFailed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct PaymentDetails<'a> {
+    pub destination: &'a str,
+    pub asset_code: &'a str,
+    pub asset_issuer: Option<&'a str>,
+    pub amount: &'a str,
+    pub memo_type: Option<&'a str>,
+    pub memo: Option<&'a str>,
+}
+
+/// Resolves an asset code/issuer pair, treating `native` (or an issuer-less `XLM`) as lumens.
+pub fn parse_asset(asset_code: &str, asset_issuer: Option<&str>) -> Result<Asset, PaymentError> {
+    match asset_issuer {
+        None if asset_code.eq_ignore_ascii_case("native") || asset_code == "XLM" => {
+            Ok(Asset::new_native())
+        }
+        None => Err(PaymentError::InvalidAsset(format!(
+            "issuer required for {}",
+            asset_code
+        ))),
+        Some(issuer) => {
+            let issuer = PublicKey::from_account_id(issuer)
+                .map_err(|e| PaymentError::InvalidAsset(e.to_string()))?;
+            Asset::new_credit(asset_code, issuer)
+                .map_err(|e| PaymentError::InvalidAsset(e.to_string()))
+        }
+    }
+}
+
+/// Builds a memo from the `memo_type`/`memo` pair returned by an anchor.
+///
+/// Hash memos are expected base64 encoded, as SEP-6 and SEP-31 specify; hex is
+/// accepted too since some anchors send it.
+pub fn parse_memo(memo_type: Option<&str>, memo: Option<&str>) -> Result<Memo, PaymentError> {
+    let memo = match memo {
+        Some(m) if !m.is_empty() => m,
+        _ => return Ok(Memo::new_none()),
+    };
+
+    match memo_type.unwrap_or("text") {
+        "text" => Memo::new_text(memo).map_err(|e| PaymentError::InvalidMemo(e.to_string())),
+        "id" => memo
+            .parse::<u64>()
+            .map(Memo::new_id)
+            .map_err(|e| PaymentError::InvalidMemo(e.to_string())),
+        "hash" => {
+            let bytes = match hex::decode(memo) {
+                Ok(bytes) if bytes.len() == 32 => bytes,
+                _ => STANDARD
+                    .decode(memo)
+                    .map_err(|e| PaymentError::InvalidMemo(e.to_string()))?,
+            };
+            if bytes.len() != 32 {
+                return Err(PaymentError::InvalidMemo(format!(
+                    "hash memo must be 32 bytes, got {}",
+                    bytes.len()
+                )));
+            }
+            Memo::new_hash(&bytes).map_err(|e| PaymentError::InvalidMemo(e.to_string()))
+        }
+        other => Err(PaymentError::InvalidMemo(format!(
+            "unsupported memo type {}",
+            other
+        ))),
+    }
+}
+
+/// Builds and signs a single-payment transaction, returning the envelope as base64 XDR.
+///
+/// `current_sequence` is the source account's sequence as reported by Horizon.
+pub fn build_payment_envelope(
+    keypair: &KeyPair,
+    current_sequence: i64,
+    network: &Network,
+    details: &PaymentDetails<'_>,
+) -> Result<String, PaymentError> {
+    let destination = PublicKey::from_account_id(details.destination)
+        .map_err(|e| PaymentError::InvalidDestination(e.to_string()))?;
+    let asset = parse_asset(details.asset_code, details.asset_issuer)?;
+    let amount = Amount::from_str(details.amount)
+        .map_err(|e| PaymentError::InvalidAmount(e.to_string()))?;
+    let stroops =
+        Stroops::try_from(amount).map_err(|e| PaymentError::InvalidAmount(e.to_string()))?;
+    let memo = parse_memo(details.memo_type, details.memo)?;
+
+    let payment = Operation::new_payment()
+        .with_destination(destination)
+        .with_amount(stroops)
+        .map_err(|e| PaymentError::InvalidAmount(e.to_string()))?
+        .with_asset(asset)
+        .build()
+        .map_err(|e| PaymentError::BuildFailed(e.to_string()))?;
+
+    let mut transaction =
+        Transaction::builder(keypair.public_key().clone(), current_sequence + 1, MIN_BASE_FEE)
+            .with_time_bounds(TimeBounds::valid_for(chrono::Duration::minutes(5)))
+            .with_memo(memo)
+            .add_operation(payment)
+            .into_transaction()
+            .map_err(|e| PaymentError::BuildFailed(e.to_string()))?;
+
+    transaction
+        .sign(keypair, network)
+        .map_err(|e| PaymentError::BuildFailed(format!("Signing failed: {}", e)))?;
+
+    transaction
+        .into_envelope()
+        .xdr_base64()
+        .map_err(|e| PaymentError::BuildFailed(format!("XDR serialization failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: [u8; 32] = [0xab; 32];
+
+    #[test]
+    fn parse_memo_without_a_value_is_none() {
+        assert!(parse_memo(None, None).unwrap().is_none());
+        assert!(parse_memo(Some("hash"), Some("")).unwrap().is_none());
+    }
+
+    #[test]
+    fn parse_memo_defaults_to_text() {
+        let memo = parse_memo(None, Some("invoice 42")).unwrap();
+        assert_eq!(memo.as_text(), Some("invoice 42"));
+    }
+
+    #[test]
+    fn parse_memo_reads_ids() {
+        assert_eq!(
+            parse_memo(Some("id"), Some("12345")).unwrap().as_id(),
+            Some(&12345)
+        );
+        assert!(parse_memo(Some("id"), Some("-1")).is_err());
+        assert!(parse_memo(Some("id"), Some("abc")).is_err());
+    }
+
+    #[test]
+    fn parse_memo_decodes_hex_hashes() {
+        let memo = parse_memo(Some("hash"), Some(&hex::encode(HASH))).unwrap();
+        assert_eq!(memo.as_hash(), Some(&HASH));
+    }
+
+    #[test]
+    fn parse_memo_decodes_base64_hashes() {
+        let memo = parse_memo(Some("hash"), Some(&STANDARD.encode(HASH))).unwrap();
+        assert_eq!(memo.as_hash(), Some(&HASH));
+    }
+
+    #[test]
+    fn parse_memo_prefers_hex_when_both_decode() {
+        // 64 hex digits are also valid base64, which would decode to 48 bytes
+        let hex_memo = "00".repeat(32);
+        let memo = parse_memo(Some("hash"), Some(&hex_memo)).unwrap();
+        assert_eq!(memo.as_hash(), Some(&[0u8; 32]));
+    }
+
+    #[test]
+    fn parse_memo_rejects_hashes_that_are_not_32_bytes() {
+        assert!(parse_memo(Some("hash"), Some(&hex::encode([1u8; 16]))).is_err());
+        assert!(parse_memo(Some("hash"), Some(&STANDARD.encode([1u8; 33]))).is_err());
+        assert!(parse_memo(Some("hash"), Some("not a hash!")).is_err());
+    }
+
+    #[test]
+    fn parse_memo_rejects_unknown_types() {
+        assert!(parse_memo(Some("return"), Some("x")).is_err());
+    }
+}
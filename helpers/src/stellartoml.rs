@@ -175,12 +175,25 @@ impl AnchorService {
         anchors.insert(slug, config);
     }
 
-    pub async fn get_anchor(&self, slug: &str) -> Result<AnchorConfig, AnchorError> {
+    fn anchor_homepages() -> HashMap<String, String> {
         let mut anchor_homepage_map: HashMap<String, String> = HashMap::new();
 
         // Set default supported anchors
         anchor_homepage_map.insert("mykobo".to_string(), "https://www.mykobo.co".to_string());
 
+        anchor_homepage_map
+    }
+
+    /// Slugs of every anchor `get_anchor` can resolve, sorted for stable iteration.
+    pub fn supported_slugs(&self) -> Vec<String> {
+        let mut slugs: Vec<String> = Self::anchor_homepages().into_keys().collect();
+        slugs.sort();
+        slugs
+    }
+
+    pub async fn get_anchor(&self, slug: &str) -> Result<AnchorConfig, AnchorError> {
+        let anchor_homepage_map = Self::anchor_homepages();
+
         let current_anchor = anchor_homepage_map
             .get(slug)
             .cloned()
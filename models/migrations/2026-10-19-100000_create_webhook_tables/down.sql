@@ -0,0 +1,9 @@
+DROP INDEX IF EXISTS offramp_service.idx_webhook_deliveries_subscription;
+
+DROP INDEX IF EXISTS offramp_service.idx_webhook_deliveries_due;
+
+DROP TABLE IF EXISTS offramp_service.webhook_deliveries;
+
+DROP INDEX IF EXISTS offramp_service.idx_webhook_subscriptions_active;
+
+DROP TABLE IF EXISTS offramp_service.webhook_subscriptions;
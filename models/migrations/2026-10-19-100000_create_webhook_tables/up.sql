@@ -0,0 +1,30 @@
+CREATE TABLE offramp_service.webhook_subscriptions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    event_types TEXT[] NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
+
+CREATE INDEX idx_webhook_subscriptions_active ON offramp_service.webhook_subscriptions (active);
+
+CREATE TABLE offramp_service.webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    subscription_id UUID NOT NULL REFERENCES offramp_service.webhook_subscriptions (id) ON DELETE CASCADE,
+    event_type TEXT NOT NULL,
+    payload JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    last_error TEXT,
+    last_response_status INTEGER,
+    delivered_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
+
+CREATE INDEX idx_webhook_deliveries_due ON offramp_service.webhook_deliveries (status, next_attempt_at);
+
+CREATE INDEX idx_webhook_deliveries_subscription ON offramp_service.webhook_deliveries (subscription_id);
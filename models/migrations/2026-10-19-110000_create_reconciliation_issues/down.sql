@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS offramp_service.idx_reconciliation_issues_resolved;
+
+DROP INDEX IF EXISTS offramp_service.idx_reconciliation_issues_unique;
+
+DROP TABLE IF EXISTS offramp_service.reconciliation_issues;
@@ -0,0 +1,24 @@
+CREATE TABLE offramp_service.reconciliation_issues (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    transaction_id TEXT NOT NULL,
+    issue_type TEXT NOT NULL,
+    stellar_transaction_id TEXT NOT NULL,
+    anchor_account TEXT NOT NULL,
+    anchor_status TEXT NOT NULL,
+    expected_amount NUMERIC,
+    paid_amount NUMERIC,
+    asset TEXT,
+    details TEXT,
+    resolved BOOLEAN NOT NULL DEFAULT FALSE,
+    resolved_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
+
+CREATE UNIQUE INDEX idx_reconciliation_issues_unique ON offramp_service.reconciliation_issues (
+    transaction_id,
+    issue_type,
+    stellar_transaction_id
+);
+
+CREATE INDEX idx_reconciliation_issues_resolved ON offramp_service.reconciliation_issues (resolved);
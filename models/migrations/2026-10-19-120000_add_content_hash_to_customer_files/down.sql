@@ -0,0 +1 @@
+ALTER TABLE offramp_service.sep12_customer_files DROP COLUMN IF EXISTS content_hash;
@@ -0,0 +1 @@
+ALTER TABLE offramp_service.sep12_customer_files ADD COLUMN content_hash TEXT;
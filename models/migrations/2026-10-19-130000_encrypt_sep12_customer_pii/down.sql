@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS offramp_service.idx_sep12_customers_phone_blind_index;
+
+DROP INDEX IF EXISTS offramp_service.idx_sep12_customers_email_blind_index;
+
+ALTER TABLE offramp_service.sep12_customers
+    DROP COLUMN IF EXISTS phone_blind_index,
+    DROP COLUMN IF EXISTS email_blind_index;
@@ -0,0 +1,20 @@
+-- Ciphertext is longer than the original VARCHAR(255) limits allow
+ALTER TABLE offramp_service.sep12_customers
+    ALTER COLUMN first_name TYPE TEXT,
+    ALTER COLUMN last_name TYPE TEXT,
+    ALTER COLUMN email TYPE TEXT,
+    ALTER COLUMN phone TYPE TEXT,
+    ALTER COLUMN date_of_birth TYPE TEXT,
+    ALTER COLUMN address_street TYPE TEXT,
+    ALTER COLUMN address_city TYPE TEXT,
+    ALTER COLUMN address_state TYPE TEXT,
+    ALTER COLUMN address_postal_code TYPE TEXT,
+    ALTER COLUMN address_country TYPE TEXT;
+
+ALTER TABLE offramp_service.sep12_customers
+    ADD COLUMN email_blind_index TEXT,
+    ADD COLUMN phone_blind_index TEXT;
+
+CREATE INDEX idx_sep12_customers_email_blind_index ON offramp_service.sep12_customers (email_blind_index);
+
+CREATE INDEX idx_sep12_customers_phone_blind_index ON offramp_service.sep12_customers (phone_blind_index);
@@ -0,0 +1 @@
+ALTER TABLE offramp_service.sep12_customers DROP COLUMN IF EXISTS sep9_fields;
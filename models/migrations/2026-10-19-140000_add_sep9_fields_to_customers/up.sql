@@ -0,0 +1 @@
+ALTER TABLE offramp_service.sep12_customers ADD COLUMN sep9_fields TEXT;
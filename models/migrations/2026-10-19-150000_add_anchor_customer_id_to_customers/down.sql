@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS offramp_service.idx_sep12_customers_anchor_customer_id;
+ALTER TABLE offramp_service.sep12_customers DROP COLUMN IF EXISTS anchor_customer_id;
+ALTER TABLE offramp_service.sep12_customers DROP COLUMN IF EXISTS anchor_slug;
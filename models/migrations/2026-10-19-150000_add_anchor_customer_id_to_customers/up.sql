@@ -0,0 +1,5 @@
+ALTER TABLE offramp_service.sep12_customers ADD COLUMN anchor_slug VARCHAR(255);
+ALTER TABLE offramp_service.sep12_customers ADD COLUMN anchor_customer_id VARCHAR(255);
+
+CREATE INDEX idx_sep12_customers_anchor_customer_id
+    ON offramp_service.sep12_customers (anchor_slug, anchor_customer_id);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS offramp_service.sep12_anchor_submissions;
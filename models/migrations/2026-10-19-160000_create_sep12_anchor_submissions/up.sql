@@ -0,0 +1,29 @@
+CREATE TABLE offramp_service.sep12_anchor_submissions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    customer_id UUID NOT NULL REFERENCES offramp_service.sep12_customers (id) ON DELETE CASCADE,
+    anchor_slug VARCHAR(255) NOT NULL,
+    anchor_customer_id VARCHAR(255),
+    status VARCHAR(255) NOT NULL,
+    submitted_fields TEXT NOT NULL DEFAULT '[]',
+    consent_given_at TIMESTAMP,
+    consent_revoked_at TIMESTAMP,
+    last_synced_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
+
+CREATE UNIQUE INDEX idx_sep12_anchor_submissions_customer_anchor ON offramp_service.sep12_anchor_submissions (
+    customer_id,
+    anchor_slug
+);
+
+CREATE INDEX idx_sep12_anchor_submissions_anchor_customer_id ON offramp_service.sep12_anchor_submissions (
+    anchor_slug,
+    anchor_customer_id
+);
+
+-- Customers created before this table existed were submitted to one anchor
+INSERT INTO offramp_service.sep12_anchor_submissions (customer_id, anchor_slug, anchor_customer_id, status, last_synced_at)
+SELECT id, anchor_slug, anchor_customer_id, status, updated_at
+FROM offramp_service.sep12_customers
+WHERE anchor_slug IS NOT NULL;
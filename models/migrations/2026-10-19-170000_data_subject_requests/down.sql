@@ -0,0 +1,7 @@
+DROP TABLE IF EXISTS offramp_service.data_erasure_log;
+
+DROP INDEX IF EXISTS offramp_service.idx_sep38_quotes_account;
+DROP INDEX IF EXISTS offramp_service.idx_sep6_transactions_account;
+
+ALTER TABLE offramp_service.sep38_quotes DROP COLUMN IF EXISTS account;
+ALTER TABLE offramp_service.sep6_transactions DROP COLUMN IF EXISTS account;
@@ -0,0 +1,16 @@
+ALTER TABLE offramp_service.sep6_transactions ADD COLUMN account VARCHAR(56);
+ALTER TABLE offramp_service.sep38_quotes ADD COLUMN account VARCHAR(56);
+
+CREATE INDEX idx_sep6_transactions_account ON offramp_service.sep6_transactions (account);
+CREATE INDEX idx_sep38_quotes_account ON offramp_service.sep38_quotes (account);
+
+-- Append-only; each entry_hash covers the previous entry's hash so edits break the chain
+CREATE TABLE offramp_service.data_erasure_log (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    sequence BIGINT NOT NULL UNIQUE,
+    account_hash TEXT NOT NULL,
+    details TEXT NOT NULL,
+    previous_hash TEXT,
+    entry_hash TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
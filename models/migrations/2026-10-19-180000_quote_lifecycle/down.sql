@@ -0,0 +1,7 @@
+DROP INDEX IF EXISTS offramp_service.idx_sep38_quotes_status_expires_at;
+DROP INDEX IF EXISTS offramp_service.idx_sep38_quotes_original_quote_id;
+
+ALTER TABLE offramp_service.sep38_quotes
+    DROP COLUMN IF EXISTS used_by,
+    DROP COLUMN IF EXISTS used_at,
+    DROP COLUMN IF EXISTS status;
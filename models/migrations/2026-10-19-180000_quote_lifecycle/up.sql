@@ -0,0 +1,10 @@
+ALTER TABLE offramp_service.sep38_quotes
+    ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'active',
+    ADD COLUMN used_at TIMESTAMP,
+    ADD COLUMN used_by VARCHAR(20);
+
+UPDATE offramp_service.sep38_quotes SET status = 'used', used_at = created_at WHERE transaction_id IS NOT NULL;
+UPDATE offramp_service.sep38_quotes SET status = 'expired' WHERE status = 'active' AND expires_at <= NOW ();
+
+CREATE INDEX idx_sep38_quotes_original_quote_id ON offramp_service.sep38_quotes (original_quote_id);
+CREATE INDEX idx_sep38_quotes_status_expires_at ON offramp_service.sep38_quotes (status, expires_at);
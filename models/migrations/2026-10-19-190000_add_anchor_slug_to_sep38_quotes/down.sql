@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS offramp_service.idx_sep38_quotes_anchor_slug;
+
+ALTER TABLE offramp_service.sep38_quotes DROP COLUMN IF EXISTS anchor_slug;
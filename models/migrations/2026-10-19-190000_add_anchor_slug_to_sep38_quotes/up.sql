@@ -0,0 +1,3 @@
+ALTER TABLE offramp_service.sep38_quotes ADD COLUMN anchor_slug VARCHAR(255);
+
+CREATE INDEX idx_sep38_quotes_anchor_slug ON offramp_service.sep38_quotes (anchor_slug, sell_asset, buy_asset);
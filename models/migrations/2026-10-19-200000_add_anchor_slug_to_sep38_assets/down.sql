@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS offramp_service.idx_sep38_assets_anchor_slug_asset;
+
+ALTER TABLE offramp_service.sep38_assets DROP COLUMN IF EXISTS anchor_slug;
@@ -0,0 +1,5 @@
+-- Nothing wrote to sep38_assets before this, so existing rows need no backfill
+ALTER TABLE offramp_service.sep38_assets ADD COLUMN anchor_slug VARCHAR(255) NOT NULL DEFAULT '';
+ALTER TABLE offramp_service.sep38_assets ALTER COLUMN anchor_slug DROP DEFAULT;
+
+CREATE UNIQUE INDEX idx_sep38_assets_anchor_slug_asset ON offramp_service.sep38_assets (anchor_slug, asset);
@@ -0,0 +1,17 @@
+CREATE TABLE offramp_service.sep38_price_samples (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    anchor_slug VARCHAR(255) NOT NULL,
+    sell_asset VARCHAR(255) NOT NULL,
+    buy_asset VARCHAR(255) NOT NULL,
+    sell_delivery_method VARCHAR(255),
+    buy_delivery_method VARCHAR(255),
+    sell_amount NUMERIC NOT NULL,
+    buy_amount NUMERIC NOT NULL,
+    price NUMERIC NOT NULL,
+    total_price NUMERIC NOT NULL,
+    fee_total NUMERIC NOT NULL,
+    fee_asset VARCHAR(255) NOT NULL,
+    sampled_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
+
+CREATE INDEX idx_sep38_price_samples_pair_sampled_at ON offramp_service.sep38_price_samples (sell_asset, buy_asset, sampled_at);
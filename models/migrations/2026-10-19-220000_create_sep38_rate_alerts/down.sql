@@ -0,0 +1 @@
+DROP TABLE IF EXISTS offramp_service.sep38_rate_alerts;
@@ -0,0 +1,27 @@
+CREATE TABLE offramp_service.sep38_rate_alerts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid (),
+    account VARCHAR(56) NOT NULL,
+    anchor_slug VARCHAR(255) NOT NULL,
+    sell_asset VARCHAR(255) NOT NULL,
+    buy_asset VARCHAR(255) NOT NULL,
+    sell_delivery_method VARCHAR(255),
+    buy_delivery_method VARCHAR(255),
+    -- 'above' or 'below'; compared against the SEP-38 price (sell units per buy unit)
+    direction VARCHAR(10) NOT NULL,
+    threshold NUMERIC NOT NULL,
+    sell_amount NUMERIC NOT NULL,
+    auto_execute BOOLEAN NOT NULL DEFAULT FALSE,
+    funding_method VARCHAR(255),
+    status VARCHAR(20) NOT NULL DEFAULT 'active',
+    expires_at TIMESTAMP,
+    triggered_at TIMESTAMP,
+    triggered_price NUMERIC,
+    quote_id VARCHAR(255),
+    transaction_id VARCHAR(255),
+    last_error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW (),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW ()
+);
+
+CREATE INDEX idx_sep38_rate_alerts_pair_status ON offramp_service.sep38_rate_alerts (anchor_slug, sell_asset, buy_asset, status);
+CREATE INDEX idx_sep38_rate_alerts_account ON offramp_service.sep38_rate_alerts (account);
@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS offramp_service.idx_sep6_refund_payments_payment_id;
+
+DROP INDEX IF EXISTS offramp_service.idx_sep6_refunds_transaction;
+
+CREATE INDEX idx_sep6_refunds_transaction ON offramp_service.sep6_refunds (transaction_id);
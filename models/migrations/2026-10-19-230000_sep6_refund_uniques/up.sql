@@ -0,0 +1,6 @@
+-- One aggregate refund row per transaction, one row per anchor refund payment
+DROP INDEX IF EXISTS offramp_service.idx_sep6_refunds_transaction;
+
+CREATE UNIQUE INDEX idx_sep6_refunds_transaction ON offramp_service.sep6_refunds (transaction_id);
+
+CREATE UNIQUE INDEX idx_sep6_refund_payments_payment_id ON offramp_service.sep6_refund_payments (payment_id);
@@ -0,0 +1,2 @@
+ALTER TABLE offramp_service.sep38_quotes DROP CONSTRAINT IF EXISTS sep38_quotes_account_public;
+ALTER TABLE offramp_service.sep6_transactions DROP CONSTRAINT IF EXISTS sep6_transactions_account_public;
@@ -0,0 +1,8 @@
+-- These columns briefly held the account secret; drop any such values and only accept G-addresses
+UPDATE offramp_service.sep6_transactions SET account = NULL WHERE account !~ '^G[A-Z2-7]{55}$';
+UPDATE offramp_service.sep38_quotes SET account = NULL WHERE account !~ '^G[A-Z2-7]{55}$';
+
+ALTER TABLE offramp_service.sep6_transactions
+    ADD CONSTRAINT sep6_transactions_account_public CHECK (account ~ '^G[A-Z2-7]{55}$');
+ALTER TABLE offramp_service.sep38_quotes
+    ADD CONSTRAINT sep38_quotes_account_public CHECK (account ~ '^G[A-Z2-7]{55}$');
@@ -0,0 +1 @@
+ALTER TABLE offramp_service.sep38_rate_alerts DROP CONSTRAINT IF EXISTS sep38_rate_alerts_account_public;
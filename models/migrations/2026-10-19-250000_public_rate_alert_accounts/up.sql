@@ -0,0 +1,6 @@
+-- Alerts used to keep the account secret for auto-execution. An alert's owner can't be
+-- recovered from SQL, so drop those rows and only accept G-addresses from now on
+DELETE FROM offramp_service.sep38_rate_alerts WHERE account !~ '^G[A-Z2-7]{55}$';
+
+ALTER TABLE offramp_service.sep38_rate_alerts
+    ADD CONSTRAINT sep38_rate_alerts_account_public CHECK (account ~ '^G[A-Z2-7]{55}$');
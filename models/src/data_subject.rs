@@ -0,0 +1,32 @@
+// src/models/data_subject.rs
+
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::schema::offramp_service::data_erasure_log;
+
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = data_erasure_log)]
+pub struct DataErasureLogEntry {
+    pub id: Uuid,
+    pub sequence: i64,
+    /// Keyed HMAC-SHA256 of the erased account's G-address, so the log holds no personal data.
+    pub account_hash: String,
+    /// JSON summary of what was removed and which anchors were asked to delete.
+    pub details: String,
+    pub previous_hash: Option<String>,
+    pub entry_hash: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = data_erasure_log)]
+pub struct NewDataErasureLogEntry {
+    pub sequence: i64,
+    pub account_hash: String,
+    pub details: String,
+    pub previous_hash: Option<String>,
+    pub entry_hash: String,
+    pub created_at: NaiveDateTime,
+}
@@ -0,0 +1,388 @@
+// src/models/encryption.rs
+//
+// Field-level envelope encryption for PII columns.
+//
+// Every value gets its own random data key. The value is sealed with that data key
+// (AES-256-GCM) and the data key is sealed with a master key from `PII_MASTER_KEYS`.
+// Stored form: `enc:v1:<key_id>:<wrapped data key>:<sealed value>`, both parts base64.
+// Values without the prefix are treated as legacy plaintext and returned as-is, so
+// existing rows keep working until the rotation command rewrites them.
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use once_cell::sync::Lazy;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use openssl::symm::{Cipher, decrypt_aead, encrypt_aead};
+use rand::RngCore;
+use std::collections::HashMap;
+use std::io::Write;
+use thiserror::Error;
+
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const KEY_LEN: usize = 32;
+
+#[derive(Error, Debug)]
+pub enum EncryptionError {
+    #[error("Encryption config error: {0}")]
+    Config(String),
+    #[error("Unknown encryption key: {0}")]
+    UnknownKey(String),
+    #[error("Malformed ciphertext: {0}")]
+    Malformed(String),
+    #[error("Crypto error: {0}")]
+    Crypto(#[from] openssl::error::ErrorStack),
+}
+
+/// Master keys by id, loaded from `PII_MASTER_KEYS` (`id:base64key,...`) and `PII_ACTIVE_KEY_ID`.
+pub struct Keyring {
+    keys: HashMap<String, Vec<u8>>,
+    active_key_id: String,
+    blind_index_key: Vec<u8>,
+}
+
+impl Keyring {
+    pub fn from_env() -> Result<Self, EncryptionError> {
+        dotenv::dotenv().ok();
+
+        let raw = std::env::var("PII_MASTER_KEYS")
+            .map_err(|_| EncryptionError::Config("PII_MASTER_KEYS must be set".into()))?;
+
+        let mut keys = HashMap::new();
+        for entry in raw.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (id, encoded) = entry.split_once(':').ok_or_else(|| {
+                EncryptionError::Config("PII_MASTER_KEYS entries must be id:base64key".into())
+            })?;
+            let key = STANDARD
+                .decode(encoded)
+                .map_err(|e| EncryptionError::Config(format!("Key {} is not base64: {}", id, e)))?;
+            keys.insert(id.to_string(), key);
+        }
+
+        let active_key_id = std::env::var("PII_ACTIVE_KEY_ID")
+            .map_err(|_| EncryptionError::Config("PII_ACTIVE_KEY_ID must be set".into()))?;
+
+        let blind_index_key = std::env::var("PII_BLIND_INDEX_KEY")
+            .map_err(|_| EncryptionError::Config("PII_BLIND_INDEX_KEY must be set".into()))
+            .and_then(|encoded| {
+                STANDARD.decode(encoded).map_err(|e| {
+                    EncryptionError::Config(format!("PII_BLIND_INDEX_KEY is not base64: {}", e))
+                })
+            })?;
+
+        Self::new(keys, active_key_id, blind_index_key)
+    }
+
+    pub fn new(
+        keys: HashMap<String, Vec<u8>>,
+        active_key_id: String,
+        blind_index_key: Vec<u8>,
+    ) -> Result<Self, EncryptionError> {
+        if let Some((id, _)) = keys.iter().find(|(_, key)| key.len() != KEY_LEN) {
+            return Err(EncryptionError::Config(format!(
+                "Key {} must be {} bytes",
+                id, KEY_LEN
+            )));
+        }
+        if !keys.contains_key(&active_key_id) {
+            return Err(EncryptionError::UnknownKey(active_key_id));
+        }
+
+        Ok(Self {
+            keys,
+            active_key_id,
+            blind_index_key,
+        })
+    }
+
+    pub fn active_key_id(&self) -> &str {
+        &self.active_key_id
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, EncryptionError> {
+        let master_key = &self.keys[&self.active_key_id];
+
+        let mut data_key = [0u8; KEY_LEN];
+        rand::thread_rng().fill_bytes(&mut data_key);
+
+        let wrapped_key = seal(master_key, &data_key)?;
+        let sealed_value = seal(&data_key, plaintext.as_bytes())?;
+
+        Ok(format!(
+            "{}{}:{}:{}",
+            PREFIX,
+            self.active_key_id,
+            STANDARD.encode(wrapped_key),
+            STANDARD.encode(sealed_value)
+        ))
+    }
+
+    pub fn decrypt(&self, stored: &str) -> Result<String, EncryptionError> {
+        let Some(rest) = stored.strip_prefix(PREFIX) else {
+            return Ok(stored.to_string());
+        };
+
+        let mut parts = rest.splitn(3, ':');
+        let (Some(key_id), Some(wrapped_key), Some(sealed_value)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(EncryptionError::Malformed("missing envelope parts".into()));
+        };
+
+        let master_key = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| EncryptionError::UnknownKey(key_id.to_string()))?;
+
+        let decode = |part: &str| {
+            STANDARD
+                .decode(part)
+                .map_err(|e| EncryptionError::Malformed(e.to_string()))
+        };
+
+        let data_key = open(master_key, &decode(wrapped_key)?)?;
+        let plaintext = open(&data_key, &decode(sealed_value)?)?;
+
+        String::from_utf8(plaintext).map_err(|e| EncryptionError::Malformed(e.to_string()))
+    }
+
+    /// Deterministic HMAC of a normalized value, so equality lookups work without decrypting.
+    pub fn blind_index(&self, normalized: &str) -> Result<String, EncryptionError> {
+        let pkey = PKey::hmac(&self.blind_index_key)?;
+        let mut signer = Signer::new(MessageDigest::sha256(), &pkey)?;
+        signer.update(normalized.as_bytes())?;
+        Ok(hex::encode(signer.sign_to_vec()?))
+    }
+}
+
+fn seal(key: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+
+    let mut tag = [0u8; TAG_LEN];
+    let ciphertext = encrypt_aead(
+        Cipher::aes_256_gcm(),
+        key,
+        Some(&nonce),
+        &[],
+        plaintext,
+        &mut tag,
+    )?;
+
+    let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len() + TAG_LEN);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&ciphertext);
+    sealed.extend_from_slice(&tag);
+    Ok(sealed)
+}
+
+fn open(key: &[u8], sealed: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+    if sealed.len() < NONCE_LEN + TAG_LEN {
+        return Err(EncryptionError::Malformed("ciphertext too short".into()));
+    }
+    let (nonce, rest) = sealed.split_at(NONCE_LEN);
+    let (ciphertext, tag) = rest.split_at(rest.len() - TAG_LEN);
+
+    Ok(decrypt_aead(
+        Cipher::aes_256_gcm(),
+        key,
+        Some(nonce),
+        &[],
+        ciphertext,
+        tag,
+    )?)
+}
+
+static KEYRING: Lazy<Result<Keyring, String>> =
+    Lazy::new(|| Keyring::from_env().map_err(|e| e.to_string()));
+
+/// The process-wide keyring, built from the environment on first use.
+pub fn keyring() -> Result<&'static Keyring, EncryptionError> {
+    KEYRING
+        .as_ref()
+        .map_err(|e| EncryptionError::Config(e.clone()))
+}
+
+pub fn blind_index_email(email: &str) -> Result<String, EncryptionError> {
+    keyring()?.blind_index(&email.trim().to_lowercase())
+}
+
+pub fn blind_index_phone(phone: &str) -> Result<String, EncryptionError> {
+    let normalized: String = phone
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '+')
+        .collect();
+    keyring()?.blind_index(&normalized)
+}
+
+/// A text column that is encrypted on write and decrypted on read.
+#[derive(Debug, Clone, PartialEq, Eq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub struct EncryptedString(pub String);
+
+impl EncryptedString {
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+impl From<String> for EncryptedString {
+    fn from(value: String) -> Self {
+        EncryptedString(value)
+    }
+}
+
+impl From<&str> for EncryptedString {
+    fn from(value: &str) -> Self {
+        EncryptedString(value.to_string())
+    }
+}
+
+impl std::ops::Deref for EncryptedString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl ToSql<Text, Pg> for EncryptedString {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        let ciphertext = keyring()?.encrypt(&self.0)?;
+        out.write_all(ciphertext.as_bytes())?;
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for EncryptedString {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let stored = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+        if !stored.starts_with(PREFIX) {
+            return Ok(EncryptedString(stored));
+        }
+        Ok(EncryptedString(keyring()?.decrypt(&stored)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ring_with(keys: &[(&str, u8)], active: &str) -> Keyring {
+        let keys = keys
+            .iter()
+            .map(|(id, fill)| (id.to_string(), vec![*fill; KEY_LEN]))
+            .collect();
+        Keyring::new(keys, active.to_string(), b"blind-index-key".to_vec()).unwrap()
+    }
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let ring = ring_with(&[("k1", 1)], "k1");
+        let stored = ring.encrypt("alice@example.com").unwrap();
+
+        assert!(stored.starts_with("enc:v1:k1:"));
+        assert!(!stored.contains("alice"));
+        assert_eq!(ring.decrypt(&stored).unwrap(), "alice@example.com");
+    }
+
+    #[test]
+    fn encryption_is_randomized() {
+        let ring = ring_with(&[("k1", 1)], "k1");
+        assert_ne!(ring.encrypt("same").unwrap(), ring.encrypt("same").unwrap());
+    }
+
+    #[test]
+    fn legacy_plaintext_passes_through() {
+        let ring = ring_with(&[("k1", 1)], "k1");
+        assert_eq!(ring.decrypt("plain value").unwrap(), "plain value");
+    }
+
+    #[test]
+    fn rotation_rewrites_under_the_new_key() {
+        let old = ring_with(&[("k1", 1)], "k1");
+        let sealed_with_k1 = old.encrypt("+15551234567").unwrap();
+
+        // During rotation both keys are loaded and k2 is active
+        let rotating = ring_with(&[("k1", 1), ("k2", 2)], "k2");
+        let plaintext = rotating.decrypt(&sealed_with_k1).unwrap();
+        let sealed_with_k2 = rotating.encrypt(&plaintext).unwrap();
+        assert!(sealed_with_k2.starts_with("enc:v1:k2:"));
+
+        // Once k1 is retired only rewritten rows stay readable
+        let retired = ring_with(&[("k2", 2)], "k2");
+        assert_eq!(retired.decrypt(&sealed_with_k2).unwrap(), "+15551234567");
+        assert!(matches!(
+            retired.decrypt(&sealed_with_k1),
+            Err(EncryptionError::UnknownKey(id)) if id == "k1"
+        ));
+    }
+
+    #[test]
+    fn wrong_key_material_fails_to_open() {
+        let sealed = ring_with(&[("k1", 1)], "k1").encrypt("secret").unwrap();
+        let impostor = ring_with(&[("k1", 9)], "k1");
+        assert!(matches!(
+            impostor.decrypt(&sealed),
+            Err(EncryptionError::Crypto(_))
+        ));
+    }
+
+    #[test]
+    fn tampered_ciphertext_is_rejected() {
+        let ring = ring_with(&[("k1", 1)], "k1");
+        let stored = ring.encrypt("secret").unwrap();
+        let (head, value) = stored.rsplit_once(':').unwrap();
+        let mut bytes = STANDARD.decode(value).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0x01;
+        let tampered = format!("{}:{}", head, STANDARD.encode(bytes));
+
+        assert!(ring.decrypt(&tampered).is_err());
+        assert!(matches!(
+            ring.decrypt("enc:v1:k1:only-two"),
+            Err(EncryptionError::Malformed(_))
+        ));
+    }
+
+    #[test]
+    fn new_validates_keys() {
+        let short = HashMap::from([("k1".to_string(), vec![0u8; 16])]);
+        assert!(matches!(
+            Keyring::new(short, "k1".to_string(), vec![1]),
+            Err(EncryptionError::Config(_))
+        ));
+
+        let keys = HashMap::from([("k1".to_string(), vec![0u8; KEY_LEN])]);
+        assert!(matches!(
+            Keyring::new(keys, "k2".to_string(), vec![1]),
+            Err(EncryptionError::UnknownKey(id)) if id == "k2"
+        ));
+    }
+
+    #[test]
+    fn blind_index_is_deterministic_and_keyed() {
+        let ring = ring_with(&[("k1", 1)], "k1");
+        let index = ring.blind_index("alice@example.com").unwrap();
+
+        assert_eq!(index.len(), 64);
+        assert_eq!(index, ring.blind_index("alice@example.com").unwrap());
+        assert_ne!(index, ring.blind_index("bob@example.com").unwrap());
+
+        // Master key rotation leaves blind indexes untouched
+        let rotated = ring_with(&[("k2", 2)], "k2");
+        assert_eq!(index, rotated.blind_index("alice@example.com").unwrap());
+
+        let keys = HashMap::from([("k1".to_string(), vec![1u8; KEY_LEN])]);
+        let other = Keyring::new(keys, "k1".to_string(), b"another-key".to_vec()).unwrap();
+        assert_ne!(index, other.blind_index("alice@example.com").unwrap());
+    }
+}
@@ -0,0 +1,369 @@
+// src/models/money.rs
+//
+// Exact decimal amounts shared by every SEP flow.
+//
+// `Amount` wraps a BigDecimal, maps to NUMERIC columns, and always serializes as a plain
+// decimal string so no client ever parses a float. It deserializes from either a string
+// or a JSON number. `Money` pairs an amount with its asset and rounds to that asset's
+// precision: 7 places for Stellar assets, ISO 4217 minor units for fiat. All rounding
+// is half-even.
+
+use bigdecimal::{BigDecimal, RoundingMode};
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Numeric;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Decimal places Stellar stores for every asset amount.
+pub const STELLAR_DECIMALS: i64 = 7;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum AmountError {
+    #[error("Invalid amount: {0}")]
+    Invalid(String),
+
+    #[error("Amount must be positive: {0}")]
+    NotPositive(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Numeric)]
+pub struct Amount(pub BigDecimal);
+
+impl Amount {
+    pub fn zero() -> Self {
+        Amount(BigDecimal::from(0))
+    }
+
+    pub fn into_inner(self) -> BigDecimal {
+        self.0
+    }
+
+    /// Parses and rejects zero or negative values.
+    pub fn parse_positive(value: &str) -> Result<Self, AmountError> {
+        let amount = Amount::from_str(value)?;
+        if amount.is_positive() {
+            Ok(amount)
+        } else {
+            Err(AmountError::NotPositive(value.to_string()))
+        }
+    }
+
+    pub fn is_positive(&self) -> bool {
+        self.0 > BigDecimal::from(0)
+    }
+
+    /// Rounds half-even to `places` decimal places.
+    pub fn round_to(&self, places: i64) -> Self {
+        Amount(
+            self.0
+                .with_scale_round(places, RoundingMode::HalfEven)
+                .normalized(),
+        )
+    }
+
+    /// Rounds to the 7 decimal places Stellar can settle.
+    pub fn round_stellar(&self) -> Self {
+        self.round_to(STELLAR_DECIMALS)
+    }
+}
+
+impl FromStr for Amount {
+    type Err = AmountError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        BigDecimal::from_str(value.trim())
+            .map(Amount)
+            .map_err(|_| AmountError::Invalid(value.to_string()))
+    }
+}
+
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0.normalized().to_plain_string())
+    }
+}
+
+impl From<BigDecimal> for Amount {
+    fn from(value: BigDecimal) -> Self {
+        Amount(value)
+    }
+}
+
+impl From<Amount> for BigDecimal {
+    fn from(value: Amount) -> Self {
+        value.0
+    }
+}
+
+impl From<i64> for Amount {
+    fn from(value: i64) -> Self {
+        Amount(BigDecimal::from(value))
+    }
+}
+
+impl std::ops::Deref for Amount {
+    type Target = BigDecimal;
+
+    fn deref(&self) -> &BigDecimal {
+        &self.0
+    }
+}
+
+impl std::ops::Add for &Amount {
+    type Output = Amount;
+
+    fn add(self, rhs: &Amount) -> Amount {
+        Amount(&self.0 + &rhs.0)
+    }
+}
+
+impl std::ops::Sub for &Amount {
+    type Output = Amount;
+
+    fn sub(self, rhs: &Amount) -> Amount {
+        Amount(&self.0 - &rhs.0)
+    }
+}
+
+impl std::ops::Mul for &Amount {
+    type Output = Amount;
+
+    fn mul(self, rhs: &Amount) -> Amount {
+        Amount(&self.0 * &rhs.0)
+    }
+}
+
+impl<'a> std::iter::Sum<&'a Amount> for Amount {
+    fn sum<I: Iterator<Item = &'a Amount>>(iter: I) -> Self {
+        Amount(iter.map(|a| &a.0).sum())
+    }
+}
+
+impl Serialize for Amount {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Amount {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Text(String),
+            Number(serde_json::Number),
+        }
+
+        let raw = match Raw::deserialize(deserializer)? {
+            Raw::Text(text) => text,
+            Raw::Number(number) => number.to_string(),
+        };
+        Amount::from_str(&raw).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Serde helper for optional amounts that treats an empty string as absent, for forms that
+/// historically sent `""` for the side they did not fix.
+pub fn empty_as_none<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Amount>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Raw {
+        Empty(String),
+        Amount(Amount),
+    }
+
+    match Option::<Raw>::deserialize(deserializer)? {
+        Some(Raw::Empty(text)) if text.trim().is_empty() => Ok(None),
+        Some(Raw::Empty(text)) => Amount::from_str(&text)
+            .map(Some)
+            .map_err(serde::de::Error::custom),
+        Some(Raw::Amount(amount)) => Ok(Some(amount)),
+        None => Ok(None),
+    }
+}
+
+impl ToSql<Numeric, Pg> for Amount {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        <BigDecimal as ToSql<Numeric, Pg>>::to_sql(&self.0, out)
+    }
+}
+
+impl FromSql<Numeric, Pg> for Amount {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        <BigDecimal as FromSql<Numeric, Pg>>::from_sql(bytes).map(Amount)
+    }
+}
+
+/// ISO 4217 minor units; currencies not listed use 2.
+pub fn iso4217_minor_units(code: &str) -> i64 {
+    match code.to_ascii_uppercase().as_str() {
+        "BIF" | "CLP" | "DJF" | "GNF" | "ISK" | "JPY" | "KMF" | "KRW" | "PYG" | "RWF" | "UGX"
+        | "UYI" | "VND" | "VUV" | "XAF" | "XOF" | "XPF" => 0,
+        "BHD" | "IQD" | "JOD" | "KWD" | "LYD" | "OMR" | "TND" => 3,
+        "CLF" | "UYW" => 4,
+        _ => 2,
+    }
+}
+
+/// Decimal places for a SEP-38 asset identifier (`stellar:CODE:ISSUER`, `iso4217:CODE`) or
+/// a bare Stellar asset code.
+pub fn asset_decimals(asset: &str) -> i64 {
+    match asset.strip_prefix("iso4217:") {
+        Some(code) => iso4217_minor_units(code),
+        None => STELLAR_DECIMALS,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Money {
+    pub amount: Amount,
+    pub asset: String,
+}
+
+impl Money {
+    /// Builds a value rounded to the asset's precision.
+    pub fn new(amount: Amount, asset: &str) -> Self {
+        Money {
+            amount: amount.round_to(asset_decimals(asset)),
+            asset: asset.to_string(),
+        }
+    }
+
+    pub fn parse(value: &str, asset: &str) -> Result<Self, AmountError> {
+        Ok(Money::new(Amount::from_str(value)?, asset))
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.amount, self.asset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(value: &str) -> Amount {
+        Amount::from_str(value).unwrap()
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Form {
+        #[serde(default, deserialize_with = "empty_as_none")]
+        amount: Option<Amount>,
+    }
+
+    #[test]
+    fn round_to_is_half_even() {
+        assert_eq!(amount("2.345").round_to(2).to_string(), "2.34");
+        assert_eq!(amount("2.355").round_to(2).to_string(), "2.36");
+        assert_eq!(amount("2.3451").round_to(2).to_string(), "2.35");
+        assert_eq!(amount("-2.345").round_to(2).to_string(), "-2.34");
+        assert_eq!(amount("0.5").round_to(0).to_string(), "0");
+        assert_eq!(amount("1.5").round_to(0).to_string(), "2");
+    }
+
+    #[test]
+    fn round_stellar_keeps_seven_places() {
+        assert_eq!(amount("1.00000005").round_stellar().to_string(), "1");
+        assert_eq!(
+            amount("1.00000015").round_stellar().to_string(),
+            "1.0000002"
+        );
+        assert_eq!(amount("10.1234567").round_stellar(), amount("10.1234567"));
+    }
+
+    #[test]
+    fn display_is_plain_and_normalized() {
+        assert_eq!(amount("100.500").to_string(), "100.5");
+        assert_eq!(amount("1e3").to_string(), "1000");
+        assert_eq!(amount("0.0000001").to_string(), "0.0000001");
+    }
+
+    #[test]
+    fn parse_positive_rejects_zero_negative_and_garbage() {
+        assert_eq!(Amount::parse_positive("0.1").unwrap(), amount("0.1"));
+        assert!(matches!(
+            Amount::parse_positive("0"),
+            Err(AmountError::NotPositive(_))
+        ));
+        assert!(matches!(
+            Amount::parse_positive("-5"),
+            Err(AmountError::NotPositive(_))
+        ));
+        assert!(matches!(
+            Amount::parse_positive("ten"),
+            Err(AmountError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn asset_decimals_follow_the_asset_kind() {
+        assert_eq!(asset_decimals("iso4217:EUR"), 2);
+        assert_eq!(asset_decimals("iso4217:JPY"), 0);
+        assert_eq!(asset_decimals("iso4217:kwd"), 3);
+        assert_eq!(asset_decimals("iso4217:CLF"), 4);
+        assert_eq!(
+            asset_decimals("stellar:USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN"),
+            STELLAR_DECIMALS
+        );
+        assert_eq!(asset_decimals("USDC"), STELLAR_DECIMALS);
+    }
+
+    #[test]
+    fn money_rounds_to_asset_precision() {
+        assert_eq!(
+            Money::parse("10.125", "iso4217:EUR").unwrap().to_string(),
+            "10.12 iso4217:EUR"
+        );
+        assert_eq!(
+            Money::parse("1500.5", "iso4217:JPY").unwrap().amount,
+            amount("1500")
+        );
+        assert_eq!(
+            Money::parse("0.123456789", "native").unwrap().amount,
+            amount("0.1234568")
+        );
+    }
+
+    #[test]
+    fn deserializes_from_string_or_number() {
+        assert_eq!(
+            serde_json::from_str::<Amount>("\"12.50\"").unwrap(),
+            amount("12.5")
+        );
+        assert_eq!(
+            serde_json::from_str::<Amount>("12.5").unwrap(),
+            amount("12.5")
+        );
+        assert_eq!(serde_json::from_str::<Amount>("7").unwrap(), amount("7"));
+        assert!(serde_json::from_str::<Amount>("\"abc\"").is_err());
+        assert!(serde_json::from_str::<Amount>("true").is_err());
+    }
+
+    #[test]
+    fn serializes_as_a_string() {
+        assert_eq!(serde_json::to_string(&amount("12.50")).unwrap(), "\"12.5\"");
+    }
+
+    #[test]
+    fn empty_as_none_treats_blank_as_absent() {
+        let parse = |json: &str| serde_json::from_str::<Form>(json).unwrap().amount;
+        assert_eq!(parse(r#"{"amount": ""}"#), None);
+        assert_eq!(parse(r#"{"amount": "  "}"#), None);
+        assert_eq!(parse(r#"{"amount": null}"#), None);
+        assert_eq!(parse(r#"{}"#), None);
+        assert_eq!(parse(r#"{"amount": "5.25"}"#), Some(amount("5.25")));
+        assert_eq!(parse(r#"{"amount": 5.25}"#), Some(amount("5.25")));
+        assert!(serde_json::from_str::<Form>(r#"{"amount": "five"}"#).is_err());
+    }
+}
@@ -0,0 +1,41 @@
+// src/models/reconciliation.rs
+
+use crate::money::Amount;
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Insertable, Queryable};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::schema::offramp_service::reconciliation_issues;
+
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = reconciliation_issues)]
+pub struct ReconciliationIssue {
+    pub id: Uuid,
+    pub transaction_id: String,
+    pub issue_type: String,
+    pub stellar_transaction_id: String,
+    pub anchor_account: String,
+    pub anchor_status: String,
+    pub expected_amount: Option<Amount>,
+    pub paid_amount: Option<Amount>,
+    pub asset: Option<String>,
+    pub details: Option<String>,
+    pub resolved: bool,
+    pub resolved_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = reconciliation_issues)]
+pub struct NewReconciliationIssue {
+    pub transaction_id: String,
+    pub issue_type: String,
+    pub stellar_transaction_id: String,
+    pub anchor_account: String,
+    pub anchor_status: String,
+    pub expected_amount: Option<Amount>,
+    pub paid_amount: Option<Amount>,
+    pub asset: Option<String>,
+    pub details: Option<String>,
+}
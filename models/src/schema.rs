@@ -77,26 +77,42 @@ pub mod offramp_service {
             customer_type -> Varchar,
             #[max_length = 20]
             status -> Varchar,
+            first_name -> Nullable<Text>,
+            last_name -> Nullable<Text>,
+            email -> Nullable<Text>,
+            phone -> Nullable<Text>,
+            date_of_birth -> Nullable<Text>,
+            address_street -> Nullable<Text>,
+            address_city -> Nullable<Text>,
+            address_state -> Nullable<Text>,
+            address_postal_code -> Nullable<Text>,
+            address_country -> Nullable<Text>,
+            created_at -> Timestamp,
+            updated_at -> Timestamp,
+            email_blind_index -> Nullable<Text>,
+            phone_blind_index -> Nullable<Text>,
+            sep9_fields -> Nullable<Text>,
             #[max_length = 255]
-            first_name -> Nullable<Varchar>,
-            #[max_length = 255]
-            last_name -> Nullable<Varchar>,
-            #[max_length = 255]
-            email -> Nullable<Varchar>,
-            #[max_length = 255]
-            phone -> Nullable<Varchar>,
-            #[max_length = 255]
-            date_of_birth -> Nullable<Varchar>,
-            #[max_length = 255]
-            address_street -> Nullable<Varchar>,
+            anchor_slug -> Nullable<Varchar>,
             #[max_length = 255]
-            address_city -> Nullable<Varchar>,
+            anchor_customer_id -> Nullable<Varchar>,
+        }
+    }
+
+    diesel::table! {
+        offramp_service.sep12_anchor_submissions (id) {
+            id -> Uuid,
+            customer_id -> Uuid,
             #[max_length = 255]
-            address_state -> Nullable<Varchar>,
+            anchor_slug -> Varchar,
             #[max_length = 255]
-            address_postal_code -> Nullable<Varchar>,
+            anchor_customer_id -> Nullable<Varchar>,
             #[max_length = 255]
-            address_country -> Nullable<Varchar>,
+            status -> Varchar,
+            submitted_fields -> Text,
+            consent_given_at -> Nullable<Timestamp>,
+            consent_revoked_at -> Nullable<Timestamp>,
+            last_synced_at -> Nullable<Timestamp>,
             created_at -> Timestamp,
             updated_at -> Timestamp,
         }
@@ -117,6 +133,7 @@ pub mod offramp_service {
             purpose -> Varchar,
             created_at -> Timestamp,
             updated_at -> Timestamp,
+            content_hash -> Nullable<Text>,
         }
     }
 
@@ -130,6 +147,69 @@ pub mod offramp_service {
             country_codes -> Nullable<Json>,
             created_at -> Timestamp,
             updated_at -> Timestamp,
+            #[max_length = 255]
+            anchor_slug -> Varchar,
+        }
+    }
+
+    diesel::table! {
+        offramp_service.sep38_price_samples (id) {
+            id -> Uuid,
+            #[max_length = 255]
+            anchor_slug -> Varchar,
+            #[max_length = 255]
+            sell_asset -> Varchar,
+            #[max_length = 255]
+            buy_asset -> Varchar,
+            #[max_length = 255]
+            sell_delivery_method -> Nullable<Varchar>,
+            #[max_length = 255]
+            buy_delivery_method -> Nullable<Varchar>,
+            sell_amount -> Numeric,
+            buy_amount -> Numeric,
+            price -> Numeric,
+            total_price -> Numeric,
+            fee_total -> Numeric,
+            #[max_length = 255]
+            fee_asset -> Varchar,
+            sampled_at -> Timestamp,
+        }
+    }
+
+    diesel::table! {
+        offramp_service.sep38_rate_alerts (id) {
+            id -> Uuid,
+            #[max_length = 56]
+            account -> Varchar,
+            #[max_length = 255]
+            anchor_slug -> Varchar,
+            #[max_length = 255]
+            sell_asset -> Varchar,
+            #[max_length = 255]
+            buy_asset -> Varchar,
+            #[max_length = 255]
+            sell_delivery_method -> Nullable<Varchar>,
+            #[max_length = 255]
+            buy_delivery_method -> Nullable<Varchar>,
+            #[max_length = 10]
+            direction -> Varchar,
+            threshold -> Numeric,
+            sell_amount -> Numeric,
+            auto_execute -> Bool,
+            #[max_length = 255]
+            funding_method -> Nullable<Varchar>,
+            #[max_length = 20]
+            status -> Varchar,
+            expires_at -> Nullable<Timestamp>,
+            triggered_at -> Nullable<Timestamp>,
+            triggered_price -> Nullable<Numeric>,
+            #[max_length = 255]
+            quote_id -> Nullable<Varchar>,
+            #[max_length = 255]
+            transaction_id -> Nullable<Varchar>,
+            last_error -> Nullable<Text>,
+            created_at -> Timestamp,
+            updated_at -> Timestamp,
         }
     }
 
@@ -159,6 +239,15 @@ pub mod offramp_service {
             #[max_length = 255]
             context -> Varchar,
             transaction_id -> Nullable<Uuid>,
+            #[max_length = 56]
+            account -> Nullable<Varchar>,
+            #[max_length = 20]
+            status -> Varchar,
+            used_at -> Nullable<Timestamp>,
+            #[max_length = 20]
+            used_by -> Nullable<Varchar>,
+            #[max_length = 255]
+            anchor_slug -> Nullable<Varchar>,
         }
     }
 
@@ -246,6 +335,8 @@ pub mod offramp_service {
             refunds -> Nullable<Varchar>,
             claimable_balance_id -> Nullable<Varchar>,
             created_at -> Timestamp,
+            #[max_length = 56]
+            account -> Nullable<Varchar>,
 
         }
     }
@@ -295,18 +386,88 @@ pub mod offramp_service {
         }
     }
 
+    diesel::table! {
+        offramp_service.webhook_subscriptions (id) {
+            id -> Uuid,
+            url -> Text,
+            secret -> Text,
+            event_types -> Array<Text>,
+            active -> Bool,
+            created_at -> Timestamp,
+            updated_at -> Timestamp,
+        }
+    }
+
+    diesel::table! {
+        offramp_service.webhook_deliveries (id) {
+            id -> Uuid,
+            subscription_id -> Uuid,
+            event_type -> Text,
+            payload -> Jsonb,
+            status -> Text,
+            attempts -> Integer,
+            next_attempt_at -> Timestamp,
+            last_error -> Nullable<Text>,
+            last_response_status -> Nullable<Integer>,
+            delivered_at -> Nullable<Timestamp>,
+            created_at -> Timestamp,
+            updated_at -> Timestamp,
+        }
+    }
+
+    diesel::table! {
+        offramp_service.reconciliation_issues (id) {
+            id -> Uuid,
+            transaction_id -> Text,
+            issue_type -> Text,
+            stellar_transaction_id -> Text,
+            anchor_account -> Text,
+            anchor_status -> Text,
+            expected_amount -> Nullable<Numeric>,
+            paid_amount -> Nullable<Numeric>,
+            asset -> Nullable<Text>,
+            details -> Nullable<Text>,
+            resolved -> Bool,
+            resolved_at -> Nullable<Timestamp>,
+            created_at -> Timestamp,
+            updated_at -> Timestamp,
+        }
+    }
+
+    diesel::table! {
+        offramp_service.data_erasure_log (id) {
+            id -> Uuid,
+            sequence -> Bigint,
+            account_hash -> Text,
+            details -> Text,
+            previous_hash -> Nullable<Text>,
+            entry_hash -> Text,
+            created_at -> Timestamp,
+        }
+    }
+
+    diesel::joinable!(sep12_anchor_submissions -> sep12_customers (customer_id));
     diesel::joinable!(sep12_customer_files -> sep12_customers (customer_id));
     diesel::joinable!(sep6_refund_payments -> sep6_refunds (refund_id));
+    diesel::joinable!(sep6_refunds -> sep6_transactions (transaction_id));
+    diesel::joinable!(webhook_deliveries -> webhook_subscriptions (subscription_id));
 
     diesel::allow_tables_to_appear_in_same_query!(
+        data_erasure_log,
+        reconciliation_issues,
+        sep12_anchor_submissions,
         sep12_customers,
         sep12_customer_files,
         sep38_assets,
+        sep38_price_samples,
         sep38_quotes,
+        sep38_rate_alerts,
         sep6_refund_payments,
         sep6_refunds,
         sep6_transactions,
         user_auth_tokens,
         users,
+        webhook_deliveries,
+        webhook_subscriptions,
     );
 }
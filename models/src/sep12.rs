@@ -1,8 +1,12 @@
 use chrono::NaiveDateTime;
-use diesel::{Associations, Identifiable, Insertable, Queryable};
+use diesel::{AsChangeset, Associations, Identifiable, Insertable, Queryable};
+use serde::Serialize;
 use uuid::Uuid;
 
-use crate::schema::offramp_service::{sep12_customer_files, sep12_customers};
+use crate::encryption::EncryptedString;
+use crate::schema::offramp_service::{
+    sep12_anchor_submissions, sep12_customer_files, sep12_customers,
+};
 
 #[derive(Queryable, Identifiable, Debug)]
 #[diesel(table_name = sep12_customers)]
@@ -13,21 +17,28 @@ pub struct Sep12Customer {
     pub memo_type: Option<String>,
     pub customer_type: String,
     pub status: String,
-    pub first_name: Option<String>,
-    pub last_name: Option<String>,
-    pub email: Option<String>,
-    pub phone: Option<String>,
-    pub date_of_birth: Option<String>,
-    pub address_street: Option<String>,
-    pub address_city: Option<String>,
-    pub address_state: Option<String>,
-    pub address_postal_code: Option<String>,
-    pub address_country: Option<String>,
+    pub first_name: Option<EncryptedString>,
+    pub last_name: Option<EncryptedString>,
+    pub email: Option<EncryptedString>,
+    pub phone: Option<EncryptedString>,
+    pub date_of_birth: Option<EncryptedString>,
+    pub address_street: Option<EncryptedString>,
+    pub address_city: Option<EncryptedString>,
+    pub address_state: Option<EncryptedString>,
+    pub address_postal_code: Option<EncryptedString>,
+    pub address_country: Option<EncryptedString>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub email_blind_index: Option<String>,
+    pub phone_blind_index: Option<String>,
+    /// Every submitted SEP-9 field as a JSON object, encrypted.
+    pub sep9_fields: Option<EncryptedString>,
+    pub anchor_slug: Option<String>,
+    /// The id the anchor assigned to this customer, used for updates and callbacks.
+    pub anchor_customer_id: Option<String>,
 }
 
-#[derive(Queryable, Identifiable, Associations, Debug)]
+#[derive(Queryable, Identifiable, Associations, Serialize, Debug)]
 #[diesel(table_name = sep12_customer_files)]
 #[diesel(belongs_to(Sep12Customer, foreign_key = customer_id))]
 pub struct Sep12CustomerFile {
@@ -40,6 +51,7 @@ pub struct Sep12CustomerFile {
     pub purpose: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub content_hash: Option<String>,
 }
 
 #[derive(Insertable)]
@@ -50,16 +62,49 @@ pub struct NewSep12Customer {
     pub memo_type: Option<String>,
     pub customer_type: String,
     pub status: String,
-    pub first_name: Option<String>,
-    pub last_name: Option<String>,
-    pub email: Option<String>,
-    pub phone: Option<String>,
-    pub date_of_birth: Option<String>,
-    pub address_street: Option<String>,
-    pub address_city: Option<String>,
-    pub address_state: Option<String>,
-    pub address_postal_code: Option<String>,
-    pub address_country: Option<String>,
+    pub anchor_slug: Option<String>,
+    pub anchor_customer_id: Option<String>,
+    #[diesel(embed)]
+    pub pii: Sep12CustomerPii,
+}
+
+/// The encrypted personal data columns plus their lookup hashes, written together.
+#[derive(Insertable, AsChangeset, Debug, Clone, Default)]
+#[diesel(table_name = sep12_customers, treat_none_as_null = true)]
+pub struct Sep12CustomerPii {
+    pub first_name: Option<EncryptedString>,
+    pub last_name: Option<EncryptedString>,
+    pub email: Option<EncryptedString>,
+    pub phone: Option<EncryptedString>,
+    pub date_of_birth: Option<EncryptedString>,
+    pub address_street: Option<EncryptedString>,
+    pub address_city: Option<EncryptedString>,
+    pub address_state: Option<EncryptedString>,
+    pub address_postal_code: Option<EncryptedString>,
+    pub address_country: Option<EncryptedString>,
+    pub email_blind_index: Option<String>,
+    pub phone_blind_index: Option<String>,
+    pub sep9_fields: Option<EncryptedString>,
+}
+
+impl From<&Sep12Customer> for Sep12CustomerPii {
+    fn from(customer: &Sep12Customer) -> Self {
+        Self {
+            first_name: customer.first_name.clone(),
+            last_name: customer.last_name.clone(),
+            email: customer.email.clone(),
+            phone: customer.phone.clone(),
+            date_of_birth: customer.date_of_birth.clone(),
+            address_street: customer.address_street.clone(),
+            address_city: customer.address_city.clone(),
+            address_state: customer.address_state.clone(),
+            address_postal_code: customer.address_postal_code.clone(),
+            address_country: customer.address_country.clone(),
+            email_blind_index: customer.email_blind_index.clone(),
+            phone_blind_index: customer.phone_blind_index.clone(),
+            sep9_fields: customer.sep9_fields.clone(),
+        }
+    }
 }
 
 #[derive(Insertable)]
@@ -71,4 +116,36 @@ pub struct NewSep12CustomerFile {
     pub size: i64,
     pub storage_path: String,
     pub purpose: String,
+    pub content_hash: Option<String>,
+}
+
+/// One anchor's copy of a customer profile: what we sent it and where it stands.
+#[derive(Queryable, Identifiable, Associations, Serialize, Debug)]
+#[diesel(table_name = sep12_anchor_submissions)]
+#[diesel(belongs_to(Sep12Customer, foreign_key = customer_id))]
+pub struct Sep12AnchorSubmission {
+    pub id: Uuid,
+    pub customer_id: Uuid,
+    pub anchor_slug: String,
+    pub anchor_customer_id: Option<String>,
+    pub status: String,
+    /// JSON list of the SEP-9 field names sent to this anchor.
+    pub submitted_fields: String,
+    pub consent_given_at: Option<NaiveDateTime>,
+    pub consent_revoked_at: Option<NaiveDateTime>,
+    pub last_synced_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = sep12_anchor_submissions)]
+pub struct NewSep12AnchorSubmission {
+    pub customer_id: Uuid,
+    pub anchor_slug: String,
+    pub anchor_customer_id: Option<String>,
+    pub status: String,
+    pub submitted_fields: String,
+    pub consent_given_at: Option<NaiveDateTime>,
+    pub last_synced_at: Option<NaiveDateTime>,
 }
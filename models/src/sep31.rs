@@ -4,7 +4,7 @@ use chrono::NaiveDateTime;
 use diesel::{Insertable, Queryable, Identifiable};
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
-use bigdecimal::BigDecimal;
+use crate::money::Amount;
 use crate::schema::offramp_service::sep31_transactions;
 
 #[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
@@ -15,7 +15,7 @@ pub struct Sep31Transaction {
     pub memo: Option<String>,
     pub memo_type: Option<String>,
     pub transaction_id: String,
-    pub amount: BigDecimal,
+    pub amount: Amount,
     pub asset_code: String,
     pub asset_issuer: Option<String>,
     pub destination_asset: Option<String>,
@@ -28,11 +28,11 @@ pub struct Sep31Transaction {
     pub status: String,
     pub status_eta: Option<i64>,
     pub status_message: Option<String>,
-    pub amount_in: Option<BigDecimal>,
+    pub amount_in: Option<Amount>,
     pub amount_in_asset: Option<String>,
-    pub amount_out: Option<BigDecimal>,
+    pub amount_out: Option<Amount>,
     pub amount_out_asset: Option<String>,
-    pub amount_fee: Option<BigDecimal>,
+    pub amount_fee: Option<Amount>,
     pub amount_fee_asset: Option<String>,
     pub fee_details: Option<serde_json::Value>,
     pub started_at: Option<NaiveDateTime>,
@@ -51,7 +51,7 @@ pub struct Sep31Transaction {
 pub struct NewSep31Transaction {
     pub transaction_id: String,
     pub account: String,
-    pub amount: BigDecimal,
+    pub amount: Amount,
     pub asset_code: String,
     pub asset_issuer: Option<String>,
     pub destination_asset: Option<String>,
@@ -66,7 +66,7 @@ pub struct NewSep31Transaction {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FeeDetails {
-    pub total: String,
+    pub total: Amount,
     pub asset: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<Vec<FeeComponent>>,
@@ -77,19 +77,19 @@ pub struct FeeComponent {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
-    pub amount: String,
+    pub amount: Amount,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Refunds {
-    pub amount_refunded: String,
-    pub amount_fee: String,
+    pub amount_refunded: Amount,
+    pub amount_fee: Amount,
     pub payments: Vec<RefundPayment>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RefundPayment {
     pub id: String,
-    pub amount: String,
-    pub fee: String,
+    pub amount: Amount,
+    pub fee: Amount,
 }
\ No newline at end of file
@@ -4,9 +4,9 @@ use chrono::NaiveDateTime;
 use diesel::{Insertable, Queryable, Identifiable};
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
-use bigdecimal::BigDecimal;
+use crate::money::Amount;
 use crate::schema::offramp_service::{sep38_assets,
-      sep38_quotes,
+      sep38_price_samples, sep38_quotes, sep38_rate_alerts,
     
 };
 
@@ -20,6 +20,7 @@ pub struct Sep38Asset {
     pub country_codes: Option<serde_json::Value>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub anchor_slug: String,
 }
 
 #[derive(Debug, Clone, Insertable)]
@@ -29,6 +30,7 @@ pub struct NewSep38Asset<'a> {
     pub sell_delivery_methods: Option<&'a serde_json::Value>,
     pub buy_delivery_methods: Option<&'a serde_json::Value>,
     pub country_codes: Option<&'a serde_json::Value>,
+    pub anchor_slug: &'a str,
 }
 
 #[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
@@ -38,11 +40,11 @@ pub struct Sep38Quote {
     pub original_quote_id: String,
     pub sell_asset: String,
     pub buy_asset: String,
-    pub sell_amount: BigDecimal,
-    pub buy_amount: BigDecimal,
-    pub price: BigDecimal,
-    pub total_price: BigDecimal,
-    pub fee_total: BigDecimal,
+    pub sell_amount: Amount,
+    pub buy_amount: Amount,
+    pub price: Amount,
+    pub total_price: Amount,
+    pub fee_total: Amount,
     pub fee_asset: String,
     pub fee_details: Option<serde_json::Value>,
     pub sell_delivery_method: Option<String>,
@@ -51,6 +53,13 @@ pub struct Sep38Quote {
     pub created_at: NaiveDateTime,
     pub context: String,
     pub transaction_id: Option<Uuid>,
+    pub account: Option<String>,
+    /// One of `active`, `used` or `expired`.
+    pub status: String,
+    pub used_at: Option<NaiveDateTime>,
+    /// Which flow consumed the quote (`sep6` or `sep31`); `transaction_id` points into its table.
+    pub used_by: Option<String>,
+    pub anchor_slug: Option<String>,
 }
 
 #[derive(Debug, Clone, Insertable)]
@@ -59,11 +68,11 @@ pub struct NewSep38Quote {
     pub original_quote_id: String,
     pub sell_asset: String,
     pub buy_asset: String,
-    pub sell_amount: BigDecimal,
-    pub buy_amount: BigDecimal,
-    pub price: BigDecimal,
-    pub total_price: BigDecimal,
-    pub fee_total: BigDecimal,
+    pub sell_amount: Amount,
+    pub buy_amount: Amount,
+    pub price: Amount,
+    pub total_price: Amount,
+    pub fee_total: Amount,
     pub fee_asset: String,
     pub fee_details: Option<serde_json::Value>,
     pub sell_delivery_method: Option<String>,
@@ -71,4 +80,85 @@ pub struct NewSep38Quote {
     pub expires_at: NaiveDateTime,
     pub context: String,
     pub transaction_id: Option<Uuid>,
-}
\ No newline at end of file
+    pub account: Option<String>,
+    pub anchor_slug: Option<String>,
+}
+
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = sep38_price_samples)]
+pub struct Sep38PriceSample {
+    pub id: Uuid,
+    pub anchor_slug: String,
+    pub sell_asset: String,
+    pub buy_asset: String,
+    pub sell_delivery_method: Option<String>,
+    pub buy_delivery_method: Option<String>,
+    pub sell_amount: Amount,
+    pub buy_amount: Amount,
+    pub price: Amount,
+    pub total_price: Amount,
+    pub fee_total: Amount,
+    pub fee_asset: String,
+    pub sampled_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = sep38_price_samples)]
+pub struct NewSep38PriceSample {
+    pub anchor_slug: String,
+    pub sell_asset: String,
+    pub buy_asset: String,
+    pub sell_delivery_method: Option<String>,
+    pub buy_delivery_method: Option<String>,
+    pub sell_amount: Amount,
+    pub buy_amount: Amount,
+    pub price: Amount,
+    pub total_price: Amount,
+    pub fee_total: Amount,
+    pub fee_asset: String,
+}
+
+#[derive(Debug, Clone, Queryable, Identifiable, Serialize, Deserialize)]
+#[diesel(table_name = sep38_rate_alerts)]
+pub struct Sep38RateAlert {
+    pub id: Uuid,
+    pub account: String,
+    pub anchor_slug: String,
+    pub sell_asset: String,
+    pub buy_asset: String,
+    pub sell_delivery_method: Option<String>,
+    pub buy_delivery_method: Option<String>,
+    /// `above` or `below`, compared against the sampled SEP-38 price.
+    pub direction: String,
+    pub threshold: Amount,
+    pub sell_amount: Amount,
+    pub auto_execute: bool,
+    pub funding_method: Option<String>,
+    pub status: String,
+    pub expires_at: Option<NaiveDateTime>,
+    pub triggered_at: Option<NaiveDateTime>,
+    pub triggered_price: Option<Amount>,
+    pub quote_id: Option<String>,
+    /// Anchor transaction id of the withdraw-exchange started on auto-execution.
+    pub transaction_id: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[diesel(table_name = sep38_rate_alerts)]
+pub struct NewSep38RateAlert {
+    pub account: String,
+    pub anchor_slug: String,
+    pub sell_asset: String,
+    pub buy_asset: String,
+    pub sell_delivery_method: Option<String>,
+    pub buy_delivery_method: Option<String>,
+    pub direction: String,
+    pub threshold: Amount,
+    pub sell_amount: Amount,
+    pub auto_execute: bool,
+    pub funding_method: Option<String>,
+    pub expires_at: Option<NaiveDateTime>,
+}
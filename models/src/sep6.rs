@@ -1,14 +1,22 @@
 use chrono::NaiveDateTime;
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
 use diesel::prelude::*;
-use serde::{Deserialize, Serialize};
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
 use uuid::Uuid;
-use bigdecimal::BigDecimal;
+use crate::money::Amount;
 use crate::schema::offramp_service::{
   sep6_refund_payments, sep6_refunds,sep6_transactions
 };
 
-#[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize)]
+#[derive(Debug, Clone, Queryable, Selectable, Insertable, Serialize, Deserialize)]
 #[diesel(table_name = sep6_transactions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
 pub struct Sep6Transaction {
     pub id: Uuid,
     pub transaction_id: String,
@@ -16,11 +24,11 @@ pub struct Sep6Transaction {
     pub status: String,
     pub status_eta: Option<i64>,
     pub more_info_url: Option<String>,
-    pub amount_in: Option<BigDecimal>,
+    pub amount_in: Option<Amount>,
     pub amount_in_asset: Option<String>,
-    pub amount_out: Option<BigDecimal>,
+    pub amount_out: Option<Amount>,
     pub amount_out_asset: Option<String>,
-    pub amount_fee: Option<BigDecimal>,
+    pub amount_fee: Option<Amount>,
     pub amount_fee_asset: Option<String>,
     pub fee_details: Option<String>,
     pub quote_id: Option<String>,
@@ -40,13 +48,16 @@ pub struct Sep6Transaction {
     pub external_transaction_id: Option<String>,
     pub message: Option<String>,
     pub refunded: Option<bool>,
-    pub refunds: Option<String>,
+    pub refunds: Option<Refunds>,
     pub required_info_message: Option<String>,
-    pub required_info_updates: Option<String>,
+    pub required_info_updates: Option<RequiredInfoUpdates>,
     pub instructions: Option<String>,
     pub claimable_balance_id: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: Option<NaiveDateTime>,
+    /// The Stellar account we fetched or created this transaction for.
+    #[serde(default)]
+    pub account: Option<String>,
 }
 
 #[derive(Debug, Clone, Insertable, AsChangeset)]
@@ -57,11 +68,11 @@ pub struct NewSep6Transaction {
     pub status: String,
     pub status_eta: Option<i64>,
     pub more_info_url: Option<String>,
-    pub amount_in: Option<BigDecimal>,
+    pub amount_in: Option<Amount>,
     pub amount_in_asset: Option<String>,
-    pub amount_out: Option<BigDecimal>,
+    pub amount_out: Option<Amount>,
     pub amount_out_asset: Option<String>,
-    pub amount_fee: Option<BigDecimal>,
+    pub amount_fee: Option<Amount>,
     pub amount_fee_asset: Option<String>,
     pub fee_details: Option<String>,
     pub quote_id: Option<String>,
@@ -82,11 +93,88 @@ pub struct NewSep6Transaction {
     pub external_transaction_id: Option<String>,
     pub message: Option<String>,
     pub refunded: Option<bool>,
-    pub refunds: Option<String>,
+    pub refunds: Option<Refunds>,
     pub required_info_message: Option<String>,
-    pub required_info_updates: Option<String>,
+    pub required_info_updates: Option<RequiredInfoUpdates>,
     pub instructions: Option<String>,
     pub claimable_balance_id: Option<String>,
+    pub account: Option<String>,
+}
+
+/// A field the anchor wants collected, keyed by field name in the maps that hold it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldSpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub optional: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub choices: Option<Vec<String>>,
+}
+
+/// Fields the anchor needs corrected while a transaction is `pending_transaction_info_update`.
+/// Stored as JSON text; older anchors nest the map under `"transaction"`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+#[serde(transparent)]
+pub struct RequiredInfoUpdates(pub HashMap<String, FieldSpec>);
+
+impl<'de> Deserialize<'de> for RequiredInfoUpdates {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut value = serde_json::Value::deserialize(deserializer)?;
+        if let Some(nested) = value.get_mut("transaction").filter(|n| n.is_object()) {
+            value = nested.take();
+        }
+        serde_json::from_value(value)
+            .map(RequiredInfoUpdates)
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl ToSql<Text, Pg> for RequiredInfoUpdates {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(serde_json::to_string(&self.0)?.as_bytes())?;
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for RequiredInfoUpdates {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let stored = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+        Ok(serde_json::from_str(&stored)?)
+    }
+}
+
+/// SEP-6 `refunds` object, stored as JSON text on the transaction row.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub struct Refunds {
+    pub amount_refunded: Amount,
+    pub amount_fee: Amount,
+    #[serde(default)]
+    pub payments: Vec<RefundPayment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RefundPayment {
+    pub id: String,
+    pub id_type: String,
+    pub amount: Amount,
+    pub fee: Amount,
+}
+
+impl ToSql<Text, Pg> for Refunds {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(serde_json::to_string(self)?.as_bytes())?;
+        Ok(serialize::IsNull::No)
+    }
+}
+
+impl FromSql<Text, Pg> for Refunds {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let stored = <String as FromSql<Text, Pg>>::from_sql(bytes)?;
+        Ok(serde_json::from_str(&stored)?)
+    }
 }
 
 #[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize)]
@@ -94,8 +182,8 @@ pub struct NewSep6Transaction {
 pub struct Sep6Refund {
     pub id: Uuid,
     pub transaction_id: Uuid,
-    pub amount_refunded: BigDecimal,
-    pub amount_fee: BigDecimal,
+    pub amount_refunded: Amount,
+    pub amount_fee: Amount,
     pub created_at: NaiveDateTime,
 }
 
@@ -103,8 +191,8 @@ pub struct Sep6Refund {
 #[diesel(table_name = sep6_refunds)]
 pub struct NewSep6Refund {
     pub transaction_id: Uuid,
-    pub amount_refunded: BigDecimal,
-    pub amount_fee: BigDecimal,
+    pub amount_refunded: Amount,
+    pub amount_fee: Amount,
 }
 
 #[derive(Debug, Clone, Queryable, Insertable, Serialize, Deserialize)]
@@ -114,8 +202,8 @@ pub struct Sep6RefundPayment {
     pub refund_id: Uuid,
     pub payment_id: String,
     pub id_type: String,
-    pub amount: BigDecimal,
-    pub fee: BigDecimal,
+    pub amount: Amount,
+    pub fee: Amount,
     pub created_at: NaiveDateTime,
 }
 
@@ -125,6 +213,80 @@ pub struct NewSep6RefundPayment {
     pub refund_id: Uuid,
     pub payment_id: String,
     pub id_type: String,
-    pub amount: BigDecimal,
-    pub fee: BigDecimal,
-}
\ No newline at end of file
+    pub amount: Amount,
+    pub fee: Amount,
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn required_info_updates_accepts_flat_and_nested_maps() {
+        let flat: RequiredInfoUpdates = serde_json::from_str(
+            r#"{"dest": {"description": "your bank account number"}, "dest_extra": {"optional": true}}"#,
+        )
+        .unwrap();
+        let nested: RequiredInfoUpdates = serde_json::from_str(
+            r#"{"transaction": {"dest": {"description": "your bank account number"}, "dest_extra": {"optional": true}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(flat, nested);
+        assert_eq!(
+            flat.0["dest"].description.as_deref(),
+            Some("your bank account number")
+        );
+        assert!(!flat.0["dest"].optional);
+        assert!(flat.0["dest_extra"].optional);
+    }
+
+    #[test]
+    fn required_info_updates_rejects_non_maps() {
+        assert!(serde_json::from_str::<RequiredInfoUpdates>(r#""dest""#).is_err());
+        assert!(serde_json::from_str::<RequiredInfoUpdates>(r#"["dest"]"#).is_err());
+    }
+
+    #[test]
+    fn refunds_deserialize_from_anchor_json() {
+        let refunds: Refunds = serde_json::from_str(
+            r#"{
+                "amount_refunded": "10.50",
+                "amount_fee": "0.5",
+                "payments": [
+                    {"id": "b9d0b2292c4e09e8eb22d036171491e87b8d2086bf8b265874c8d182cb9c9020", "id_type": "stellar", "amount": "10", "fee": 0.5}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(refunds.amount_refunded.to_string(), "10.5");
+        assert_eq!(refunds.amount_fee.to_string(), "0.5");
+        assert_eq!(refunds.payments.len(), 1);
+        assert_eq!(refunds.payments[0].id_type, "stellar");
+        assert_eq!(refunds.payments[0].amount.to_string(), "10");
+        assert_eq!(refunds.payments[0].fee.to_string(), "0.5");
+    }
+
+    #[test]
+    fn refunds_without_payments_default_to_empty() {
+        let refunds: Refunds =
+            serde_json::from_str(r#"{"amount_refunded": "0", "amount_fee": "0"}"#).unwrap();
+        assert!(refunds.payments.is_empty());
+    }
+
+    #[test]
+    fn refunds_reject_strings_and_missing_totals() {
+        assert!(serde_json::from_str::<Refunds>(r#""{\"amount_refunded\": \"1\"}""#).is_err());
+        assert!(serde_json::from_str::<Refunds>(r#"{"amount_fee": "0"}"#).is_err());
+    }
+
+    #[test]
+    fn required_info_updates_serializes_flat() {
+        let updates: RequiredInfoUpdates =
+            serde_json::from_str(r#"{"transaction": {"dest": {"choices": ["a", "b"]}}}"#).unwrap();
+        assert_eq!(
+            serde_json::to_value(&updates).unwrap(),
+            serde_json::json!({"dest": {"optional": false, "choices": ["a", "b"]}})
+        );
+    }
+}
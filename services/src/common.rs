@@ -1,5 +1,6 @@
 // Function to extract and format all anchor configuration info
 // 
+use helpers::horizon::{HorizonError, HttpHorizonClient};
 use helpers::stellartoml::{AnchorService, AnchorError};
 
 pub async fn get_anchor_config_details(anchor_service: &AnchorService, slug: &str) -> Result<AnchorConfigDetails, AnchorError> {
@@ -52,6 +53,19 @@ pub async fn get_anchor_config_details(anchor_service: &AnchorService, slug: &st
     Ok(details)
 }
 
+// Horizon client for the network an anchor operates on
+pub async fn get_anchor_horizon_client(
+    anchor_service: &AnchorService,
+    slug: &str,
+) -> Result<HttpHorizonClient, HorizonError> {
+    let anchor_config = anchor_service
+        .get_anchor(slug)
+        .await
+        .map_err(|e| HorizonError::ConfigError(e.to_string()))?;
+
+    HttpHorizonClient::for_anchor(anchor_config.horizon_url.as_deref())
+}
+
 // Structured representation of anchor configuration
 #[derive(Debug, Clone)]
 pub struct AnchorConfigDetails {
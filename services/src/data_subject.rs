@@ -0,0 +1,657 @@
+// Data subject requests
+// Helper functions:
+// Export everything we hold for an account
+// Erase local PII and ask anchors to delete theirs
+// Hash-chained erasure log
+pub mod data_subject {
+    use base64::Engine;
+    use base64::engine::general_purpose::STANDARD;
+    use chrono::{NaiveDateTime, Utc};
+    use diesel::prelude::*;
+    use diesel::sql_types::Text;
+    use helpers::keypair::public_account_id;
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::PKey;
+    use openssl::sign::Signer;
+    use serde::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, BTreeSet};
+    use thiserror::Error;
+    use uuid::Uuid;
+
+    use crate::sep9::sep9::Sep9Fields;
+    use crate::sep12::sep12::{
+        Sep12Error, delete_customer_documents, delete_customer_rows, request_anchor_deletion,
+    };
+    use crate::storage::storage::{DocumentStore, content_hash, document_store_from_env};
+    use models::{
+        common::establish_connection,
+        data_subject::{DataErasureLogEntry, NewDataErasureLogEntry},
+        encryption::EncryptedString,
+        schema::offramp_service::{
+            data_erasure_log, reconciliation_issues, sep6_transactions, sep12_anchor_submissions,
+            sep12_customer_files, sep12_customers, sep31_transactions, sep38_quotes,
+            sep38_rate_alerts,
+        },
+        sep6::Sep6Transaction,
+        sep12::{Sep12AnchorSubmission, Sep12Customer, Sep12CustomerFile},
+        sep31::Sep31Transaction,
+        sep38::{Sep38Quote, Sep38RateAlert},
+    };
+
+    /// Stands in for the account on SEP-31 rows, where the column can't be null, and in
+    /// webhook payloads.
+    pub const ERASED_ACCOUNT: &str = "erased";
+
+    #[derive(Error, Debug)]
+    pub enum DataSubjectError {
+        #[error("KYC error: {0}")]
+        Sep12Error(#[from] Sep12Error),
+
+        #[error("Invalid request: {0}")]
+        InvalidRequest(String),
+
+        #[error("Database error: {0}")]
+        DatabaseError(String),
+
+        #[error("Invalid configuration: {0}")]
+        ConfigError(String),
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct CustomerRecord {
+        pub id: Uuid,
+        pub customer_type: String,
+        pub status: String,
+        pub memo: Option<String>,
+        pub anchor_slug: Option<String>,
+        pub anchor_customer_id: Option<String>,
+        pub first_name: Option<String>,
+        pub last_name: Option<String>,
+        pub email: Option<String>,
+        pub phone: Option<String>,
+        pub date_of_birth: Option<String>,
+        pub address_street: Option<String>,
+        pub address_city: Option<String>,
+        pub address_state: Option<String>,
+        pub address_postal_code: Option<String>,
+        pub address_country: Option<String>,
+        pub sep9_fields: BTreeMap<String, String>,
+        pub created_at: NaiveDateTime,
+        pub updated_at: NaiveDateTime,
+    }
+
+    #[derive(Debug, Serialize)]
+    pub struct FileRecord {
+        #[serde(flatten)]
+        pub file: Sep12CustomerFile,
+        /// Document bytes, base64 encoded; missing when the store couldn't return them.
+        pub content_base64: Option<String>,
+        pub error: Option<String>,
+    }
+
+    #[derive(Debug, Serialize)]
+    pub struct DataExport {
+        pub account: String,
+        pub generated_at: NaiveDateTime,
+        pub customers: Vec<CustomerRecord>,
+        pub anchor_submissions: Vec<Sep12AnchorSubmission>,
+        pub files: Vec<FileRecord>,
+        pub sep6_transactions: Vec<Sep6Transaction>,
+        pub sep31_transactions: Vec<Sep31Transaction>,
+        pub sep38_quotes: Vec<Sep38Quote>,
+        pub rate_alerts: Vec<Sep38RateAlert>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct AnchorDeletion {
+        pub slug: String,
+        pub memo: Option<String>,
+        pub deleted: bool,
+        pub error: Option<String>,
+    }
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    pub struct ErasureSummary {
+        pub anchors: Vec<AnchorDeletion>,
+        pub customers_deleted: usize,
+        pub files_deleted: usize,
+        pub sep6_transactions_anonymized: usize,
+        pub sep31_transactions_anonymized: usize,
+        pub sep38_quotes_anonymized: usize,
+        pub rate_alerts_deleted: usize,
+        pub webhook_deliveries_redacted: usize,
+        pub reconciliation_issues_deleted: usize,
+    }
+
+    #[derive(Debug, Serialize)]
+    pub struct ErasureReport {
+        #[serde(flatten)]
+        pub summary: ErasureSummary,
+        pub log_entry: DataErasureLogEntry,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct LogVerification {
+        pub entries: usize,
+        pub valid: bool,
+        /// Sequence of the first entry whose hash or link doesn't match.
+        pub broken_at: Option<i64>,
+    }
+
+    fn db_error(e: diesel::result::Error) -> DataSubjectError {
+        DataSubjectError::DatabaseError(e.to_string())
+    }
+
+    // Lets the erasure transaction use `?` on queries and still return DataSubjectError
+    impl From<diesel::result::Error> for DataSubjectError {
+        fn from(e: diesel::result::Error) -> Self {
+            db_error(e)
+        }
+    }
+
+    // Requests carry the account secret; everything we store or match on uses its G-address
+    fn public_account(account: &str) -> Result<String, DataSubjectError> {
+        public_account_id(account)
+            .map_err(|_| DataSubjectError::InvalidRequest("Invalid account secret".to_string()))
+    }
+
+    // Keyed so the log can't be reversed by hashing every public account on the network
+    fn account_hash(account: &str) -> Result<String, DataSubjectError> {
+        let secret = std::env::var("ERASURE_HASH_KEY")
+            .ok()
+            .filter(|key| !key.is_empty())
+            .ok_or_else(|| DataSubjectError::ConfigError("ERASURE_HASH_KEY must be set".into()))?;
+        let hmac = || -> Result<String, openssl::error::ErrorStack> {
+            let key = PKey::hmac(secret.as_bytes())?;
+            let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+            signer.update(account.as_bytes())?;
+            Ok(hex::encode(signer.sign_to_vec()?))
+        };
+        hmac().map_err(|e| DataSubjectError::ConfigError(e.to_string()))
+    }
+
+    fn customer_record(customer: Sep12Customer) -> Result<CustomerRecord, DataSubjectError> {
+        let plain = |value: Option<EncryptedString>| value.map(EncryptedString::into_inner);
+        let sep9_fields = match customer.sep9_fields.as_deref() {
+            Some(stored) => Sep9Fields::from_json(stored)
+                .and_then(|fields| fields.to_map())
+                .map_err(Sep12Error::from)?,
+            None => BTreeMap::new(),
+        };
+
+        Ok(CustomerRecord {
+            id: customer.id,
+            customer_type: customer.customer_type,
+            status: customer.status,
+            memo: customer.memo,
+            anchor_slug: customer.anchor_slug,
+            anchor_customer_id: customer.anchor_customer_id,
+            first_name: plain(customer.first_name),
+            last_name: plain(customer.last_name),
+            email: plain(customer.email),
+            phone: plain(customer.phone),
+            date_of_birth: plain(customer.date_of_birth),
+            address_street: plain(customer.address_street),
+            address_city: plain(customer.address_city),
+            address_state: plain(customer.address_state),
+            address_postal_code: plain(customer.address_postal_code),
+            address_country: plain(customer.address_country),
+            sep9_fields,
+            created_at: customer.created_at,
+            updated_at: customer.updated_at,
+        })
+    }
+
+    fn load_customers(
+        conn: &mut PgConnection,
+        account: &str,
+    ) -> Result<Vec<Sep12Customer>, DataSubjectError> {
+        sep12_customers::table
+            .filter(sep12_customers::account.eq(account))
+            .load(conn)
+            .map_err(db_error)
+    }
+
+    fn load_submissions(
+        conn: &mut PgConnection,
+        customer_ids: &[Uuid],
+    ) -> Result<Vec<Sep12AnchorSubmission>, DataSubjectError> {
+        sep12_anchor_submissions::table
+            .filter(sep12_anchor_submissions::customer_id.eq_any(customer_ids))
+            .load(conn)
+            .map_err(db_error)
+    }
+
+    // 1. Everything we hold for an account, documents included
+    pub async fn export_account(account: &str) -> Result<DataExport, DataSubjectError> {
+        let public = public_account(account)?;
+        let mut conn =
+            establish_connection().map_err(|e| DataSubjectError::DatabaseError(e.to_string()))?;
+
+        let customers = load_customers(&mut conn, account)?;
+        let customer_ids: Vec<Uuid> = customers.iter().map(|c| c.id).collect();
+        let anchor_submissions = load_submissions(&mut conn, &customer_ids)?;
+
+        let stored_files: Vec<Sep12CustomerFile> = sep12_customer_files::table
+            .filter(sep12_customer_files::customer_id.eq_any(&customer_ids))
+            .load(&mut conn)
+            .map_err(db_error)?;
+
+        let mut files = Vec::with_capacity(stored_files.len());
+        if !stored_files.is_empty() {
+            let store = document_store_from_env().map_err(Sep12Error::from)?;
+            for file in stored_files {
+                // A missing document shouldn't keep the rest of the export from the user
+                let (content_base64, error) = match store.get(&file.storage_path).await {
+                    Ok(bytes) => (Some(STANDARD.encode(bytes)), None),
+                    Err(e) => (None, Some(e.to_string())),
+                };
+                files.push(FileRecord {
+                    file,
+                    content_base64,
+                    error,
+                });
+            }
+        }
+
+        let sep6_transactions = sep6_transactions::table
+            .filter(
+                sep6_transactions::account
+                    .eq(&public)
+                    .or(sep6_transactions::from.eq(&public))
+                    .or(sep6_transactions::to.eq(&public)),
+            )
+            .select(Sep6Transaction::as_select())
+            .load(&mut conn)
+            .map_err(db_error)?;
+
+        let sep31_transactions = sep31_transactions::table
+            .filter(sep31_transactions::account.eq(account))
+            .load(&mut conn)
+            .map_err(db_error)?;
+
+        let sep38_quotes = sep38_quotes::table
+            .filter(sep38_quotes::account.eq(&public))
+            .load(&mut conn)
+            .map_err(db_error)?;
+
+        let rate_alerts = sep38_rate_alerts::table
+            .filter(sep38_rate_alerts::account.eq(&public))
+            .load(&mut conn)
+            .map_err(db_error)?;
+
+        Ok(DataExport {
+            account: public,
+            generated_at: Utc::now().naive_utc(),
+            customers: customers
+                .into_iter()
+                .map(customer_record)
+                .collect::<Result<_, _>>()?,
+            anchor_submissions,
+            files,
+            sep6_transactions,
+            sep31_transactions,
+            sep38_quotes,
+            rate_alerts,
+        })
+    }
+
+    // 2. Erase: anchors first, then documents, then every local row in one transaction
+    pub async fn erase_account(account: &str) -> Result<ErasureReport, DataSubjectError> {
+        let public = public_account(account)?;
+        // Checked up front so a missing key can't leave an erasure without its log entry
+        let account_hash = account_hash(&public)?;
+        let mut conn =
+            establish_connection().map_err(|e| DataSubjectError::DatabaseError(e.to_string()))?;
+
+        let customers = load_customers(&mut conn, account)?;
+        let customer_ids: Vec<Uuid> = customers.iter().map(|c| c.id).collect();
+        let submissions = load_submissions(&mut conn, &customer_ids)?;
+
+        // Every anchor the account was onboarded to, once per memo it was registered under
+        let mut targets: BTreeSet<(String, Option<String>)> = BTreeSet::new();
+        for customer in &customers {
+            if let Some(slug) = &customer.anchor_slug {
+                targets.insert((slug.clone(), customer.memo.clone()));
+            }
+            for submission in submissions.iter().filter(|s| s.customer_id == customer.id) {
+                targets.insert((submission.anchor_slug.clone(), customer.memo.clone()));
+            }
+        }
+
+        let mut summary = ErasureSummary::default();
+        for (slug, memo) in targets {
+            // Failures are logged rather than fatal so local erasure always goes ahead
+            let result = match request_anchor_deletion(&slug, account, memo.as_deref()).await {
+                Ok(()) | Err(Sep12Error::CustomerNotFound) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            };
+            summary.anchors.push(AnchorDeletion {
+                slug,
+                memo,
+                deleted: result.is_ok(),
+                error: result.err(),
+            });
+        }
+
+        // Deleting documents is idempotent, so a failed transaction below can simply be retried
+        summary.files_deleted = delete_customer_documents(&mut conn, &customers).await?;
+
+        let log_entry = conn.transaction::<_, DataSubjectError, _>(|conn| {
+            summary.customers_deleted = delete_customer_rows(conn, &customers)?;
+
+            let account_rows = sep6_transactions::account
+                .eq(&public)
+                .or(sep6_transactions::from.eq(&public))
+                .or(sep6_transactions::to.eq(&public));
+
+            // Issue rows tie the account's withdrawals to on-chain payments, so they go too
+            let transaction_ids: Vec<String> = sep6_transactions::table
+                .filter(account_rows)
+                .select(sep6_transactions::transaction_id)
+                .load(conn)?;
+            summary.reconciliation_issues_deleted = diesel::delete(
+                reconciliation_issues::table
+                    .filter(reconciliation_issues::transaction_id.eq_any(&transaction_ids)),
+            )
+            .execute(conn)?;
+
+            // Outbox payloads (quote and rate alert events) carry the public account; pending
+            // ones still go out, just without it. Older rows may hold the secret itself.
+            for value in [public.as_str(), account] {
+                summary.webhook_deliveries_redacted += diesel::sql_query(
+                    "UPDATE offramp_service.webhook_deliveries \
+                     SET payload = replace(payload::text, $1, $2)::jsonb, updated_at = now() \
+                     WHERE strpos(payload::text, $1) > 0",
+                )
+                .bind::<Text, _>(value)
+                .bind::<Text, _>(ERASED_ACCOUNT)
+                .execute(conn)?;
+            }
+
+            summary.sep6_transactions_anonymized = diesel::update(
+                sep6_transactions::table.filter(sep6_transactions::account.eq(&public)),
+            )
+            .set(sep6_transactions::account.eq(None::<String>))
+            .execute(conn)?;
+            summary.sep6_transactions_anonymized += diesel::update(
+                sep6_transactions::table.filter(sep6_transactions::from.eq(&public)),
+            )
+            .set(sep6_transactions::from.eq(None::<String>))
+            .execute(conn)?;
+            summary.sep6_transactions_anonymized +=
+                diesel::update(sep6_transactions::table.filter(sep6_transactions::to.eq(&public)))
+                    .set(sep6_transactions::to.eq(None::<String>))
+                    .execute(conn)?;
+
+            summary.sep31_transactions_anonymized = diesel::update(
+                sep31_transactions::table.filter(sep31_transactions::account.eq(account)),
+            )
+            .set(sep31_transactions::account.eq(ERASED_ACCOUNT))
+            .execute(conn)?;
+
+            summary.sep38_quotes_anonymized =
+                diesel::update(sep38_quotes::table.filter(sep38_quotes::account.eq(&public)))
+                    .set(sep38_quotes::account.eq(None::<String>))
+                    .execute(conn)?;
+
+            summary.rate_alerts_deleted = diesel::delete(
+                sep38_rate_alerts::table.filter(sep38_rate_alerts::account.eq(&public)),
+            )
+            .execute(conn)?;
+
+            let details = serde_json::to_string(&summary)
+                .map_err(|e| DataSubjectError::InvalidRequest(e.to_string()))?;
+            append_log_entry(conn, &account_hash, &details)
+        })?;
+
+        Ok(ErasureReport { summary, log_entry })
+    }
+
+    fn entry_hash(
+        sequence: i64,
+        previous_hash: Option<&str>,
+        account_hash: &str,
+        details: &str,
+        created_at: NaiveDateTime,
+    ) -> String {
+        // Whole seconds, so the hash survives the database's timestamp precision
+        let payload = format!(
+            "{}|{}|{}|{}|{}",
+            sequence,
+            previous_hash.unwrap_or(""),
+            account_hash,
+            details,
+            created_at.and_utc().timestamp()
+        );
+        content_hash(payload.as_bytes())
+    }
+
+    fn append_log_entry(
+        conn: &mut PgConnection,
+        account_hash: &str,
+        details: &str,
+    ) -> Result<DataErasureLogEntry, DataSubjectError> {
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            // Serialise writers so two erasures can't both chain off the same entry
+            diesel::sql_query("LOCK TABLE offramp_service.data_erasure_log IN EXCLUSIVE MODE")
+                .execute(conn)?;
+
+            let last: Option<DataErasureLogEntry> = data_erasure_log::table
+                .order(data_erasure_log::sequence.desc())
+                .first(conn)
+                .optional()?;
+
+            let sequence = last.as_ref().map_or(1, |l| l.sequence + 1);
+            let previous_hash = last.map(|l| l.entry_hash);
+            let created_at = Utc::now().naive_utc();
+
+            diesel::insert_into(data_erasure_log::table)
+                .values(&NewDataErasureLogEntry {
+                    sequence,
+                    account_hash: account_hash.to_string(),
+                    details: details.to_string(),
+                    entry_hash: entry_hash(
+                        sequence,
+                        previous_hash.as_deref(),
+                        account_hash,
+                        details,
+                        created_at,
+                    ),
+                    previous_hash,
+                    created_at,
+                })
+                .get_result(conn)
+        })
+        .map_err(db_error)
+    }
+
+    // Each entry must follow the previous one, link to its hash and hash to its own contents
+    fn verify_chain(entries: &[DataErasureLogEntry]) -> LogVerification {
+        let mut previous: Option<&DataErasureLogEntry> = None;
+        for entry in entries {
+            let expected_sequence = previous.map_or(1, |p| p.sequence + 1);
+            let linked = entry.previous_hash.as_deref() == previous.map(|p| p.entry_hash.as_str());
+            let hashed = entry.entry_hash
+                == entry_hash(
+                    entry.sequence,
+                    entry.previous_hash.as_deref(),
+                    &entry.account_hash,
+                    &entry.details,
+                    entry.created_at,
+                );
+
+            if entry.sequence != expected_sequence || !linked || !hashed {
+                return LogVerification {
+                    entries: entries.len(),
+                    valid: false,
+                    broken_at: Some(entry.sequence),
+                };
+            }
+            previous = Some(entry);
+        }
+
+        LogVerification {
+            entries: entries.len(),
+            valid: true,
+            broken_at: None,
+        }
+    }
+
+    // 3. Walk the erasure log and check every link in the chain
+    pub fn verify_erasure_log() -> Result<LogVerification, DataSubjectError> {
+        let mut conn =
+            establish_connection().map_err(|e| DataSubjectError::DatabaseError(e.to_string()))?;
+
+        let entries: Vec<DataErasureLogEntry> = data_erasure_log::table
+            .order(data_erasure_log::sequence.asc())
+            .load(&mut conn)
+            .map_err(db_error)?;
+
+        Ok(verify_chain(&entries))
+    }
+
+    // 4. Erasure history for one account
+    pub fn get_erasure_log(account: &str) -> Result<Vec<DataErasureLogEntry>, DataSubjectError> {
+        let mut conn =
+            establish_connection().map_err(|e| DataSubjectError::DatabaseError(e.to_string()))?;
+
+        data_erasure_log::table
+            .filter(data_erasure_log::account_hash.eq(account_hash(&public_account(account)?)?))
+            .order(data_erasure_log::sequence.asc())
+            .load(&mut conn)
+            .map_err(db_error)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use chrono::NaiveDate;
+        use stellar_base::KeyPair;
+
+        fn at(seconds: u32) -> NaiveDateTime {
+            NaiveDate::from_ymd_opt(2026, 10, 19)
+                .unwrap()
+                .and_hms_opt(12, 0, seconds)
+                .unwrap()
+        }
+
+        // A well-formed log of `len` entries, built the way append_log_entry writes them
+        fn chain(len: usize) -> Vec<DataErasureLogEntry> {
+            let mut entries: Vec<DataErasureLogEntry> = Vec::new();
+            for index in 0..len {
+                let sequence = index as i64 + 1;
+                let previous_hash = entries.last().map(|e| e.entry_hash.clone());
+                let account_hash = format!("account-{}", sequence);
+                let details = format!(r#"{{"customers_deleted":{}}}"#, sequence);
+                let created_at = at(index as u32);
+                entries.push(DataErasureLogEntry {
+                    id: Uuid::new_v4(),
+                    sequence,
+                    entry_hash: entry_hash(
+                        sequence,
+                        previous_hash.as_deref(),
+                        &account_hash,
+                        &details,
+                        created_at,
+                    ),
+                    account_hash,
+                    details,
+                    previous_hash,
+                    created_at,
+                });
+            }
+            entries
+        }
+
+        #[test]
+        fn entry_hash_is_deterministic_and_covers_every_field() {
+            let base = entry_hash(2, Some("prev"), "account", "{}", at(0));
+            assert_eq!(base, entry_hash(2, Some("prev"), "account", "{}", at(0)));
+            assert_eq!(base.len(), 64);
+
+            assert_ne!(base, entry_hash(3, Some("prev"), "account", "{}", at(0)));
+            assert_ne!(base, entry_hash(2, Some("other"), "account", "{}", at(0)));
+            assert_ne!(base, entry_hash(2, None, "account", "{}", at(0)));
+            assert_ne!(base, entry_hash(2, Some("prev"), "someone", "{}", at(0)));
+            assert_ne!(base, entry_hash(2, Some("prev"), "account", "{ }", at(0)));
+            assert_ne!(base, entry_hash(2, Some("prev"), "account", "{}", at(1)));
+        }
+
+        #[test]
+        fn entry_hash_ignores_sub_second_precision() {
+            let stored = at(5) + chrono::Duration::microseconds(123_456);
+            assert_eq!(
+                entry_hash(1, None, "account", "{}", at(5)),
+                entry_hash(1, None, "account", "{}", stored)
+            );
+        }
+
+        #[test]
+        fn empty_and_intact_logs_verify() {
+            let empty = verify_chain(&[]);
+            assert!(empty.valid);
+            assert_eq!(empty.entries, 0);
+
+            let intact = verify_chain(&chain(4));
+            assert!(intact.valid);
+            assert_eq!(intact.entries, 4);
+            assert_eq!(intact.broken_at, None);
+        }
+
+        #[test]
+        fn edited_details_break_the_chain_at_that_entry() {
+            let mut entries = chain(4);
+            entries[2].details = r#"{"customers_deleted":0}"#.to_string();
+
+            let result = verify_chain(&entries);
+            assert!(!result.valid);
+            assert_eq!(result.broken_at, Some(3));
+        }
+
+        #[test]
+        fn rehashed_entry_still_breaks_the_next_link() {
+            let mut entries = chain(4);
+            entries[1].account_hash = "someone-else".to_string();
+            entries[1].entry_hash = entry_hash(
+                entries[1].sequence,
+                entries[1].previous_hash.as_deref(),
+                &entries[1].account_hash,
+                &entries[1].details,
+                entries[1].created_at,
+            );
+
+            assert_eq!(verify_chain(&entries).broken_at, Some(3));
+        }
+
+        #[test]
+        fn removed_entries_are_detected() {
+            let mut entries = chain(4);
+            entries.remove(1);
+            assert_eq!(verify_chain(&entries).broken_at, Some(3));
+
+            let mut entries = chain(3);
+            entries.remove(0);
+            assert_eq!(verify_chain(&entries).broken_at, Some(2));
+        }
+
+        #[test]
+        fn relinked_entries_are_detected() {
+            let mut entries = chain(3);
+            entries[2].previous_hash = entries[0].previous_hash.clone();
+            assert_eq!(verify_chain(&entries).broken_at, Some(3));
+        }
+
+        #[test]
+        fn public_account_derives_the_g_address() {
+            let keypair = KeyPair::random().unwrap();
+            let public = public_account(&keypair.secret_key().secret_seed()).unwrap();
+            assert_eq!(public, keypair.public_key().account_id());
+
+            assert!(matches!(
+                public_account(&public),
+                Err(DataSubjectError::InvalidRequest(_))
+            ));
+        }
+    }
+}
@@ -3,3 +3,9 @@ pub mod sep12;
 pub mod sep38;
 pub mod sep6;
 pub mod sep31 ;
+pub mod webhook;
+pub mod reconciliation;
+pub mod storage;
+pub mod sep9;
+pub mod data_subject;
+pub mod rate_alert;
@@ -0,0 +1,586 @@
+// Rate alerts
+// Helper functions:
+// Create, list and cancel alerts per account
+// Evaluate alerts against each SEP-38 price sample
+// Optionally firm-quote and start a withdraw-exchange once triggered
+pub mod rate_alert {
+    use chrono::{DateTime, NaiveDateTime, Utc};
+    use diesel::prelude::*;
+    use helpers::keypair::public_account_id;
+    use serde::{Deserialize, Serialize};
+    use thiserror::Error;
+    use uuid::Uuid;
+
+    use crate::sep6::sep6::{Sep6Error, get_withdraw_exchange};
+    use crate::sep38::sep38::{SampledPair, Sep38Error, quote_exchange_price, sampled_pairs};
+    use crate::webhook::webhook::{SEP38_RATE_ALERT_TRIGGERED, enqueue_event};
+    use models::{
+        common::establish_connection,
+        money::Amount,
+        schema::offramp_service::sep38_rate_alerts,
+        sep38::{NewSep38RateAlert, Sep38PriceSample, Sep38RateAlert},
+    };
+
+    pub const DIRECTION_ABOVE: &str = "above";
+    pub const DIRECTION_BELOW: &str = "below";
+
+    pub const ALERT_ACTIVE: &str = "active";
+    pub const ALERT_TRIGGERED: &str = "triggered";
+    pub const ALERT_EXECUTED: &str = "executed";
+    pub const ALERT_FAILED: &str = "failed";
+    pub const ALERT_EXPIRED: &str = "expired";
+    pub const ALERT_CANCELLED: &str = "cancelled";
+
+    #[derive(Error, Debug)]
+    pub enum RateAlertError {
+        #[error("Quote error: {0}")]
+        QuoteError(#[from] Sep38Error),
+
+        #[error("Withdrawal error: {0}")]
+        WithdrawError(#[from] Sep6Error),
+
+        #[error("Invalid request: {0}")]
+        InvalidRequest(String),
+
+        #[error("Alert not found")]
+        AlertNotFound,
+
+        #[error("Database error: {0}")]
+        DatabaseError(String),
+    }
+
+    impl From<diesel::result::Error> for RateAlertError {
+        fn from(e: diesel::result::Error) -> Self {
+            RateAlertError::DatabaseError(e.to_string())
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct RateAlertRequest {
+        /// The account secret; only its G-address is stored.
+        pub account: String,
+        pub slug: String,
+        pub sell_asset: String,
+        pub buy_asset: String,
+        pub sell_delivery_method: Option<String>,
+        pub buy_delivery_method: Option<String>,
+        pub direction: String,
+        pub threshold: Amount,
+        pub sell_amount: Amount,
+        /// RFC 3339; alerts without one stay active until triggered or cancelled.
+        pub expires_at: Option<String>,
+        /// Only for accounts with a server-side signer; see `execution_signer`.
+        #[serde(default)]
+        pub auto_execute: bool,
+        /// Required with `auto_execute`; passed to SEP-6 withdraw-exchange.
+        pub funding_method: Option<String>,
+    }
+
+    fn db_error(e: diesel::result::Error) -> RateAlertError {
+        RateAlertError::DatabaseError(e.to_string())
+    }
+
+    fn require_positive(value: &Amount, field: &str) -> Result<(), RateAlertError> {
+        if value.is_positive() {
+            Ok(())
+        } else {
+            Err(RateAlertError::InvalidRequest(format!(
+                "{} must be a positive number",
+                field
+            )))
+        }
+    }
+
+    // Requests carry the account secret; alerts store and match its G-address
+    fn public_account(account: &str) -> Result<String, RateAlertError> {
+        public_account_id(account)
+            .map_err(|_| RateAlertError::InvalidRequest("Invalid account secret".to_string()))
+    }
+
+    /// Secret of a server-side signer for `account`, read from `RATE_ALERT_SIGNER_SECRETS`
+    /// (comma separated seeds). Auto-execution only runs for these accounts, so request
+    /// secrets never have to be kept around until an alert fires.
+    fn execution_signer(account: &str) -> Option<String> {
+        find_signer(
+            &std::env::var("RATE_ALERT_SIGNER_SECRETS").unwrap_or_default(),
+            account,
+        )
+    }
+
+    fn find_signer(secrets: &str, account: &str) -> Option<String> {
+        secrets
+            .split(',')
+            .map(str::trim)
+            .filter(|secret| !secret.is_empty())
+            .find(|secret| public_account_id(secret).is_ok_and(|public| public == account))
+            .map(str::to_string)
+    }
+
+    // Alerts only fire from price samples, so the pair has to be one the sampler covers
+    fn is_sampled(pairs: &[SampledPair], request: &RateAlertRequest) -> bool {
+        let method_covered = |wanted: &Option<String>, sampled: &Option<String>| {
+            wanted.as_deref().is_none_or(str::is_empty) || wanted == sampled
+        };
+        pairs.iter().any(|pair| {
+            pair.slug == request.slug
+                && pair.sell_asset == request.sell_asset
+                && pair.buy_asset == request.buy_asset
+                && method_covered(&request.sell_delivery_method, &pair.sell_delivery_method)
+                && method_covered(&request.buy_delivery_method, &pair.buy_delivery_method)
+        })
+    }
+
+    fn crossed(alert: &Sep38RateAlert, price: &Amount) -> bool {
+        match alert.direction.as_str() {
+            DIRECTION_ABOVE => price >= &alert.threshold,
+            DIRECTION_BELOW => price <= &alert.threshold,
+            _ => false,
+        }
+    }
+
+    // An alert without a delivery method matches samples for any method
+    fn matches_sample(alert: &Sep38RateAlert, sample: &Sep38PriceSample) -> bool {
+        let method_matches = |wanted: &Option<String>, sampled: &Option<String>| {
+            wanted.is_none() || wanted == sampled
+        };
+        method_matches(&alert.sell_delivery_method, &sample.sell_delivery_method)
+            && method_matches(&alert.buy_delivery_method, &sample.buy_delivery_method)
+    }
+
+    // 1. Create an alert
+    pub fn create_alert(request: RateAlertRequest) -> Result<Sep38RateAlert, RateAlertError> {
+        if request.direction != DIRECTION_ABOVE && request.direction != DIRECTION_BELOW {
+            return Err(RateAlertError::InvalidRequest(format!(
+                "direction must be {} or {}",
+                DIRECTION_ABOVE, DIRECTION_BELOW
+            )));
+        }
+
+        if !helpers::stellartoml::AnchorService::new()
+            .supported_slugs()
+            .contains(&request.slug)
+        {
+            return Err(RateAlertError::InvalidRequest(format!(
+                "Unsupported anchor: {}",
+                request.slug
+            )));
+        }
+
+        if !is_sampled(&sampled_pairs(), &request) {
+            return Err(RateAlertError::InvalidRequest(format!(
+                "{}/{} is not sampled for {}; add it to SEP38_PRICE_PAIRS",
+                request.sell_asset, request.buy_asset, request.slug
+            )));
+        }
+
+        require_positive(&request.threshold, "threshold")?;
+        require_positive(&request.sell_amount, "sell_amount")?;
+
+        let expires_at = match request.expires_at.as_deref() {
+            Some(value) => {
+                let expires_at = DateTime::parse_from_rfc3339(value)
+                    .map_err(|_| {
+                        RateAlertError::InvalidRequest(
+                            "Invalid expires_at: expected RFC 3339".to_string(),
+                        )
+                    })?
+                    .naive_utc();
+                if expires_at <= Utc::now().naive_utc() {
+                    return Err(RateAlertError::InvalidRequest(
+                        "expires_at must be in the future".to_string(),
+                    ));
+                }
+                Some(expires_at)
+            }
+            None => None,
+        };
+
+        let funding_method = request.funding_method.filter(|m| !m.is_empty());
+        if request.auto_execute && funding_method.is_none() {
+            return Err(RateAlertError::InvalidRequest(
+                "funding_method is required when auto_execute is set".to_string(),
+            ));
+        }
+
+        let account = public_account(&request.account)?;
+        if request.auto_execute && execution_signer(&account).is_none() {
+            return Err(RateAlertError::InvalidRequest(
+                "auto_execute is not enabled for this account".to_string(),
+            ));
+        }
+
+        let mut conn =
+            establish_connection().map_err(|e| RateAlertError::DatabaseError(e.to_string()))?;
+
+        diesel::insert_into(sep38_rate_alerts::table)
+            .values(&NewSep38RateAlert {
+                account,
+                anchor_slug: request.slug,
+                sell_asset: request.sell_asset,
+                buy_asset: request.buy_asset,
+                sell_delivery_method: request.sell_delivery_method.filter(|m| !m.is_empty()),
+                buy_delivery_method: request.buy_delivery_method.filter(|m| !m.is_empty()),
+                direction: request.direction,
+                threshold: request.threshold,
+                sell_amount: request.sell_amount,
+                auto_execute: request.auto_execute,
+                funding_method,
+                expires_at,
+            })
+            .get_result(&mut conn)
+            .map_err(db_error)
+    }
+
+    // 2. List an account's alerts, newest first
+    pub fn list_alerts(
+        account: &str,
+        status: Option<&str>,
+    ) -> Result<Vec<Sep38RateAlert>, RateAlertError> {
+        let account = public_account(account)?;
+        let mut conn =
+            establish_connection().map_err(|e| RateAlertError::DatabaseError(e.to_string()))?;
+
+        let mut query = sep38_rate_alerts::table
+            .filter(sep38_rate_alerts::account.eq(&account))
+            .into_boxed();
+        if let Some(status) = status {
+            query = query.filter(sep38_rate_alerts::status.eq(status));
+        }
+
+        query
+            .order(sep38_rate_alerts::created_at.desc())
+            .load(&mut conn)
+            .map_err(db_error)
+    }
+
+    // 3. Cancel an active alert
+    pub fn cancel_alert(account: &str, alert_id: &str) -> Result<Sep38RateAlert, RateAlertError> {
+        let alert_uuid = Uuid::parse_str(alert_id)
+            .map_err(|_| RateAlertError::InvalidRequest("Invalid alert ID".to_string()))?;
+        let account = public_account(account)?;
+
+        let mut conn =
+            establish_connection().map_err(|e| RateAlertError::DatabaseError(e.to_string()))?;
+
+        let alert: Sep38RateAlert = sep38_rate_alerts::table
+            .find(alert_uuid)
+            .filter(sep38_rate_alerts::account.eq(&account))
+            .first(&mut conn)
+            .optional()
+            .map_err(db_error)?
+            .ok_or(RateAlertError::AlertNotFound)?;
+
+        if alert.status != ALERT_ACTIVE {
+            return Err(RateAlertError::InvalidRequest(format!(
+                "Alert is already {}",
+                alert.status
+            )));
+        }
+
+        diesel::update(sep38_rate_alerts::table.find(alert_uuid))
+            .set((
+                sep38_rate_alerts::status.eq(ALERT_CANCELLED),
+                sep38_rate_alerts::updated_at.eq(Utc::now().naive_utc()),
+            ))
+            .get_result(&mut conn)
+            .map_err(db_error)
+    }
+
+    // Firm-quotes the alert's amount and starts a withdraw-exchange against that quote
+    async fn execute_alert(
+        alert: &Sep38RateAlert,
+    ) -> Result<(String, Option<String>), RateAlertError> {
+        // The signer may have been removed since the alert was created
+        let signer = execution_signer(&alert.account).ok_or_else(|| {
+            RateAlertError::InvalidRequest(format!(
+                "No execution signer configured for {}",
+                alert.account
+            ))
+        })?;
+
+        let quote = quote_exchange_price(
+            alert.anchor_slug.clone(),
+            signer.clone(),
+            alert.sell_asset.clone(),
+            alert.buy_asset.clone(),
+            Some(alert.sell_amount.clone()),
+            None,
+            None,
+            alert.sell_delivery_method.clone(),
+            alert.buy_delivery_method.clone(),
+            None,
+            "sep6".to_string(),
+        )
+        .await?;
+
+        // SEP-6 wants the bare asset code for the on-chain side
+        let source_asset = alert
+            .sell_asset
+            .strip_prefix("stellar:")
+            .and_then(|rest| rest.split(':').next())
+            .unwrap_or(&alert.sell_asset);
+
+        let withdrawal = get_withdraw_exchange(
+            &alert.anchor_slug,
+            &signer,
+            source_asset,
+            &alert.buy_asset,
+            &alert.sell_amount,
+            Some(&quote.id),
+            alert.funding_method.as_deref().unwrap_or_default(),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .await?;
+
+        Ok((quote.id, withdrawal.id))
+    }
+
+    // Moves an active alert to triggered; returns false if another evaluation got there first
+    fn claim_alert(
+        conn: &mut PgConnection,
+        alert: &Sep38RateAlert,
+        price: &Amount,
+    ) -> Result<bool, RateAlertError> {
+        let now = Utc::now().naive_utc();
+        let claimed = diesel::update(
+            sep38_rate_alerts::table
+                .find(alert.id)
+                .filter(sep38_rate_alerts::status.eq(ALERT_ACTIVE)),
+        )
+        .set((
+            sep38_rate_alerts::status.eq(ALERT_TRIGGERED),
+            sep38_rate_alerts::triggered_at.eq(Some(now)),
+            sep38_rate_alerts::triggered_price.eq(Some(price)),
+            sep38_rate_alerts::updated_at.eq(now),
+        ))
+        .execute(conn)
+        .map_err(db_error)?;
+        Ok(claimed > 0)
+    }
+
+    fn notify_triggered(
+        conn: &mut PgConnection,
+        alert: &Sep38RateAlert,
+        price: &Amount,
+        status: &str,
+        quote_id: Option<String>,
+        transaction_id: Option<String>,
+        error: Option<String>,
+    ) -> Result<(), RateAlertError> {
+        enqueue_event(
+            conn,
+            SEP38_RATE_ALERT_TRIGGERED,
+            // The column only holds G-addresses, so this never carries a secret
+            serde_json::json!({
+                "alert_id": alert.id,
+                "account": alert.account,
+                "slug": alert.anchor_slug,
+                "sell_asset": alert.sell_asset,
+                "buy_asset": alert.buy_asset,
+                "direction": alert.direction,
+                "threshold": alert.threshold.to_string(),
+                "price": price.to_string(),
+                "status": status,
+                "quote_id": quote_id,
+                "transaction_id": transaction_id,
+                "error": error,
+            }),
+        )
+        .map_err(|e| RateAlertError::DatabaseError(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn trigger_alert(
+        conn: &mut PgConnection,
+        alert: &Sep38RateAlert,
+        price: &Amount,
+    ) -> Result<(), RateAlertError> {
+        // Only the first evaluation to claim the alert acts on it
+        if !alert.auto_execute {
+            return conn.transaction::<_, RateAlertError, _>(|conn| {
+                if !claim_alert(conn, alert, price)? {
+                    return Ok(());
+                }
+                notify_triggered(conn, alert, price, ALERT_TRIGGERED, None, None, None)
+            });
+        }
+
+        // The claim commits on its own so a slow execution can't be repeated by the next pass
+        if !claim_alert(conn, alert, price)? {
+            return Ok(());
+        }
+
+        let (status, quote_id, transaction_id, error) = match execute_alert(alert).await {
+            Ok((quote, transaction)) => (ALERT_EXECUTED, Some(quote), transaction, None),
+            Err(e) => (ALERT_FAILED, None, None, Some(e.to_string())),
+        };
+
+        conn.transaction::<_, RateAlertError, _>(|conn| {
+            diesel::update(sep38_rate_alerts::table.find(alert.id))
+                .set((
+                    sep38_rate_alerts::status.eq(status),
+                    sep38_rate_alerts::quote_id.eq(&quote_id),
+                    sep38_rate_alerts::transaction_id.eq(&transaction_id),
+                    sep38_rate_alerts::last_error.eq(&error),
+                    sep38_rate_alerts::updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)
+                .map_err(db_error)?;
+
+            notify_triggered(conn, alert, price, status, quote_id, transaction_id, error)
+        })
+    }
+
+    fn expire_alerts(conn: &mut PgConnection, now: NaiveDateTime) -> Result<usize, RateAlertError> {
+        diesel::update(
+            sep38_rate_alerts::table
+                .filter(sep38_rate_alerts::status.eq(ALERT_ACTIVE))
+                .filter(sep38_rate_alerts::expires_at.le(now)),
+        )
+        .set((
+            sep38_rate_alerts::status.eq(ALERT_EXPIRED),
+            sep38_rate_alerts::updated_at.eq(now),
+        ))
+        .execute(conn)
+        .map_err(db_error)
+    }
+
+    /// Checks active alerts on the sampled anchor and pair; returns how many fired.
+    pub async fn evaluate_alerts(
+        conn: &mut PgConnection,
+        sample: &Sep38PriceSample,
+    ) -> Result<usize, RateAlertError> {
+        expire_alerts(conn, Utc::now().naive_utc())?;
+
+        let alerts: Vec<Sep38RateAlert> = sep38_rate_alerts::table
+            .filter(sep38_rate_alerts::status.eq(ALERT_ACTIVE))
+            .filter(sep38_rate_alerts::anchor_slug.eq(&sample.anchor_slug))
+            .filter(sep38_rate_alerts::sell_asset.eq(&sample.sell_asset))
+            .filter(sep38_rate_alerts::buy_asset.eq(&sample.buy_asset))
+            .load(conn)
+            .map_err(db_error)?;
+
+        let mut fired = 0;
+        for alert in alerts
+            .iter()
+            .filter(|a| matches_sample(a, sample) && crossed(a, &sample.price))
+        {
+            trigger_alert(conn, alert, &sample.price).await?;
+            fired += 1;
+        }
+        Ok(fired)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::str::FromStr;
+        use stellar_base::KeyPair;
+
+        const USDC: &str = "stellar:USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+
+        fn pair(buy_delivery_method: Option<&str>) -> SampledPair {
+            SampledPair {
+                slug: "mykobo".to_string(),
+                sell_asset: USDC.to_string(),
+                buy_asset: "iso4217:EUR".to_string(),
+                sell_amount: Amount::from_str("100").unwrap(),
+                sell_delivery_method: None,
+                buy_delivery_method: buy_delivery_method.map(str::to_string),
+            }
+        }
+
+        fn request(
+            slug: &str,
+            buy_asset: &str,
+            buy_delivery_method: Option<&str>,
+        ) -> RateAlertRequest {
+            RateAlertRequest {
+                account: KeyPair::random().unwrap().secret_key().secret_seed(),
+                slug: slug.to_string(),
+                sell_asset: USDC.to_string(),
+                buy_asset: buy_asset.to_string(),
+                sell_delivery_method: None,
+                buy_delivery_method: buy_delivery_method.map(str::to_string),
+                direction: DIRECTION_ABOVE.to_string(),
+                threshold: Amount::from_str("0.9").unwrap(),
+                sell_amount: Amount::from_str("100").unwrap(),
+                expires_at: None,
+                auto_execute: false,
+                funding_method: None,
+            }
+        }
+
+        #[test]
+        fn sampled_pairs_accept_matching_alerts() {
+            let pairs = [pair(Some("SEPA"))];
+            assert!(is_sampled(&pairs, &request("mykobo", "iso4217:EUR", None)));
+            assert!(is_sampled(
+                &pairs,
+                &request("mykobo", "iso4217:EUR", Some("SEPA"))
+            ));
+            assert!(is_sampled(
+                &pairs,
+                &request("mykobo", "iso4217:EUR", Some(""))
+            ));
+        }
+
+        #[test]
+        fn unsampled_pairs_are_rejected() {
+            let pairs = [pair(Some("SEPA"))];
+            assert!(!is_sampled(&pairs, &request("other", "iso4217:EUR", None)));
+            assert!(!is_sampled(&pairs, &request("mykobo", "iso4217:USD", None)));
+            assert!(!is_sampled(
+                &pairs,
+                &request("mykobo", "iso4217:EUR", Some("SWIFT"))
+            ));
+            assert!(!is_sampled(&[], &request("mykobo", "iso4217:EUR", None)));
+        }
+
+        #[test]
+        fn alerts_are_keyed_by_the_public_account() {
+            let keypair = KeyPair::random().unwrap();
+            let secret = keypair.secret_key().secret_seed();
+            assert_eq!(
+                public_account(&secret).unwrap(),
+                keypair.public_key().account_id()
+            );
+            assert!(matches!(
+                public_account(&keypair.public_key().account_id()),
+                Err(RateAlertError::InvalidRequest(_))
+            ));
+        }
+
+        #[test]
+        fn signers_are_found_by_public_account() {
+            let signer = KeyPair::random().unwrap();
+            let other = KeyPair::random().unwrap();
+            let secrets = format!(
+                " {} , not-a-seed,,{}",
+                other.secret_key().secret_seed(),
+                signer.secret_key().secret_seed()
+            );
+
+            assert_eq!(
+                find_signer(&secrets, &signer.public_key().account_id()),
+                Some(signer.secret_key().secret_seed())
+            );
+            assert_eq!(
+                find_signer(&secrets, &other.public_key().account_id()),
+                Some(other.secret_key().secret_seed())
+            );
+            assert_eq!(
+                find_signer(
+                    &secrets,
+                    &KeyPair::random().unwrap().public_key().account_id()
+                ),
+                None
+            );
+            assert_eq!(find_signer("", &signer.public_key().account_id()), None);
+        }
+    }
+}
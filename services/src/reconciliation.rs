@@ -0,0 +1,556 @@
+// Reconciliation
+// Helper functions:
+// Match on-chain withdrawal payments to SEP-6 transactions
+// Record mismatches
+// Issue report
+pub mod reconciliation {
+    use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+    use diesel::prelude::*;
+    use serde::{Deserialize, Serialize};
+    use std::collections::HashMap;
+    use std::str::FromStr;
+    use thiserror::Error;
+    use uuid::Uuid;
+
+    use helpers::horizon::{HorizonClient, HttpHorizonClient, Order, PaymentRecord};
+    use models::{
+        common::establish_connection,
+        money::Amount,
+        reconciliation::{NewReconciliationIssue, ReconciliationIssue},
+        schema::offramp_service::{reconciliation_issues, sep6_transactions},
+        sep6::Sep6Transaction,
+    };
+
+    pub const PAID_NOT_ACKNOWLEDGED: &str = "paid_not_acknowledged";
+    pub const AMOUNT_MISMATCH: &str = "amount_mismatch";
+    pub const ASSET_MISMATCH: &str = "asset_mismatch";
+    pub const DUPLICATE_PAYMENT: &str = "duplicate_payment";
+
+    const PAGE_SIZE: u32 = 200;
+
+    #[derive(Error, Debug)]
+    pub enum ReconciliationError {
+        #[error("Horizon error: {0}")]
+        HorizonError(#[from] helpers::horizon::HorizonError),
+
+        #[error("Invalid request: {0}")]
+        InvalidRequest(String),
+
+        #[error("Issue not found")]
+        IssueNotFound,
+
+        #[error("Database error: {0}")]
+        DatabaseError(String),
+    }
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    pub struct ReconciliationSummary {
+        pub transactions_checked: usize,
+        pub payments_scanned: usize,
+        pub issues_found: usize,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct ReconciliationReport {
+        pub open_issues: i64,
+        pub counts_by_type: HashMap<String, i64>,
+        pub issues: Vec<ReconciliationIssue>,
+    }
+
+    fn env_or<T: FromStr>(name: &str, default: T) -> T {
+        std::env::var(name)
+            .ok()
+            .and_then(|v| v.parse::<T>().ok())
+            .unwrap_or(default)
+    }
+
+    fn payment_created_at(payment: &PaymentRecord) -> Option<NaiveDateTime> {
+        DateTime::parse_from_rfc3339(&payment.created_at)
+            .ok()
+            .map(|dt| dt.naive_utc())
+    }
+
+    fn payment_asset(payment: &PaymentRecord) -> String {
+        match payment.asset_type.as_deref() {
+            Some("native") => "native".to_string(),
+            _ => payment.asset_code.clone().unwrap_or_default(),
+        }
+    }
+
+    fn same_asset(expected: &str, paid: &str) -> bool {
+        // Stored assets may be a bare code or SEP-38 style `stellar:CODE:ISSUER`
+        let code = expected
+            .strip_prefix("stellar:")
+            .unwrap_or(expected)
+            .split(':')
+            .next()
+            .unwrap_or(expected);
+
+        match paid {
+            "native" => code.eq_ignore_ascii_case("native") || code == "XLM",
+            _ => code == paid,
+        }
+    }
+
+    // Withdrawals we've been given payment instructions for, grouped by the anchor's receiving account
+    fn load_candidates(
+        conn: &mut PgConnection,
+    ) -> Result<HashMap<String, Vec<Sep6Transaction>>, ReconciliationError> {
+        let transactions: Vec<Sep6Transaction> = sep6_transactions::table
+            .filter(sep6_transactions::kind.like("withdraw%"))
+            .filter(sep6_transactions::withdraw_anchor_account.is_not_null())
+            .filter(sep6_transactions::withdraw_memo.is_not_null())
+            .select(Sep6Transaction::as_select())
+            .load(conn)
+            .map_err(|e| ReconciliationError::DatabaseError(e.to_string()))?;
+
+        let mut by_account: HashMap<String, Vec<Sep6Transaction>> = HashMap::new();
+        for tx in transactions {
+            if let Some(account) = tx.withdraw_anchor_account.clone() {
+                by_account.entry(account).or_default().push(tx);
+            }
+        }
+
+        Ok(by_account)
+    }
+
+    // Newest-first scan of incoming payments, stopping once we're past the oldest withdrawal
+    async fn scan_payments<H: HorizonClient>(
+        horizon: &H,
+        account: &str,
+        since: Option<NaiveDateTime>,
+        max_pages: usize,
+    ) -> Result<Vec<PaymentRecord>, ReconciliationError> {
+        let mut payments = Vec::new();
+        let mut cursor = None;
+
+        for _ in 0..max_pages {
+            let page = horizon
+                .payments_for_account(account, cursor, PAGE_SIZE, Order::Desc)
+                .await?;
+            let page_len = page.records.len();
+            let mut reached_start = false;
+
+            for payment in page.records {
+                if let (Some(since), Some(created_at)) = (since, payment_created_at(&payment))
+                    && created_at < since
+                {
+                    reached_start = true;
+                    break;
+                }
+
+                let incoming = payment.to.as_deref() == Some(account)
+                    && payment.transaction_successful != Some(false);
+                if incoming {
+                    payments.push(payment);
+                }
+            }
+
+            if reached_start || page_len < PAGE_SIZE as usize || page.next_cursor.is_none() {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+
+        Ok(payments)
+    }
+
+    // Payments carrying the withdrawal's memo, oldest first
+    fn matching_payments<'a>(
+        tx: &Sep6Transaction,
+        payments: &'a [PaymentRecord],
+    ) -> Vec<&'a PaymentRecord> {
+        payments
+            .iter()
+            .rev()
+            .filter(|payment| {
+                payment
+                    .transaction
+                    .as_ref()
+                    .and_then(|t| t.memo.as_deref())
+                    .is_some_and(|memo| Some(memo) == tx.withdraw_memo.as_deref())
+            })
+            .collect()
+    }
+
+    fn find_issues(
+        tx: &Sep6Transaction,
+        account: &str,
+        payments: &[&PaymentRecord],
+        grace: Duration,
+    ) -> Vec<NewReconciliationIssue> {
+        let mut issues = Vec::new();
+        let expected_asset = tx.amount_in_asset.clone();
+
+        let issue = |issue_type: &str, payment: &PaymentRecord, details: String| {
+            NewReconciliationIssue {
+                transaction_id: tx.transaction_id.clone(),
+                issue_type: issue_type.to_string(),
+                stellar_transaction_id: payment.transaction_hash.clone(),
+                anchor_account: account.to_string(),
+                anchor_status: tx.status.clone(),
+                expected_amount: tx.amount_in.clone(),
+                paid_amount: payment
+                    .amount
+                    .as_deref()
+                    .and_then(|a| Amount::from_str(a).ok()),
+                asset: Some(payment_asset(payment)),
+                details: Some(details),
+            }
+        };
+
+        for (index, payment) in payments.iter().enumerate() {
+            if index > 0 {
+                issues.push(issue(
+                    DUPLICATE_PAYMENT,
+                    payment,
+                    format!(
+                        "{} payments carry memo for this withdrawal; first was {}",
+                        payments.len(),
+                        payments[0].transaction_hash
+                    ),
+                ));
+            }
+
+            let paid_asset = payment_asset(payment);
+            if let Some(expected) = &expected_asset
+                && !same_asset(expected, &paid_asset)
+            {
+                issues.push(issue(
+                    ASSET_MISMATCH,
+                    payment,
+                    format!("expected {}, paid {}", expected, paid_asset),
+                ));
+            }
+
+            let paid_amount = payment
+                .amount
+                .as_deref()
+                .and_then(|a| Amount::from_str(a).ok());
+            if let (Some(expected), Some(paid)) = (&tx.amount_in, &paid_amount)
+                && expected != paid
+            {
+                issues.push(issue(
+                    AMOUNT_MISMATCH,
+                    payment,
+                    format!("expected {}, paid {}", expected, paid),
+                ));
+            }
+        }
+
+        // The anchor should have picked the payment up once the grace period has passed
+        if tx.status == "pending_user_transfer_start"
+            && let Some(first) = payments.first()
+            && payment_created_at(first)
+                .is_none_or(|created_at| created_at + grace < Utc::now().naive_utc())
+        {
+            issues.push(issue(
+                PAID_NOT_ACKNOWLEDGED,
+                first,
+                format!(
+                    "payment {} on-chain but anchor still reports {}",
+                    first.transaction_hash, tx.status
+                ),
+            ));
+        }
+
+        issues
+    }
+
+    // 1. Run a reconciliation pass
+    pub async fn reconcile_withdrawals<H: HorizonClient>(
+        horizon: &H,
+    ) -> Result<ReconciliationSummary, ReconciliationError> {
+        let max_pages = env_or("RECONCILIATION_MAX_PAGES", 20usize);
+        let grace = Duration::minutes(env_or("RECONCILIATION_GRACE_MINUTES", 30i64));
+
+        let mut conn = establish_connection()
+            .map_err(|e| ReconciliationError::DatabaseError(e.to_string()))?;
+        let mut summary = ReconciliationSummary::default();
+
+        for (account, transactions) in load_candidates(&mut conn)? {
+            let since = transactions
+                .iter()
+                .map(|tx| tx.started_at.unwrap_or(tx.created_at))
+                .min();
+            let payments = scan_payments(horizon, &account, since, max_pages).await?;
+            summary.payments_scanned += payments.len();
+
+            for tx in &transactions {
+                summary.transactions_checked += 1;
+
+                let matched = matching_payments(tx, &payments);
+                for issue in find_issues(tx, &account, &matched, grace) {
+                    summary.issues_found += diesel::insert_into(reconciliation_issues::table)
+                        .values(&issue)
+                        .on_conflict_do_nothing()
+                        .execute(&mut conn)
+                        .map_err(|e| ReconciliationError::DatabaseError(e.to_string()))?;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    // 2. Issue report
+    pub fn get_report(
+        issue_type: Option<&str>,
+        include_resolved: bool,
+        limit: Option<i64>,
+    ) -> Result<ReconciliationReport, ReconciliationError> {
+        let mut conn = establish_connection()
+            .map_err(|e| ReconciliationError::DatabaseError(e.to_string()))?;
+
+        let counts: Vec<(String, i64)> = reconciliation_issues::table
+            .filter(reconciliation_issues::resolved.eq(false))
+            .group_by(reconciliation_issues::issue_type)
+            .select((reconciliation_issues::issue_type, diesel::dsl::count_star()))
+            .load(&mut conn)
+            .map_err(|e| ReconciliationError::DatabaseError(e.to_string()))?;
+
+        let mut query = reconciliation_issues::table.into_boxed();
+
+        if !include_resolved {
+            query = query.filter(reconciliation_issues::resolved.eq(false));
+        }
+
+        if let Some(t) = issue_type {
+            query = query.filter(reconciliation_issues::issue_type.eq(t.to_string()));
+        }
+
+        let issues = query
+            .order(reconciliation_issues::created_at.desc())
+            .limit(limit.unwrap_or(100))
+            .load(&mut conn)
+            .map_err(|e| ReconciliationError::DatabaseError(e.to_string()))?;
+
+        Ok(ReconciliationReport {
+            open_issues: counts.iter().map(|(_, count)| count).sum(),
+            counts_by_type: counts.into_iter().collect(),
+            issues,
+        })
+    }
+
+    // 3. Mark an issue as handled
+    pub fn resolve_issue(issue_id: &str) -> Result<ReconciliationIssue, ReconciliationError> {
+        let issue_uuid = Uuid::parse_str(issue_id)
+            .map_err(|_| ReconciliationError::InvalidRequest("Invalid issue ID".to_string()))?;
+
+        let mut conn = establish_connection()
+            .map_err(|e| ReconciliationError::DatabaseError(e.to_string()))?;
+
+        let now = Utc::now().naive_utc();
+        diesel::update(reconciliation_issues::table.find(issue_uuid))
+            .set((
+                reconciliation_issues::resolved.eq(true),
+                reconciliation_issues::resolved_at.eq(Some(now)),
+                reconciliation_issues::updated_at.eq(now),
+            ))
+            .get_result(&mut conn)
+            .optional()
+            .map_err(|e| ReconciliationError::DatabaseError(e.to_string()))?
+            .ok_or(ReconciliationError::IssueNotFound)
+    }
+
+    // 4. Background job, scheduled on launch
+    pub async fn run_reconciliation_worker() {
+        let interval = env_or("RECONCILIATION_INTERVAL_SECS", 300u64);
+
+        loop {
+            match HttpHorizonClient::from_env() {
+                Ok(horizon) => {
+                    if let Err(e) = reconcile_withdrawals(&horizon).await {
+                        eprintln!("Error reconciling withdrawals: {:?}", e);
+                    }
+                }
+                Err(e) => eprintln!("Error creating Horizon client: {:?}", e),
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use helpers::horizon::{MockHorizonClient, PaymentsPage};
+
+        const ANCHOR: &str = "GBANCHORACCOUNT";
+
+        fn withdrawal(status: &str, amount: &str, asset: &str, memo: &str) -> Sep6Transaction {
+            serde_json::from_value(serde_json::json!({
+                "id": Uuid::new_v4(),
+                "transaction_id": "tx-1",
+                "kind": "withdrawal",
+                "status": status,
+                "amount_in": amount,
+                "amount_in_asset": asset,
+                "withdraw_anchor_account": ANCHOR,
+                "withdraw_memo": memo,
+                "created_at": "2026-10-19T10:00:00",
+            }))
+            .unwrap()
+        }
+
+        fn payment(
+            hash: &str,
+            amount: &str,
+            asset_code: &str,
+            memo: &str,
+            created_at: &str,
+        ) -> PaymentRecord {
+            serde_json::from_value(serde_json::json!({
+                "id": hash,
+                "paging_token": hash,
+                "type": "payment",
+                "created_at": created_at,
+                "transaction_hash": hash,
+                "transaction_successful": true,
+                "from": "GSENDER",
+                "to": ANCHOR,
+                "amount": amount,
+                "asset_type": if asset_code == "native" { "native" } else { "credit_alphanum4" },
+                "asset_code": if asset_code == "native" { None } else { Some(asset_code) },
+                "transaction": {
+                    "id": hash,
+                    "hash": hash,
+                    "ledger": 1,
+                    "created_at": created_at,
+                    "source_account": "GSENDER",
+                    "memo_type": "text",
+                    "memo": memo,
+                    "successful": true,
+                },
+            }))
+            .unwrap()
+        }
+
+        fn issue_types(issues: &[NewReconciliationIssue]) -> Vec<&str> {
+            issues.iter().map(|i| i.issue_type.as_str()).collect()
+        }
+
+        const LONG_AGO: &str = "2026-01-01T10:05:00Z";
+
+        #[test]
+        fn same_asset_accepts_bare_and_prefixed_codes() {
+            assert!(same_asset("USDC", "USDC"));
+            assert!(same_asset("stellar:USDC:GISSUER", "USDC"));
+            assert!(same_asset("native", "native"));
+            assert!(same_asset("stellar:native", "native"));
+            assert!(same_asset("XLM", "native"));
+            assert!(!same_asset("stellar:USDC:GISSUER", "EURC"));
+            assert!(!same_asset("USDC", "native"));
+        }
+
+        #[test]
+        fn matching_payments_filters_by_memo_oldest_first() {
+            let tx = withdrawal("pending_anchor", "10", "USDC", "memo-1");
+            // Horizon returns newest first
+            let payments = vec![
+                payment("c", "10", "USDC", "memo-1", "2026-10-19T10:20:00Z"),
+                payment("b", "10", "USDC", "other", "2026-10-19T10:10:00Z"),
+                payment("a", "10", "USDC", "memo-1", LONG_AGO),
+            ];
+
+            let matched = matching_payments(&tx, &payments);
+            let hashes: Vec<&str> = matched
+                .iter()
+                .map(|p| p.transaction_hash.as_str())
+                .collect();
+            assert_eq!(hashes, vec!["a", "c"]);
+        }
+
+        #[test]
+        fn matching_payments_ignores_memoless_payments() {
+            let tx = withdrawal("pending_anchor", "10", "USDC", "memo-1");
+            let mut unrelated = payment("a", "10", "USDC", "memo-1", LONG_AGO);
+            unrelated.transaction = None;
+            assert!(matching_payments(&tx, &[unrelated]).is_empty());
+        }
+
+        #[test]
+        fn exact_payment_raises_nothing() {
+            let tx = withdrawal("pending_anchor", "10.50", "stellar:USDC:GISSUER", "memo-1");
+            let paid = payment("a", "10.5000000", "USDC", "memo-1", LONG_AGO);
+            assert!(find_issues(&tx, ANCHOR, &[&paid], Duration::minutes(30)).is_empty());
+        }
+
+        #[test]
+        fn flags_amount_and_asset_mismatches() {
+            let tx = withdrawal("pending_anchor", "10", "USDC", "memo-1");
+            let paid = payment("a", "9.5", "EURC", "memo-1", LONG_AGO);
+
+            let issues = find_issues(&tx, ANCHOR, &[&paid], Duration::minutes(30));
+            assert_eq!(issue_types(&issues), vec![ASSET_MISMATCH, AMOUNT_MISMATCH]);
+            assert_eq!(
+                issues[1].paid_amount,
+                Some(Amount::from_str("9.5").unwrap())
+            );
+        }
+
+        #[test]
+        fn flags_every_payment_after_the_first_as_duplicate() {
+            let tx = withdrawal("pending_anchor", "10", "USDC", "memo-1");
+            let first = payment("a", "10", "USDC", "memo-1", LONG_AGO);
+            let second = payment("b", "10", "USDC", "memo-1", LONG_AGO);
+
+            let issues = find_issues(&tx, ANCHOR, &[&first, &second], Duration::minutes(30));
+            assert_eq!(issue_types(&issues), vec![DUPLICATE_PAYMENT]);
+            assert_eq!(issues[0].stellar_transaction_id, "b");
+        }
+
+        #[test]
+        fn flags_unacknowledged_payments_after_the_grace_period() {
+            let tx = withdrawal("pending_user_transfer_start", "10", "USDC", "memo-1");
+            let paid = payment("a", "10", "USDC", "memo-1", LONG_AGO);
+
+            let issues = find_issues(&tx, ANCHOR, &[&paid], Duration::minutes(30));
+            assert_eq!(issue_types(&issues), vec![PAID_NOT_ACKNOWLEDGED]);
+        }
+
+        #[test]
+        fn waits_out_the_grace_period() {
+            let tx = withdrawal("pending_user_transfer_start", "10", "USDC", "memo-1");
+            let just_now = Utc::now().to_rfc3339();
+            let paid = payment("a", "10", "USDC", "memo-1", &just_now);
+
+            assert!(find_issues(&tx, ANCHOR, &[&paid], Duration::minutes(30)).is_empty());
+        }
+
+        #[tokio::test]
+        async fn scan_keeps_successful_incoming_payments_until_the_start() {
+            let mut outgoing = payment("out", "1", "USDC", "memo-1", "2026-10-19T10:30:00Z");
+            outgoing.to = Some("GELSEWHERE".to_string());
+            let mut failed = payment("failed", "1", "USDC", "memo-1", "2026-10-19T10:25:00Z");
+            failed.transaction_successful = Some(false);
+            let records = vec![
+                outgoing,
+                failed,
+                payment("in", "1", "USDC", "memo-1", "2026-10-19T10:20:00Z"),
+                payment("old", "1", "USDC", "memo-1", "2026-10-19T09:00:00Z"),
+            ];
+
+            let mut horizon = MockHorizonClient::new();
+            horizon
+                .expect_payments_for_account()
+                .times(1)
+                .returning(move |_, _, _, _| {
+                    Ok(PaymentsPage {
+                        records: records.clone(),
+                        next_cursor: Some("next".to_string()),
+                    })
+                });
+
+            let since =
+                NaiveDateTime::parse_from_str("2026-10-19T10:00:00", "%Y-%m-%dT%H:%M:%S").unwrap();
+            let payments = scan_payments(&horizon, ANCHOR, Some(since), 5)
+                .await
+                .unwrap();
+            let hashes: Vec<&str> = payments
+                .iter()
+                .map(|p| p.transaction_hash.as_str())
+                .collect();
+            assert_eq!(hashes, vec!["in"]);
+        }
+    }
+}
@@ -1,16 +1,29 @@
 pub mod sep12 {
 
     use crate::common::get_anchor_config_details;
+    use crate::sep9::sep9::{Sep9Error, Sep9Fields};
+    use crate::storage::storage::{
+        DocumentStore, StorageError, content_hash, document_store_from_env, sanitize_file_name,
+        validate_document,
+    };
+    use crate::webhook::webhook::{enqueue_event, SEP12_STATUS_CHANGED};
     use diesel::prelude::*;
-    use helpers::{auth::authenticate, keypair::generate_keypair};
+    use helpers::{
+        auth::{authenticate, verify_callback_signature},
+        keypair::{generate_keypair, public_account_id},
+    };
     use serde::{Deserialize, Serialize};
     use thiserror::Error;
     use uuid::Uuid;
 
     use models::{
         common::establish_connection,
-        schema::offramp_service::{sep12_customer_files, sep12_customers},
-        sep12::{NewSep12Customer, NewSep12CustomerFile, Sep12Customer},
+        encryption::{EncryptedString, EncryptionError, blind_index_email, blind_index_phone},
+        schema::offramp_service::{sep12_anchor_submissions, sep12_customer_files, sep12_customers},
+        sep12::{
+            NewSep12AnchorSubmission, NewSep12Customer, NewSep12CustomerFile,
+            Sep12AnchorSubmission, Sep12Customer, Sep12CustomerFile, Sep12CustomerPii,
+        },
     };
 
     #[derive(Error, Debug)]
@@ -35,24 +48,87 @@ pub mod sep12 {
 
         #[error("Database error: {0}")]
         DatabaseError(String),
+
+        #[error("Storage error: {0}")]
+        StorageError(#[from] StorageError),
+
+        #[error("File not found")]
+        FileNotFound,
+
+        #[error("Encryption error: {0}")]
+        EncryptionError(#[from] EncryptionError),
+
+        #[error("Invalid KYC field: {0}")]
+        InvalidField(#[from] Sep9Error),
+
+        #[error("Customer has not consented to sharing data with {0}")]
+        ConsentRequired(String),
+    }
+
+    impl From<diesel::result::Error> for Sep12Error {
+        fn from(e: diesel::result::Error) -> Self {
+            Sep12Error::DatabaseError(e.to_string())
+        }
     }
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct Customer {
         pub id: Option<String>,
+        // Not part of the anchor's response body, only of our own
+        #[serde(default)]
         pub account: String,
         pub memo: Option<String>,
         #[serde(rename = "type")]
         pub customer_type: Option<String>,
         pub status: String,
+        #[serde(default, deserialize_with = "named_entries")]
         pub fields: Option<Vec<Field>>,
+        #[serde(default, deserialize_with = "named_entries")]
         pub provided_fields: Option<Vec<ProvidedField>>,
         pub message: Option<String>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    // SEP-12 sends `fields`/`provided_fields` as objects keyed by field name; older anchors
+    // (and our own responses) use a list with a `name` on each entry.
+    fn named_entries<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: serde::de::DeserializeOwned,
+    {
+        use serde::de::Error as _;
+
+        let entries = match Option::<serde_json::Value>::deserialize(deserializer)? {
+            None => return Ok(None),
+            Some(serde_json::Value::Object(object)) => object
+                .into_iter()
+                .map(|(name, mut entry)| {
+                    if let Some(entry) = entry.as_object_mut() {
+                        entry.insert("name".to_string(), name.into());
+                    }
+                    entry
+                })
+                .collect(),
+            Some(serde_json::Value::Array(entries)) => entries,
+            Some(other) => {
+                return Err(D::Error::custom(format!(
+                    "expected an object or list of fields, got {}",
+                    other
+                )));
+            }
+        };
+
+        entries
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<T>, _>>()
+            .map(Some)
+            .map_err(D::Error::custom)
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct Field {
         pub name: String,
+        #[serde(default)]
         pub description: String,
         #[serde(rename = "type")]
         pub field_type: String,
@@ -60,16 +136,46 @@ pub mod sep12 {
         pub choices: Option<Vec<String>>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct ProvidedField {
         pub name: String,
+        #[serde(default)]
         pub description: String,
         #[serde(rename = "type")]
         pub field_type: String,
+        #[serde(default)]
         pub status: String,
         pub error: Option<String>,
     }
 
+    /// What the anchor still needs from a customer, compared against what we hold.
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    pub struct RequirementsDiff {
+        pub status: String,
+        pub customer_id: Option<String>,
+        /// Required fields we have no value for; the user must supply these.
+        pub missing: Vec<Field>,
+        /// Optional fields we have no value for.
+        pub optional: Vec<Field>,
+        /// Provided fields the anchor is still processing or wants verified.
+        pub pending: Vec<ProvidedField>,
+        /// Provided fields the anchor rejected, with its reason in `error`.
+        pub rejected: Vec<ProvidedField>,
+        /// Requested fields we already hold and can send without asking the user.
+        pub known: Vec<String>,
+        /// Fields we sent to the anchor from stored customer data.
+        pub auto_submitted: Vec<String>,
+        pub message: Option<String>,
+    }
+
+    /// The body an anchor posts to a registered SEP-12 callback URL.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct CustomerCallback {
+        pub id: String,
+        pub status: String,
+        pub message: Option<String>,
+    }
+
     #[derive(Debug, Serialize, Deserialize)]
     pub struct VerificationRequest {
         pub id: String,
@@ -194,6 +300,311 @@ pub mod sep12 {
         }
     }
 
+    #[derive(Debug, Deserialize)]
+    struct PutCustomerResponse {
+        id: String,
+    }
+
+    // SEP-12 answers PUT /customer with just the id, so the status comes from GET /customer
+    async fn customer_after_put(
+        response: reqwest::Response,
+        slug: &str,
+        account: &str,
+        memo: Option<&str>,
+        customer_type: Option<&str>,
+    ) -> Result<Customer, Sep12Error> {
+        let PutCustomerResponse { id } = response.json().await?;
+        let mut customer = get_account_kyc(slug, account, memo, customer_type).await?;
+        customer.id = Some(id);
+        Ok(customer)
+    }
+
+    // Lookup hashes for the searchable PII columns
+    fn blind_indexes(
+        email: Option<&EncryptedString>,
+        phone: Option<&EncryptedString>,
+    ) -> Result<(Option<String>, Option<String>), Sep12Error> {
+        Ok((
+            email.map(|e| blind_index_email(e)).transpose()?,
+            phone.map(|p| blind_index_phone(p)).transpose()?,
+        ))
+    }
+
+    // Map SEP-9 fields onto the customer's columns; the full set goes into sep9_fields
+    fn customer_pii(fields: &Sep9Fields) -> Result<Sep12CustomerPii, Sep12Error> {
+        let natural = &fields.natural_person;
+        let column = |value: &Option<String>| value.as_deref().map(EncryptedString::from);
+
+        let email = column(&natural.email_address);
+        let phone = column(&natural.mobile_number);
+        let (email_blind_index, phone_blind_index) = blind_indexes(email.as_ref(), phone.as_ref())?;
+
+        Ok(Sep12CustomerPii {
+            first_name: column(&natural.first_name),
+            last_name: column(&natural.last_name),
+            email,
+            phone,
+            date_of_birth: column(&natural.birth_date),
+            address_street: column(&natural.address),
+            address_city: column(&natural.city),
+            address_state: column(&natural.state_or_province),
+            address_postal_code: column(&natural.postal_code),
+            address_country: column(&natural.address_country_code),
+            email_blind_index,
+            phone_blind_index,
+            sep9_fields: Some(EncryptedString(fields.to_json()?)),
+        })
+    }
+
+    fn notify_status_change(
+        conn: &mut PgConnection,
+        customer: &Sep12Customer,
+        slug: &str,
+        previous_status: &str,
+        status: &str,
+    ) -> Result<(), Sep12Error> {
+        if previous_status == status {
+            return Ok(());
+        }
+
+        enqueue_event(
+            conn,
+            SEP12_STATUS_CHANGED,
+            serde_json::json!({
+                "customer_id": customer.id,
+                "anchor": slug,
+                "customer_type": customer.customer_type,
+                "previous_status": previous_status,
+                "status": status,
+            }),
+        )
+        .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn find_submission(
+        conn: &mut PgConnection,
+        customer_id: Uuid,
+        slug: &str,
+    ) -> Result<Option<Sep12AnchorSubmission>, Sep12Error> {
+        sep12_anchor_submissions::table
+            .filter(sep12_anchor_submissions::customer_id.eq(customer_id))
+            .filter(sep12_anchor_submissions::anchor_slug.eq(slug))
+            .first(conn)
+            .optional()
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))
+    }
+
+    // The customer's status at this anchor, before any change we're about to record
+    fn anchor_status(
+        customer: &Sep12Customer,
+        submission: Option<&Sep12AnchorSubmission>,
+    ) -> String {
+        submission
+            .map(|s| s.status.clone())
+            .unwrap_or_else(|| customer.status.clone())
+    }
+
+    // The id this anchor knows the customer by; older rows only have our own id
+    fn anchor_customer_id_for(
+        customer: &Sep12Customer,
+        submission: Option<&Sep12AnchorSubmission>,
+        slug: &str,
+    ) -> Option<String> {
+        submission
+            .and_then(|s| s.anchor_customer_id.clone())
+            .or_else(|| {
+                customer
+                    .anchor_customer_id
+                    .clone()
+                    .filter(|_| customer.anchor_slug.as_deref().is_none_or(|s| s == slug))
+            })
+    }
+
+    fn submitted_field_names(submission: Option<&Sep12AnchorSubmission>) -> Vec<String> {
+        submission
+            .and_then(|s| serde_json::from_str(&s.submitted_fields).ok())
+            .unwrap_or_default()
+    }
+
+    // Upsert what we've sent an anchor and the status it reported back
+    fn record_submission(
+        conn: &mut PgConnection,
+        customer: &Sep12Customer,
+        slug: &str,
+        anchor_customer_id: Option<&str>,
+        status: &str,
+        fields: &[String],
+    ) -> Result<Sep12AnchorSubmission, Sep12Error> {
+        let now = chrono::Utc::now().naive_utc();
+        let existing = find_submission(conn, customer.id, slug)?;
+
+        let mut submitted = submitted_field_names(existing.as_ref());
+        for field in fields {
+            if !submitted.contains(field) {
+                submitted.push(field.clone());
+            }
+        }
+        let submitted = serde_json::to_string(&submitted)
+            .map_err(|e| Sep12Error::InvalidRequest(e.to_string()))?;
+
+        let submission = match existing {
+            Some(existing) => diesel::update(sep12_anchor_submissions::table.find(existing.id))
+                .set((
+                    sep12_anchor_submissions::anchor_customer_id
+                        .eq(anchor_customer_id.or(existing.anchor_customer_id.as_deref())),
+                    sep12_anchor_submissions::status.eq(status),
+                    sep12_anchor_submissions::submitted_fields.eq(submitted),
+                    sep12_anchor_submissions::last_synced_at.eq(Some(now)),
+                    sep12_anchor_submissions::updated_at.eq(now),
+                ))
+                .get_result(conn),
+            // A first submission made directly by the user is their consent for that anchor
+            None => diesel::insert_into(sep12_anchor_submissions::table)
+                .values(&NewSep12AnchorSubmission {
+                    customer_id: customer.id,
+                    anchor_slug: slug.to_string(),
+                    anchor_customer_id: anchor_customer_id.map(|id| id.to_string()),
+                    status: status.to_string(),
+                    submitted_fields: submitted,
+                    consent_given_at: Some(now),
+                    last_synced_at: Some(now),
+                })
+                .get_result(conn),
+        }
+        .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        // The profile's own status follows the anchor it was first created with
+        if customer.anchor_slug.as_deref().is_none_or(|s| s == slug) {
+            diesel::update(sep12_customers::table.find(customer.id))
+                .set((
+                    sep12_customers::status.eq(status),
+                    sep12_customers::updated_at.eq(now),
+                ))
+                .execute(conn)
+                .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+        }
+
+        Ok(submission)
+    }
+
+    // Record a status reported by the anchor, emitting an event when it changed
+    fn apply_customer_status(
+        conn: &mut PgConnection,
+        customer: &Sep12Customer,
+        slug: &str,
+        status: &str,
+    ) -> Result<(), Sep12Error> {
+        let submission = find_submission(conn, customer.id, slug)?;
+        let previous_status = anchor_status(customer, submission.as_ref());
+        if previous_status == status {
+            return Ok(());
+        }
+
+        let anchor_customer_id = anchor_customer_id_for(customer, submission.as_ref(), slug);
+        conn.transaction::<_, Sep12Error, _>(|conn| {
+            record_submission(conn, customer, slug, anchor_customer_id.as_deref(), status, &[])?;
+            notify_status_change(conn, customer, slug, &previous_status, status)
+        })
+    }
+
+    fn load_customer(conn: &mut PgConnection, customer_id: &str) -> Result<Sep12Customer, Sep12Error> {
+        let customer_uuid = Uuid::parse_str(customer_id)
+            .map_err(|_| Sep12Error::InvalidRequest("Invalid customer ID".to_string()))?;
+
+        sep12_customers::table
+            .find(customer_uuid)
+            .first(conn)
+            .optional()
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?
+            .ok_or(Sep12Error::CustomerNotFound)
+    }
+
+    // Authenticate with the anchor and resolve its KYC server
+    async fn kyc_server_and_token(slug: &str, account: &str) -> Result<(String, String), Sep12Error> {
+        let keypair = generate_keypair(account).map_err(|_| Sep12Error::Keypairgenerationfailed)?;
+        let anchor_service = helpers::stellartoml::AnchorService::new();
+
+        let anchor_config = get_anchor_config_details(&anchor_service, slug)
+            .await
+            .map_err(|_| Sep12Error::AuthFailed)?;
+        let jwt = authenticate(&anchor_service, slug, &keypair)
+            .await
+            .map_err(|_| Sep12Error::AuthFailed)?;
+
+        let kyc_server = anchor_config
+            .general_info
+            .kyc_server
+            .ok_or_else(|| Sep12Error::InvalidRequest("Anchor has no KYC server".to_string()))?;
+
+        Ok((kyc_server, jwt))
+    }
+
+    async fn read_customer_file<S: DocumentStore>(
+        store: &S,
+        file: &Sep12CustomerFile,
+    ) -> Result<Vec<u8>, Sep12Error> {
+        let bytes = store.get(&file.storage_path).await?;
+
+        if let Some(expected) = &file.content_hash
+            && *expected != content_hash(&bytes)
+        {
+            return Err(StorageError::IntegrityMismatch(file.storage_path.clone()).into());
+        }
+
+        Ok(bytes)
+    }
+
+    fn validate_files(files: &[(String, Vec<u8>, String)]) -> Result<(), Sep12Error> {
+        for (name, content, content_type) in files {
+            validate_document(name, content_type, content)?;
+        }
+        Ok(())
+    }
+
+    // Write uploaded documents to the configured store and record them against the customer
+    async fn store_customer_files(
+        conn: &mut PgConnection,
+        customer_id: Uuid,
+        files: &[(String, Vec<u8>, String)],
+    ) -> Result<(), Sep12Error> {
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let store = document_store_from_env()?;
+
+        for (name, content, content_type) in files {
+            let file_id = Uuid::new_v4();
+            let storage_path = format!(
+                "customers/{}/{}_{}",
+                customer_id,
+                file_id,
+                sanitize_file_name(name)
+            );
+
+            store.put(&storage_path, content, content_type).await?;
+
+            let new_file = NewSep12CustomerFile {
+                customer_id,
+                file_name: name.to_string(),
+                content_type: content_type.to_string(),
+                size: content.len() as i64,
+                storage_path,
+                purpose: name.to_string(),
+                content_hash: Some(content_hash(content)),
+            };
+
+            diesel::insert_into(sep12_customer_files::table)
+                .values(&new_file)
+                .execute(conn)
+                .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
     // 3. Create account KYC
     pub async fn create_account_kyc(
         slug: &str,
@@ -204,6 +615,8 @@ pub mod sep12 {
         files: Vec<(String, Vec<u8>, String)>,
     ) -> Result<Customer, Sep12Error> {
         let client = reqwest::Client::new();
+        validate_files(&files)?;
+        let sep9_fields = Sep9Fields::from_pairs(&fields)?;
 
         let keypair = match generate_keypair(account) {
             Ok(kp) => kp,
@@ -227,7 +640,7 @@ pub mod sep12 {
             |s| s.to_string(), // Use the string value if Some
         );
         let mut form = reqwest::multipart::Form::new()
-            .text("account", account.to_string())
+            .text("account", keypair.public_key().account_id())
             .text("type", customer_type.to_string());
 
         if let Some(m) = memo {
@@ -253,7 +666,8 @@ pub mod sep12 {
             .await?;
 
         if response.status().is_success() {
-            let customer_response: Customer = response.json().await?;
+            let customer_response =
+                customer_after_put(response, slug, account, memo, Some(customer_type)).await?;
 
             // Save to database
             let mut conn =
@@ -265,28 +679,9 @@ pub mod sep12 {
                 memo_type: None, // You may need to get this from somewhere
                 customer_type: customer_type.to_string(),
                 status: customer_response.status.clone(),
-                first_name: fields
-                    .iter()
-                    .find(|(n, _)| *n == "first_name")
-                    .map(|(_, v)| v.to_string()),
-                last_name: fields
-                    .iter()
-                    .find(|(n, _)| *n == "last_name")
-                    .map(|(_, v)| (*v).to_string()),
-                email: fields
-                    .iter()
-                    .find(|(n, _)| *n == "email")
-                    .map(|(_, v)| (*v).to_string()),
-                phone: fields
-                    .iter()
-                    .find(|(n, _)| *n == "phone_number")
-                    .map(|(_, v)| (*v).to_string()),
-                date_of_birth: None, // Parse from fields if available
-                address_street: None,
-                address_city: None,
-                address_state: None,
-                address_postal_code: None,
-                address_country: None,
+                anchor_slug: Some(slug.to_string()),
+                anchor_customer_id: customer_response.id.clone(),
+                pii: customer_pii(&sep9_fields)?,
             };
 
             let customer: Sep12Customer = diesel::insert_into(sep12_customers::table)
@@ -294,27 +689,18 @@ pub mod sep12 {
                 .get_result(&mut conn)
                 .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
 
-            // Save files if any
-            for (name, content, content_type) in &files {
-                let file_id = Uuid::new_v4();
-                let storage_path = format!("customers/{}/{}_{}", customer.id, file_id, name);
+            let submitted: Vec<String> = fields.iter().map(|(name, _)| name.clone()).collect();
+            record_submission(
+                &mut conn,
+                &customer,
+                slug,
+                customer_response.id.as_deref(),
+                &customer_response.status,
+                &submitted,
+            )?;
 
-                // In a real implementation, you would save the file to storage here
-
-                let new_file = NewSep12CustomerFile {
-                    customer_id: customer.id,
-                    file_name: name.to_string(),
-                    content_type: content_type.to_string(),
-                    size: content.len() as i64,
-                    storage_path: storage_path.clone(),
-                    purpose: name.to_string(),
-                };
-
-                diesel::insert_into(sep12_customer_files::table)
-                    .values(&new_file)
-                    .execute(&mut conn)
-                    .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
-            }
+            // Save files if any
+            store_customer_files(&mut conn, customer.id, &files).await?;
 
             Ok(customer_response)
         } else {
@@ -332,6 +718,7 @@ pub mod sep12 {
         account: &str,
     ) -> Result<Customer, Sep12Error> {
         let client = reqwest::Client::new();
+        validate_files(&files)?;
 
         let keypair = match generate_keypair(account) {
             Ok(kp) => kp,
@@ -348,6 +735,13 @@ pub mod sep12 {
             .first(&mut conn)
             .map_err(|_| Sep12Error::CustomerNotFound)?;
 
+        // Submitted fields are layered over what we already hold for the customer
+        let mut sep9_fields = match &customer.sep9_fields {
+            Some(stored) => Sep9Fields::from_json(stored)?,
+            None => Sep9Fields::default(),
+        };
+        sep9_fields.merge(&fields)?;
+
         // Get anchor configuration for authentication
         let anchor_config =
             get_anchor_config_details(&helpers::stellartoml::AnchorService::new(), slug)
@@ -366,7 +760,10 @@ pub mod sep12 {
             || "".to_string(), // Default value if None
             |s| s.to_string(), // Use the string value if Some
         );
-        let mut form = reqwest::multipart::Form::new().text("id", customer_id.to_string());
+        let submission = find_submission(&mut conn, customer.id, slug)?;
+        let anchor_customer_id = anchor_customer_id_for(&customer, submission.as_ref(), slug)
+            .unwrap_or_else(|| customer_id.to_string());
+        let mut form = reqwest::multipart::Form::new().text("id", anchor_customer_id.clone());
 
         for (name, value) in fields.iter() {
             form = form.text(name.to_string(), value.to_string());
@@ -387,58 +784,45 @@ pub mod sep12 {
             .await?;
 
         if response.status().is_success() {
-            let customer_response: Customer = response.json().await?;
+            let customer_response = customer_after_put(
+                response,
+                slug,
+                account,
+                customer.memo.as_deref(),
+                Some(&customer.customer_type),
+            )
+            .await?;
+
+            let previous_status = anchor_status(&customer, submission.as_ref());
+            let submitted: Vec<String> = fields.iter().map(|(name, _)| name.clone()).collect();
+            let pii = customer_pii(&sep9_fields)?;
 
             // Update customer in database
-            let first_name = fields
-                .iter()
-                .find(|(n, _)| *n == "first_name")
-                .map(|(_, v)| v.to_string());
-            let last_name = fields
-                .iter()
-                .find(|(n, _)| *n == "last_name")
-                .map(|(_, v)| v.to_string());
-            let email = fields
-                .iter()
-                .find(|(n, _)| *n == "email")
-                .map(|(_, v)| v.to_string());
-            let phone = fields
-                .iter()
-                .find(|(n, _)| *n == "phone_number")
-                .map(|(_, v)| v.to_string());
+            conn.transaction::<_, Sep12Error, _>(|conn| {
+                diesel::update(sep12_customers::table.find(customer.id))
+                    .set(pii)
+                    .execute(conn)
+                    .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
 
-            diesel::update(sep12_customers::table.find(customer.id))
-                .set((
-                    sep12_customers::status.eq(&customer_response.status),
-                    sep12_customers::first_name.eq(first_name.as_ref()),
-                    sep12_customers::last_name.eq(last_name.as_ref()),
-                    sep12_customers::email.eq(email.as_ref()),
-                    sep12_customers::phone.eq(phone.as_ref()),
-                ))
-                .execute(&mut conn)
-                .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+                record_submission(
+                    conn,
+                    &customer,
+                    slug,
+                    Some(customer_response.id.as_deref().unwrap_or(&anchor_customer_id)),
+                    &customer_response.status,
+                    &submitted,
+                )?;
+                notify_status_change(
+                    conn,
+                    &customer,
+                    slug,
+                    &previous_status,
+                    &customer_response.status,
+                )
+            })?;
 
             // Save new files if any
-            for (name, content, content_type) in files {
-                let file_id = Uuid::new_v4();
-                let storage_path = format!("customers/{}/{}_{}", customer.id, file_id, name);
-
-                // In a real implementation, you would save the file to storage here
-
-                let new_file = NewSep12CustomerFile {
-                    customer_id: customer.id,
-                    file_name: name.to_string(),
-                    content_type: content_type.to_string(),
-                    size: content.len() as i64,
-                    storage_path: storage_path.clone(),
-                    purpose: name.to_string(),
-                };
-
-                diesel::insert_into(sep12_customer_files::table)
-                    .values(&new_file)
-                    .execute(&mut conn)
-                    .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
-            }
+            store_customer_files(&mut conn, customer.id, &files).await?;
 
             Ok(customer_response)
         } else {
@@ -453,32 +837,38 @@ pub mod sep12 {
         account: &str,
         memo: Option<&str>,
     ) -> Result<(), Sep12Error> {
-        let client = reqwest::Client::new();
+        request_anchor_deletion(slug, account, memo).await?;
 
-        let keypair = match generate_keypair(account) {
-            Ok(kp) => kp,
-            Err(_) => return Err(Sep12Error::AuthFailed),
-        };
-        // Get anchor configuration for authentication
-        let anchor_config =
-            get_anchor_config_details(&helpers::stellartoml::AnchorService::new(), slug)
-                .await
-                .map_err(|_| Sep12Error::AuthFailed)?;
+        // Delete from database
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
 
-        let jwt =
-            match authenticate(&helpers::stellartoml::AnchorService::new(), slug, &keypair).await {
-                Ok(token) => token,
-                Err(_) => return Err(Sep12Error::Keypairgenerationfailed),
-            };
+        let mut query = sep12_customers::table
+            .filter(sep12_customers::account.eq(account))
+            .into_boxed();
+        if let Some(m) = memo {
+            query = query.filter(sep12_customers::memo.eq(m));
+        }
+        let customers: Vec<Sep12Customer> = query
+            .load(&mut conn)
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
 
-        let kyc_server = &anchor_config.general_info.kyc_server;
-        // Unwrap the Option or provide a default value
-        let kyc_server_str = kyc_server.as_ref().map_or_else(
-            || "".to_string(), // Default value if None
-            |s| s.to_string(), // Use the string value if Some
-        );
-        let mut request = client
-            .delete(&format!("{}/customer/{}", kyc_server_str, account))
+        purge_customers(&mut conn, &customers).await?;
+
+        Ok(())
+    }
+
+    /// DELETE /customer/:account at one anchor, leaving our own records alone.
+    pub async fn request_anchor_deletion(
+        slug: &str,
+        account: &str,
+        memo: Option<&str>,
+    ) -> Result<(), Sep12Error> {
+        let (kyc_server, jwt) = kyc_server_and_token(slug, account).await?;
+        let public_account = public_account_id(account).map_err(|_| Sep12Error::AuthFailed)?;
+
+        let mut request = reqwest::Client::new()
+            .delete(format!("{}/customer/{}", kyc_server, public_account))
             .bearer_auth(jwt);
 
         if let Some(m) = memo {
@@ -488,29 +878,6 @@ pub mod sep12 {
         let response = request.send().await?;
 
         if response.status().is_success() {
-            // Delete from database
-            let mut conn =
-                establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
-
-            match memo {
-                Some(m) => {
-                    diesel::delete(
-                        sep12_customers::table
-                            .filter(sep12_customers::account.eq(account))
-                            .filter(sep12_customers::memo.eq(m)),
-                    )
-                    .execute(&mut conn)
-                    .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
-                }
-                __ => {
-                    diesel::delete(
-                        sep12_customers::table.filter(sep12_customers::account.eq(account)),
-                    )
-                    .execute(&mut conn)
-                    .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
-                }
-            }
-
             Ok(())
         } else if response.status() == 404 {
             Err(Sep12Error::CustomerNotFound)
@@ -521,4 +888,804 @@ pub mod sep12 {
             )))
         }
     }
+
+    /// Removes customers with their stored documents and anchor submissions.
+    ///
+    /// Returns the number of customers and files deleted.
+    pub async fn purge_customers(
+        conn: &mut PgConnection,
+        customers: &[Sep12Customer],
+    ) -> Result<(usize, usize), Sep12Error> {
+        if customers.is_empty() {
+            return Ok((0, 0));
+        }
+
+        let files_deleted = delete_customer_documents(conn, customers).await?;
+        let customers_deleted = conn
+            .transaction::<_, diesel::result::Error, _>(|conn| {
+                delete_customer_rows(conn, customers)
+            })
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        Ok((customers_deleted, files_deleted))
+    }
+
+    /// Removes the customers' documents from the document store, leaving their rows alone.
+    ///
+    /// Deleting a document that is already gone succeeds, so this is safe to repeat.
+    pub async fn delete_customer_documents(
+        conn: &mut PgConnection,
+        customers: &[Sep12Customer],
+    ) -> Result<usize, Sep12Error> {
+        let ids: Vec<Uuid> = customers.iter().map(|c| c.id).collect();
+        let files: Vec<Sep12CustomerFile> = sep12_customer_files::table
+            .filter(sep12_customer_files::customer_id.eq_any(&ids))
+            .load(conn)
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        if !files.is_empty() {
+            let store = document_store_from_env()?;
+            for file in &files {
+                store.delete(&file.storage_path).await?;
+            }
+        }
+
+        Ok(files.len())
+    }
+
+    /// Deletes customer rows and their file records; call it inside the caller's transaction.
+    pub fn delete_customer_rows(
+        conn: &mut PgConnection,
+        customers: &[Sep12Customer],
+    ) -> Result<usize, diesel::result::Error> {
+        let ids: Vec<Uuid> = customers.iter().map(|c| c.id).collect();
+        diesel::delete(
+            sep12_customer_files::table.filter(sep12_customer_files::customer_id.eq_any(&ids)),
+        )
+        .execute(conn)?;
+        diesel::delete(sep12_customers::table.filter(sep12_customers::id.eq_any(&ids))).execute(conn)
+    }
+
+    // 6. List stored documents for a customer
+    pub fn list_customer_files(customer_id: &str) -> Result<Vec<Sep12CustomerFile>, Sep12Error> {
+        let customer_uuid = Uuid::parse_str(customer_id)
+            .map_err(|_| Sep12Error::InvalidRequest("Invalid customer ID".to_string()))?;
+
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        sep12_customer_files::table
+            .filter(sep12_customer_files::customer_id.eq(customer_uuid))
+            .order(sep12_customer_files::created_at.desc())
+            .load(&mut conn)
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))
+    }
+
+    // 7. Fetch a stored document, checking it hasn't changed since upload
+    pub async fn get_customer_file(
+        file_id: &str,
+    ) -> Result<(Sep12CustomerFile, Vec<u8>), Sep12Error> {
+        let file_uuid = Uuid::parse_str(file_id)
+            .map_err(|_| Sep12Error::InvalidRequest("Invalid file ID".to_string()))?;
+
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        let file: Sep12CustomerFile = sep12_customer_files::table
+            .find(file_uuid)
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?
+            .ok_or(Sep12Error::FileNotFound)?;
+
+        let bytes = read_customer_file(&document_store_from_env()?, &file).await?;
+
+        Ok((file, bytes))
+    }
+
+    // 8. Look customers up by email or phone without decrypting every row
+    pub fn find_customers(
+        email: Option<&str>,
+        phone: Option<&str>,
+    ) -> Result<Vec<Sep12Customer>, Sep12Error> {
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        let mut query = sep12_customers::table.into_boxed();
+
+        if let Some(e) = email {
+            query = query.filter(sep12_customers::email_blind_index.eq(blind_index_email(e)?));
+        }
+
+        if let Some(p) = phone {
+            query = query.filter(sep12_customers::phone_blind_index.eq(blind_index_phone(p)?));
+        }
+
+        query
+            .load(&mut conn)
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))
+    }
+
+    /// Most recently updated local customer for an account, narrowed by memo for shared
+    /// accounts.
+    pub fn find_customer_by_account(
+        account: &str,
+        memo: Option<&str>,
+    ) -> Result<Option<Sep12Customer>, Sep12Error> {
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        let mut query = sep12_customers::table
+            .filter(sep12_customers::account.eq(account))
+            .into_boxed();
+        if let Some(m) = memo {
+            query = query.filter(sep12_customers::memo.eq(m));
+        }
+
+        query
+            .order(sep12_customers::updated_at.desc())
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))
+    }
+
+    // 9. Re-encrypt customer PII under the active master key
+    //
+    // Reading decrypts with whichever key sealed the row and writing seals with the
+    // active key, so rewriting each row is all a rotation needs. Plaintext rows left
+    // over from before encryption are encrypted on the way through.
+    pub fn rotate_pii_keys(batch_size: i64) -> Result<usize, Sep12Error> {
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        let mut last_id: Option<Uuid> = None;
+        let mut rotated = 0;
+
+        loop {
+            let mut query = sep12_customers::table
+                .order(sep12_customers::id.asc())
+                .limit(batch_size)
+                .into_boxed();
+            if let Some(id) = last_id {
+                query = query.filter(sep12_customers::id.gt(id));
+            }
+
+            let batch: Vec<Sep12Customer> = query
+                .load(&mut conn)
+                .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+            let Some(last) = batch.last() else {
+                break;
+            };
+            last_id = Some(last.id);
+
+            let updates = batch
+                .iter()
+                .map(|customer| {
+                    let mut pii = Sep12CustomerPii::from(customer);
+                    (pii.email_blind_index, pii.phone_blind_index) =
+                        blind_indexes(customer.email.as_ref(), customer.phone.as_ref())?;
+                    Ok((customer.id, pii))
+                })
+                .collect::<Result<Vec<_>, Sep12Error>>()?;
+
+            conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                for (id, pii) in &updates {
+                    diesel::update(sep12_customers::table.find(id))
+                        .set(pii)
+                        .execute(conn)?;
+                }
+                Ok(())
+            })
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+            rotated += batch.len();
+        }
+
+        Ok(rotated)
+    }
+
+    // 10. Submit a verification code (e.g. `mobile_number_verification`) to the anchor
+    pub async fn submit_verification(
+        slug: &str,
+        account: &str,
+        request: &VerificationRequest,
+    ) -> Result<Customer, Sep12Error> {
+        if !request.field.ends_with("_verification") {
+            return Err(Sep12Error::InvalidRequest(format!(
+                "{} is not a verification field",
+                request.field
+            )));
+        }
+
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+        let customer = load_customer(&mut conn, &request.id)?;
+        let submission = find_submission(&mut conn, customer.id, slug)?;
+        let anchor_customer_id = anchor_customer_id_for(&customer, submission.as_ref(), slug)
+            .unwrap_or_else(|| request.id.clone());
+
+        let (kyc_server, jwt) = kyc_server_and_token(slug, account).await?;
+
+        let mut body = serde_json::Map::new();
+        body.insert("id".to_string(), anchor_customer_id.into());
+        body.insert(request.field.clone(), request.value.clone().into());
+
+        let response = reqwest::Client::new()
+            .put(format!("{}/customer/verification", kyc_server))
+            .bearer_auth(jwt)
+            .json(&body)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            let customer_response: Customer = response.json().await?;
+            apply_customer_status(&mut conn, &customer, slug, &customer_response.status)?;
+            Ok(customer_response)
+        } else if response.status() == 404 {
+            Err(Sep12Error::CustomerNotFound)
+        } else {
+            let error = response.text().await?;
+            Err(Sep12Error::VerificationFailed(error))
+        }
+    }
+
+    fn callback_base_url() -> Result<String, Sep12Error> {
+        std::env::var("SEP12_CALLBACK_URL")
+            .map(|url| url.trim_end_matches('/').to_string())
+            .map_err(|_| Sep12Error::InvalidRequest("SEP12_CALLBACK_URL must be set".to_string()))
+    }
+
+    // 11. PUT /customer/callback, defaulting to our own receiver for this anchor
+    pub async fn register_callback(
+        slug: &str,
+        account: &str,
+        customer_id: &str,
+        callback_url: Option<&str>,
+    ) -> Result<String, Sep12Error> {
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+        let customer = load_customer(&mut conn, customer_id)?;
+
+        let url = match callback_url {
+            Some(url) => url.to_string(),
+            None => format!("{}/{}", callback_base_url()?, slug),
+        };
+
+        let (kyc_server, jwt) = kyc_server_and_token(slug, account).await?;
+        let public_account =
+            public_account_id(&customer.account).map_err(|_| Sep12Error::AuthFailed)?;
+
+        let mut body = serde_json::json!({
+            "url": url,
+            "account": public_account,
+        });
+        let submission = find_submission(&mut conn, customer.id, slug)?;
+        if let Some(anchor_customer_id) = anchor_customer_id_for(&customer, submission.as_ref(), slug)
+        {
+            body["id"] = anchor_customer_id.into();
+        }
+        if let Some(memo) = &customer.memo {
+            body["memo"] = memo.clone().into();
+        }
+
+        let response = reqwest::Client::new()
+            .put(format!("{}/customer/callback", kyc_server))
+            .bearer_auth(jwt)
+            .json(&body)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(url)
+        } else if response.status() == 404 {
+            Err(Sep12Error::CustomerNotFound)
+        } else {
+            let error = response.text().await?;
+            Err(Sep12Error::InvalidRequest(error))
+        }
+    }
+
+    // 12. Inbound status callback from an anchor
+    pub async fn handle_callback(
+        slug: &str,
+        signature: Option<&str>,
+        body: &str,
+    ) -> Result<(), Sep12Error> {
+        let signature = signature
+            .ok_or_else(|| Sep12Error::VerificationFailed("Missing signature".to_string()))?;
+
+        // Anchors sign over the host of the callback URL they were given
+        let base_url = callback_base_url()?;
+        let host = url::Url::parse(&base_url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_string()))
+            .ok_or_else(|| Sep12Error::InvalidRequest("Invalid SEP12_CALLBACK_URL".to_string()))?;
+        let max_age = std::env::var("SEP12_CALLBACK_MAX_AGE_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(120);
+
+        let anchor_config =
+            get_anchor_config_details(&helpers::stellartoml::AnchorService::new(), slug)
+                .await
+                .map_err(|_| Sep12Error::AuthFailed)?;
+
+        verify_callback_signature(
+            &anchor_config.general_info.signing_key,
+            signature,
+            &host,
+            body,
+            max_age,
+        )
+        .map_err(|e| Sep12Error::VerificationFailed(e.to_string()))?;
+
+        let update: CustomerCallback = serde_json::from_str(body)
+            .map_err(|e| Sep12Error::InvalidRequest(format!("Invalid callback body: {}", e)))?;
+
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        let customer: Sep12Customer = sep12_anchor_submissions::table
+            .inner_join(sep12_customers::table)
+            .filter(sep12_anchor_submissions::anchor_slug.eq(slug))
+            .filter(sep12_anchor_submissions::anchor_customer_id.eq(&update.id))
+            .select(sep12_customers::all_columns)
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?
+            .ok_or(Sep12Error::CustomerNotFound)?;
+
+        apply_customer_status(&mut conn, &customer, slug, &update.status)
+    }
+
+    // Split the anchor's view of the customer into what's outstanding and what we could fill in
+    fn diff_requirements(
+        response: &Customer,
+        known: Option<&Sep9Fields>,
+    ) -> (RequirementsDiff, Vec<(String, String)>) {
+        let mut diff = RequirementsDiff {
+            status: response.status.clone(),
+            customer_id: response.id.clone(),
+            message: response.message.clone(),
+            ..Default::default()
+        };
+        let mut fillable = Vec::new();
+
+        for field in response.fields.iter().flatten() {
+            // Documents aren't kept as SEP-9 values, so they always come from the user
+            let stored = known
+                .filter(|_| field.field_type != "binary")
+                .and_then(|k| k.get(&field.name));
+
+            match stored {
+                Some(value) => fillable.push((field.name.clone(), value)),
+                None if field.optional.unwrap_or(false) => diff.optional.push(field.clone()),
+                None => diff.missing.push(field.clone()),
+            }
+        }
+
+        for provided in response.provided_fields.iter().flatten() {
+            match provided.status.as_str() {
+                "REJECTED" => diff.rejected.push(provided.clone()),
+                "PROCESSING" | "VERIFICATION_REQUIRED" => diff.pending.push(provided.clone()),
+                _ => {}
+            }
+        }
+
+        (diff, fillable)
+    }
+
+    // 13. Requirements diff, optionally sending the anchor fields we already hold
+    pub async fn get_requirements_diff(
+        slug: &str,
+        account: &str,
+        memo: Option<&str>,
+        customer_type: Option<&str>,
+        auto_submit: bool,
+    ) -> Result<RequirementsDiff, Sep12Error> {
+        let response = get_account_kyc(slug, account, memo, customer_type).await?;
+
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        let mut query = sep12_customers::table
+            .filter(sep12_customers::account.eq(account))
+            .into_boxed();
+        if let Some(m) = memo {
+            query = query.filter(sep12_customers::memo.eq(m));
+        }
+        if let Some(t) = customer_type {
+            query = query.filter(sep12_customers::customer_type.eq(t));
+        }
+        let customer: Option<Sep12Customer> = query
+            .order(sep12_customers::updated_at.desc())
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        let known = customer
+            .as_ref()
+            .and_then(|c| c.sep9_fields.as_deref())
+            .map(Sep9Fields::from_json)
+            .transpose()?;
+
+        let (mut diff, fillable) = diff_requirements(&response, known.as_ref());
+
+        match customer {
+            Some(customer) if auto_submit && !fillable.is_empty() => {
+                let submitted: Vec<String> = fillable.iter().map(|(name, _)| name.clone()).collect();
+                let updated = update_account_kyc(
+                    slug,
+                    &customer.id.to_string(),
+                    fillable,
+                    Vec::new(),
+                    account,
+                )
+                .await?;
+
+                // Whatever the anchor still lists after our submission is for the user
+                (diff, _) = diff_requirements(&updated, None);
+                diff.auto_submitted = submitted;
+            }
+            _ => diff.known = fillable.into_iter().map(|(name, _)| name).collect(),
+        }
+
+        Ok(diff)
+    }
+
+    // 14. Per-anchor submissions for a customer profile
+    pub fn list_submissions(customer_id: &str) -> Result<Vec<Sep12AnchorSubmission>, Sep12Error> {
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+        let customer = load_customer(&mut conn, customer_id)?;
+
+        sep12_anchor_submissions::table
+            .filter(sep12_anchor_submissions::customer_id.eq(customer.id))
+            .order(sep12_anchor_submissions::created_at.asc())
+            .load(&mut conn)
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))
+    }
+
+    fn has_consent(submission: &Sep12AnchorSubmission) -> bool {
+        match (submission.consent_given_at, submission.consent_revoked_at) {
+            (Some(given), Some(revoked)) => given > revoked,
+            (Some(_), None) => true,
+            _ => false,
+        }
+    }
+
+    // 15. Record or withdraw the customer's consent to share their profile with an anchor
+    pub fn set_anchor_consent(
+        customer_id: &str,
+        slug: &str,
+        granted: bool,
+    ) -> Result<Sep12AnchorSubmission, Sep12Error> {
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+        let customer = load_customer(&mut conn, customer_id)?;
+        let now = chrono::Utc::now().naive_utc();
+
+        match find_submission(&mut conn, customer.id, slug)? {
+            Some(submission) => {
+                let query = diesel::update(sep12_anchor_submissions::table.find(submission.id));
+                if granted {
+                    query
+                        .set((
+                            sep12_anchor_submissions::consent_given_at.eq(Some(now)),
+                            sep12_anchor_submissions::updated_at.eq(now),
+                        ))
+                        .get_result(&mut conn)
+                } else {
+                    query
+                        .set((
+                            sep12_anchor_submissions::consent_revoked_at.eq(Some(now)),
+                            sep12_anchor_submissions::updated_at.eq(now),
+                        ))
+                        .get_result(&mut conn)
+                }
+            }
+            None if granted => diesel::insert_into(sep12_anchor_submissions::table)
+                .values(&NewSep12AnchorSubmission {
+                    customer_id: customer.id,
+                    anchor_slug: slug.to_string(),
+                    anchor_customer_id: None,
+                    status: "NOT_SUBMITTED".to_string(),
+                    submitted_fields: "[]".to_string(),
+                    consent_given_at: Some(now),
+                    last_synced_at: None,
+                })
+                .get_result(&mut conn),
+            None => return Err(Sep12Error::ConsentRequired(slug.to_string())),
+        }
+        .map_err(|e| Sep12Error::DatabaseError(e.to_string()))
+    }
+
+    // 16. Push a stored profile to another anchor the customer has consented to
+    pub async fn onboard_to_anchor(
+        customer_id: &str,
+        slug: &str,
+    ) -> Result<Sep12AnchorSubmission, Sep12Error> {
+        let mut conn =
+            establish_connection().map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+        let customer = load_customer(&mut conn, customer_id)?;
+
+        let submission = find_submission(&mut conn, customer.id, slug)?;
+        if !submission.as_ref().is_some_and(has_consent) {
+            return Err(Sep12Error::ConsentRequired(slug.to_string()));
+        }
+
+        let known = match customer.sep9_fields.as_deref() {
+            Some(stored) => Sep9Fields::from_json(stored)?,
+            None => {
+                return Err(Sep12Error::InvalidRequest(
+                    "Customer has no stored KYC data".to_string(),
+                ));
+            }
+        };
+
+        // Only send what this anchor asks for; without a field list, send everything we hold
+        let requested = match get_account_kyc(
+            slug,
+            &customer.account,
+            customer.memo.as_deref(),
+            Some(&customer.customer_type),
+        )
+        .await
+        {
+            Ok(response) => response.fields.unwrap_or_default(),
+            Err(Sep12Error::CustomerNotFound) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+
+        let fields: Vec<(String, String)> = if requested.is_empty() {
+            known.to_map()?.into_iter().collect()
+        } else {
+            requested
+                .iter()
+                .filter_map(|f| known.get(&f.name).map(|value| (f.name.clone(), value)))
+                .collect()
+        };
+
+        let stored_files: Vec<Sep12CustomerFile> = sep12_customer_files::table
+            .filter(sep12_customer_files::customer_id.eq(customer.id))
+            .order(sep12_customer_files::created_at.desc())
+            .load(&mut conn)
+            .map_err(|e| Sep12Error::DatabaseError(e.to_string()))?;
+
+        let public_account =
+            public_account_id(&customer.account).map_err(|_| Sep12Error::AuthFailed)?;
+        let mut form = reqwest::multipart::Form::new()
+            .text("account", public_account)
+            .text("type", customer.customer_type.clone());
+        if let Some(memo) = &customer.memo {
+            form = form.text("memo", memo.clone());
+        }
+        let anchor_customer_id = anchor_customer_id_for(&customer, submission.as_ref(), slug);
+        if let Some(id) = &anchor_customer_id {
+            form = form.text("id", id.clone());
+        }
+
+        let mut submitted = Vec::new();
+        for (name, value) in fields {
+            form = form.text(name.clone(), value);
+            submitted.push(name);
+        }
+
+        // Documents go along when the anchor asks for them by name; newest upload wins
+        let store = document_store_from_env()?;
+        for field in requested.iter().filter(|f| f.field_type == "binary") {
+            if let Some(file) = stored_files.iter().find(|file| file.purpose == field.name) {
+                let bytes = read_customer_file(&store, file).await?;
+                let part = reqwest::multipart::Part::bytes(bytes)
+                    .file_name(file.file_name.clone())
+                    .mime_str(&file.content_type)?;
+                form = form.part(field.name.clone(), part);
+                submitted.push(field.name.clone());
+            }
+        }
+
+        let (kyc_server, jwt) = kyc_server_and_token(slug, &customer.account).await?;
+
+        let response = reqwest::Client::new()
+            .put(format!("{}/customer", kyc_server))
+            .bearer_auth(jwt)
+            .multipart(form)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            let customer_response = customer_after_put(
+                response,
+                slug,
+                &customer.account,
+                customer.memo.as_deref(),
+                Some(&customer.customer_type),
+            )
+            .await?;
+            let previous_status = anchor_status(&customer, submission.as_ref());
+
+            conn.transaction::<_, Sep12Error, _>(|conn| {
+                let submission = record_submission(
+                    conn,
+                    &customer,
+                    slug,
+                    customer_response.id.as_deref().or(anchor_customer_id.as_deref()),
+                    &customer_response.status,
+                    &submitted,
+                )?;
+                notify_status_change(
+                    conn,
+                    &customer,
+                    slug,
+                    &previous_status,
+                    &customer_response.status,
+                )?;
+
+                Ok(submission)
+            })
+        } else {
+            let error = response.text().await?;
+            Err(Sep12Error::InvalidRequest(error))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn customer(body: serde_json::Value) -> Customer {
+            serde_json::from_value(body).unwrap()
+        }
+
+        fn known(pairs: &[(&str, &str)]) -> Sep9Fields {
+            let pairs: Vec<(String, String)> = pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+            Sep9Fields::from_pairs(&pairs).unwrap()
+        }
+
+        fn names<'a>(fields: impl IntoIterator<Item = &'a String>) -> Vec<&'a str> {
+            fields.into_iter().map(String::as_str).collect()
+        }
+
+        #[test]
+        fn reads_fields_keyed_by_name_or_listed() {
+            let keyed = customer(serde_json::json!({
+                "status": "NEEDS_INFO",
+                "fields": {"first_name": {"description": "First name", "type": "string"}},
+            }));
+            let listed = customer(serde_json::json!({
+                "status": "NEEDS_INFO",
+                "fields": [{"name": "first_name", "description": "First name", "type": "string"}],
+            }));
+
+            for response in [keyed, listed] {
+                let fields = response.fields.unwrap();
+                assert_eq!(fields.len(), 1);
+                assert_eq!(fields[0].name, "first_name");
+            }
+            assert!(
+                serde_json::from_value::<Customer>(serde_json::json!({
+                    "status": "NEEDS_INFO",
+                    "fields": "first_name",
+                }))
+                .is_err()
+            );
+        }
+
+        #[test]
+        fn requested_fields_we_hold_are_fillable() {
+            let response = customer(serde_json::json!({
+                "id": "anchor-customer",
+                "status": "NEEDS_INFO",
+                "fields": {
+                    "first_name": {"type": "string"},
+                    "email_address": {"type": "string"},
+                },
+            }));
+            let stored = known(&[("first_name", "Jane"), ("email", "jane@example.com")]);
+
+            let (diff, fillable) = diff_requirements(&response, Some(&stored));
+            assert!(diff.missing.is_empty());
+            assert_eq!(
+                fillable,
+                vec![
+                    ("email_address".to_string(), "jane@example.com".to_string()),
+                    ("first_name".to_string(), "Jane".to_string()),
+                ]
+            );
+            assert_eq!(diff.status, "NEEDS_INFO");
+            assert_eq!(diff.customer_id.as_deref(), Some("anchor-customer"));
+        }
+
+        #[test]
+        fn newly_requested_fields_are_missing_or_optional() {
+            let response = customer(serde_json::json!({
+                "status": "NEEDS_INFO",
+                "fields": {
+                    "first_name": {"type": "string"},
+                    "tax_id": {"type": "string"},
+                    "occupation": {"type": "string", "optional": true},
+                },
+            }));
+            let stored = known(&[("first_name", "Jane")]);
+
+            let (diff, fillable) = diff_requirements(&response, Some(&stored));
+            assert_eq!(names(diff.missing.iter().map(|f| &f.name)), vec!["tax_id"]);
+            assert_eq!(
+                names(diff.optional.iter().map(|f| &f.name)),
+                vec!["occupation"]
+            );
+            assert_eq!(
+                names(fillable.iter().map(|(name, _)| name)),
+                vec!["first_name"]
+            );
+        }
+
+        #[test]
+        fn documents_always_come_from_the_user() {
+            let response = customer(serde_json::json!({
+                "status": "NEEDS_INFO",
+                "fields": {"photo_id_front": {"type": "binary"}},
+            }));
+            let stored = known(&[("photo_id_front", "stored.png")]);
+
+            let (diff, fillable) = diff_requirements(&response, Some(&stored));
+            assert_eq!(
+                names(diff.missing.iter().map(|f| &f.name)),
+                vec!["photo_id_front"]
+            );
+            assert!(fillable.is_empty());
+        }
+
+        #[test]
+        fn without_stored_data_everything_requested_is_outstanding() {
+            let response = customer(serde_json::json!({
+                "status": "NEEDS_INFO",
+                "fields": {"first_name": {"type": "string"}},
+            }));
+
+            let (diff, fillable) = diff_requirements(&response, None);
+            assert_eq!(diff.missing.len(), 1);
+            assert!(fillable.is_empty());
+        }
+
+        #[test]
+        fn sorts_provided_fields_by_status_and_drops_accepted_ones() {
+            let response = customer(serde_json::json!({
+                "status": "PROCESSING",
+                "provided_fields": {
+                    "first_name": {"type": "string", "status": "ACCEPTED"},
+                    "id_number": {"type": "string", "status": "PROCESSING"},
+                    "mobile_number": {"type": "string", "status": "VERIFICATION_REQUIRED"},
+                    "birth_date": {"type": "date", "status": "REJECTED", "error": "too young"},
+                },
+            }));
+
+            let (diff, fillable) = diff_requirements(&response, None);
+            assert_eq!(
+                names(diff.pending.iter().map(|f| &f.name)),
+                vec!["id_number", "mobile_number"]
+            );
+            assert_eq!(
+                names(diff.rejected.iter().map(|f| &f.name)),
+                vec!["birth_date"]
+            );
+            assert_eq!(diff.rejected[0].error.as_deref(), Some("too young"));
+            assert!(diff.missing.is_empty() && diff.optional.is_empty() && fillable.is_empty());
+        }
+
+        #[test]
+        fn accepted_customer_has_nothing_outstanding() {
+            let response = customer(serde_json::json!({"status": "ACCEPTED"}));
+            let stored = known(&[("first_name", "Jane")]);
+
+            let (diff, fillable) = diff_requirements(&response, Some(&stored));
+            assert_eq!(diff.status, "ACCEPTED");
+            assert!(diff.missing.is_empty() && diff.pending.is_empty() && diff.rejected.is_empty());
+            assert!(fillable.is_empty());
+        }
+    }
 }
@@ -4,21 +4,33 @@
 // Update transactions
 // Get single transactions
 // PUT Transaction Callback
+// Register sender/receiver customers
 //
 
 pub mod sep31 {
     use crate::common::get_anchor_config_details;
-    use bigdecimal::BigDecimal;
+    use crate::sep12::sep12::{Customer, Sep12Error, create_account_kyc, update_account_kyc};
+    use crate::sep38::sep38::{Sep38Error, ensure_quote_usable, mark_quote_used};
+    use crate::webhook::webhook::{enqueue_event, SEP31_STATUS_CHANGED};
     use diesel::prelude::*;
-    use helpers::{auth::authenticate, keypair::generate_keypair};
+    use helpers::{
+        auth::authenticate,
+        horizon::HorizonClient,
+        keypair::generate_keypair,
+        payment::{PaymentDetails, build_payment_envelope},
+    };
     use models::{
-        common::establish_connection, schema::offramp_service::sep31_transactions,
-        sep31::NewSep31Transaction,
+        common::establish_connection,
+        money::Amount,
+        schema::offramp_service::sep31_transactions,
+        sep31::{NewSep31Transaction, Sep31Transaction},
     };
     use reqwest::Client;
     use serde::{Deserialize, Serialize};
     use std::collections::HashMap;
+    use stellar_base::Network;
     use thiserror::Error;
+    use uuid::Uuid;
 
     #[derive(Error, Debug)]
     pub enum Sep31Error {
@@ -39,6 +51,21 @@ pub mod sep31 {
 
         #[error("Database error: {0}")]
         DatabaseError(String),
+
+        #[error("Payment failed: {0}")]
+        PaymentFailed(String),
+
+        #[error("Customer registration failed: {0}")]
+        CustomerError(#[from] Sep12Error),
+
+        #[error("Quote rejected: {0}")]
+        QuoteError(#[from] Sep38Error),
+    }
+
+    impl From<diesel::result::Error> for Sep31Error {
+        fn from(e: diesel::result::Error) -> Self {
+            Sep31Error::DatabaseError(e.to_string())
+        }
     }
 
     #[derive(Debug, Serialize, Deserialize)]
@@ -81,11 +108,11 @@ pub mod sep31 {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub fields: Option<TransactionFields>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub max_amount: Option<BigDecimal>,
+        pub max_amount: Option<Amount>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub fee_fixed: Option<BigDecimal>,
+        pub fee_fixed: Option<Amount>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub fee_percent: Option<BigDecimal>,
+        pub fee_percent: Option<Amount>,
     }
 
     #[derive(Debug, Serialize, Deserialize)]
@@ -95,7 +122,7 @@ pub mod sep31 {
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct TransactionRequest {
-        pub amount: BigDecimal,
+        pub amount: Amount,
         pub asset_code: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub asset_issuer: Option<String>,
@@ -133,15 +160,15 @@ pub mod sep31 {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub status_message: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub amount_in: Option<String>,
+        pub amount_in: Option<Amount>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub amount_in_asset: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub amount_out: Option<String>,
+        pub amount_out: Option<Amount>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub amount_out_asset: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub amount_fee: Option<String>,
+        pub amount_fee: Option<Amount>,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub amount_fee_asset: Option<String>,
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -174,7 +201,7 @@ pub mod sep31 {
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct FeeDetails {
-        pub total: String,
+        pub total: Amount,
         pub asset: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub details: Option<Vec<FeeComponent>>,
@@ -185,21 +212,21 @@ pub mod sep31 {
         pub name: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub description: Option<String>,
-        pub amount: String,
+        pub amount: Amount,
     }
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct Refunds {
-        pub amount_refunded: String,
-        pub amount_fee: String,
+        pub amount_refunded: Amount,
+        pub amount_fee: Amount,
         pub payments: Vec<RefundPayment>,
     }
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct RefundPayment {
         pub id: String,
-        pub amount: String,
-        pub fee: String,
+        pub amount: Amount,
+        pub fee: Amount,
     }
 
     #[derive(Debug, Serialize, Deserialize)]
@@ -207,6 +234,76 @@ pub mod sep31 {
         pub url: String,
     }
 
+    /// A sender or receiver to register with the receiving anchor.
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    pub struct CustomerDetails {
+        /// Local id of a customer registered earlier; updated instead of created when set.
+        pub customer_id: Option<String>,
+        /// One of the anchor's SEP-12 types for this role; may be omitted when it offers only one.
+        pub customer_type: Option<String>,
+        pub memo: Option<String>,
+        pub fields: Vec<(String, String)>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct CustomersResponse {
+        pub sender_id: Option<String>,
+        pub sender_type: Option<String>,
+        pub sender_status: Option<String>,
+        pub receiver_id: Option<String>,
+        pub receiver_type: Option<String>,
+        pub receiver_status: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct PaymentResponse {
+        pub transaction_id: String,
+        pub stellar_transaction_id: String,
+        pub status: String,
+    }
+
+    // Store the anchor's view of a transaction and publish a status change event
+    fn sync_transaction_status(
+        conn: &mut PgConnection,
+        transaction: &Transaction,
+    ) -> Result<(), Sep31Error> {
+        conn.transaction::<_, Sep31Error, _>(|conn| {
+            let previous_status: Option<String> = sep31_transactions::table
+                .filter(sep31_transactions::transaction_id.eq(&transaction.id))
+                .select(sep31_transactions::status)
+                .first(conn)
+                .optional()
+                .map_err(|e| Sep31Error::DatabaseError(e.to_string()))?;
+
+            diesel::update(sep31_transactions::table)
+                .filter(sep31_transactions::transaction_id.eq(&transaction.id))
+                .set((
+                    sep31_transactions::status.eq(transaction.status.clone()),
+                    sep31_transactions::stellar_transaction_id
+                        .eq(transaction.stellar_transaction_id.clone()),
+                ))
+                .execute(conn)
+                .map_err(|e| Sep31Error::DatabaseError(e.to_string()))?;
+
+            if let Some(previous) = previous_status.filter(|p| *p != transaction.status) {
+                enqueue_event(
+                    conn,
+                    SEP31_STATUS_CHANGED,
+                    serde_json::json!({
+                        "transaction_id": transaction.id,
+                        "previous_status": previous,
+                        "status": transaction.status,
+                        "status_message": transaction.status_message,
+                        "stellar_transaction_id": transaction.stellar_transaction_id,
+                    }),
+                )
+                .map_err(|e| Sep31Error::DatabaseError(e.to_string()))?;
+            }
+
+            Ok(())
+        })
+    }
+
     // 1. GET /info
     pub async fn get_info(slug: &str) -> Result<InfoResponse, Sep31Error> {
         let client = Client::new();
@@ -260,6 +357,12 @@ pub mod sep31 {
                 Err(_) => return Err(Sep31Error::AuthFailed),
             };
 
+        if let Some(qid) = &request.quote_id {
+            let mut conn =
+                establish_connection().map_err(|e| Sep31Error::DatabaseError(e.to_string()))?;
+            ensure_quote_usable(&mut conn, qid, account)?;
+        }
+
         let direct_payment_server = &anchor_config.general_info.direct_payment_server;
         let direct_payment_server_str = direct_payment_server
             .as_ref()
@@ -286,7 +389,7 @@ pub mod sep31 {
                 asset_code: request.asset_code,
                 asset_issuer: request.asset_issuer,
                 destination_asset: request.destination_asset,
-                quote_id: request.quote_id,
+                quote_id: request.quote_id.clone(),
                 sender_id: request.sender_id,
                 receiver_id: request.receiver_id,
                 stellar_account_id: transaction_response.stellar_account_id.clone(),
@@ -295,11 +398,16 @@ pub mod sep31 {
                 status: "pending_sender".to_string(),
             };
 
-            diesel::insert_into(sep31_transactions::table)
+            let row_id: Uuid = diesel::insert_into(sep31_transactions::table)
                 .values(&new_transaction)
-                .execute(&mut conn)
+                .returning(sep31_transactions::id)
+                .get_result(&mut conn)
                 .map_err(|e| Sep31Error::DatabaseError(e.to_string()))?;
 
+            if let Some(qid) = &request.quote_id {
+                mark_quote_used(&mut conn, qid, row_id, "sep31")?;
+            }
+
             Ok(transaction_response)
         } else {
             let error = response.text().await?;
@@ -346,7 +454,13 @@ pub mod sep31 {
             .await?;
 
         if response.status().is_success() {
-            Ok(response.json().await?)
+            let transaction: Transaction = response.json().await?;
+            let mut conn =
+                establish_connection().map_err(|e| Sep31Error::DatabaseError(e.to_string()))?;
+
+            sync_transaction_status(&mut conn, &transaction)?;
+
+            Ok(transaction)
         } else if response.status() == 404 {
             Err(Sep31Error::TransactionNotFound)
         } else {
@@ -402,15 +516,7 @@ pub mod sep31 {
             let mut conn =
                 establish_connection().map_err(|e| Sep31Error::DatabaseError(e.to_string()))?;
 
-            diesel::update(sep31_transactions::table)
-                .filter(sep31_transactions::transaction_id.eq(transaction_id))
-                .set((
-                    sep31_transactions::status.eq(transaction.status.clone()),
-                    sep31_transactions::stellar_transaction_id
-                        .eq(transaction.stellar_transaction_id.clone()),
-                ))
-                .execute(&mut conn)
-                .map_err(|e| Sep31Error::DatabaseError(e.to_string()))?;
+            sync_transaction_status(&mut conn, &transaction)?;
 
             Ok(transaction)
         } else if response.status() == 404 {
@@ -472,4 +578,224 @@ pub mod sep31 {
             Err(Sep31Error::InvalidRequest(error))
         }
     }
+
+    // 6. Send the funds to the receiving anchor's account
+    pub async fn submit_transaction_payment<H: HorizonClient>(
+        horizon: &H,
+        slug: &str,
+        account: &str,
+        transaction_id: &str,
+    ) -> Result<PaymentResponse, Sep31Error> {
+        let keypair = match generate_keypair(account) {
+            Ok(kp) => kp,
+            Err(_) => return Err(Sep31Error::KeypairGenerationFailed),
+        };
+
+        let mut conn =
+            establish_connection().map_err(|e| Sep31Error::DatabaseError(e.to_string()))?;
+
+        let transaction: Sep31Transaction = sep31_transactions::table
+            .filter(sep31_transactions::transaction_id.eq(transaction_id))
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| Sep31Error::DatabaseError(e.to_string()))?
+            .ok_or(Sep31Error::TransactionNotFound)?;
+
+        if transaction.stellar_transaction_id.is_some() {
+            return Err(Sep31Error::InvalidRequest(
+                "Payment has already been submitted".to_string(),
+            ));
+        }
+        if transaction.status != "pending_sender" {
+            return Err(Sep31Error::InvalidRequest(format!(
+                "Transaction is {}, expected pending_sender",
+                transaction.status
+            )));
+        }
+
+        let destination = transaction.stellar_account_id.as_deref().ok_or_else(|| {
+            Sep31Error::InvalidRequest("Anchor did not return a stellar_account_id".to_string())
+        })?;
+
+        let anchor_config =
+            get_anchor_config_details(&helpers::stellartoml::AnchorService::new(), slug)
+                .await
+                .map_err(|_| Sep31Error::AuthFailed)?;
+        let network = Network::new(anchor_config.general_info.network_passphrase.clone());
+
+        let sequence = horizon
+            .fetch_account_sequence(&keypair.public_key().account_id())
+            .await
+            .map_err(|e| Sep31Error::PaymentFailed(e.to_string()))?;
+
+        let amount = transaction.amount.to_string();
+        let envelope = build_payment_envelope(
+            &keypair,
+            sequence,
+            &network,
+            &PaymentDetails {
+                destination,
+                asset_code: &transaction.asset_code,
+                asset_issuer: transaction.asset_issuer.as_deref(),
+                amount: &amount,
+                memo_type: transaction.stellar_memo_type.as_deref(),
+                memo: transaction.stellar_memo.as_deref(),
+            },
+        )
+        .map_err(|e| Sep31Error::PaymentFailed(e.to_string()))?;
+
+        let submitted = horizon
+            .submit_transaction(&envelope)
+            .await
+            .map_err(|e| Sep31Error::PaymentFailed(e.to_string()))?;
+
+        let status = "pending_receiver".to_string();
+
+        conn.transaction::<_, Sep31Error, _>(|conn| {
+            diesel::update(sep31_transactions::table)
+                .filter(sep31_transactions::transaction_id.eq(transaction_id))
+                .set((
+                    sep31_transactions::status.eq(&status),
+                    sep31_transactions::stellar_transaction_id.eq(&submitted.hash),
+                ))
+                .execute(conn)
+                .map_err(|e| Sep31Error::DatabaseError(e.to_string()))?;
+
+            enqueue_event(
+                conn,
+                SEP31_STATUS_CHANGED,
+                serde_json::json!({
+                    "transaction_id": transaction_id,
+                    "previous_status": transaction.status,
+                    "status": status,
+                    "status_message": null,
+                    "stellar_transaction_id": submitted.hash,
+                }),
+            )
+            .map_err(|e| Sep31Error::DatabaseError(e.to_string()))?;
+
+            Ok(())
+        })?;
+
+        Ok(PaymentResponse {
+            transaction_id: transaction_id.to_string(),
+            stellar_transaction_id: submitted.hash,
+            status,
+        })
+    }
+
+    // Pick the SEP-12 type to register a party under, from what the anchor lists for that role
+    fn resolve_customer_type(
+        role: &str,
+        requirements: Option<&Sep12TypeRequirements>,
+        requested: Option<&str>,
+    ) -> Result<Option<String>, Sep31Error> {
+        let types: Vec<&String> = match requirements {
+            Some(r) if !r.types.is_empty() => {
+                let mut types: Vec<&String> = r.types.keys().collect();
+                types.sort();
+                types
+            }
+            // The anchor doesn't collect KYC for this role
+            _ => return Ok(None),
+        };
+
+        match requested {
+            Some(t) if types.iter().any(|known| known.as_str() == t) => Ok(Some(t.to_string())),
+            Some(t) => Err(Sep31Error::InvalidRequest(format!(
+                "{} type {} is not offered; expected one of {:?}",
+                role, t, types
+            ))),
+            None if types.len() == 1 => Ok(Some(types[0].clone())),
+            None => Err(Sep31Error::InvalidRequest(format!(
+                "{} type is required; expected one of {:?}",
+                role, types
+            ))),
+        }
+    }
+
+    async fn register_customer(
+        slug: &str,
+        account: &str,
+        customer_type: &str,
+        details: CustomerDetails,
+    ) -> Result<Customer, Sep31Error> {
+        let customer = match &details.customer_id {
+            Some(id) => update_account_kyc(slug, id, details.fields, Vec::new(), account).await?,
+            None => {
+                create_account_kyc(
+                    slug,
+                    account,
+                    details.memo.as_deref(),
+                    customer_type,
+                    details.fields,
+                    Vec::new(),
+                )
+                .await?
+            }
+        };
+
+        if customer.id.is_none() {
+            return Err(Sep31Error::InvalidRequest(format!(
+                "Anchor returned no id for {} customer",
+                customer_type
+            )));
+        }
+
+        Ok(customer)
+    }
+
+    // 7. Register the sender and receiver with the types /info asks for
+    pub async fn register_customers(
+        slug: &str,
+        account: &str,
+        asset_code: &str,
+        sender: CustomerDetails,
+        receiver: CustomerDetails,
+    ) -> Result<CustomersResponse, Sep31Error> {
+        let info = get_info(slug).await?;
+        let asset = info.receive.get(asset_code).ok_or_else(|| {
+            Sep31Error::InvalidRequest(format!("Anchor does not receive {}", asset_code))
+        })?;
+        if !asset.enabled {
+            return Err(Sep31Error::InvalidRequest(format!(
+                "{} is not enabled for receiving",
+                asset_code
+            )));
+        }
+
+        let sender_type = resolve_customer_type(
+            "sender",
+            asset.sep12.as_ref().map(|r| &r.sender),
+            sender.customer_type.as_deref(),
+        )?;
+        let receiver_type = resolve_customer_type(
+            "receiver",
+            asset.sep12.as_ref().map(|r| &r.receiver),
+            receiver.customer_type.as_deref(),
+        )?;
+
+        let mut response = CustomersResponse {
+            sender_id: None,
+            sender_type: sender_type.clone(),
+            sender_status: None,
+            receiver_id: None,
+            receiver_type: receiver_type.clone(),
+            receiver_status: None,
+        };
+
+        if let Some(customer_type) = &sender_type {
+            let customer = register_customer(slug, account, customer_type, sender).await?;
+            response.sender_id = customer.id;
+            response.sender_status = Some(customer.status);
+        }
+
+        if let Some(customer_type) = &receiver_type {
+            let customer = register_customer(slug, account, customer_type, receiver).await?;
+            response.receiver_id = customer.id;
+            response.receiver_status = Some(customer.status);
+        }
+
+        Ok(response)
+    }
 }
@@ -4,19 +4,33 @@
 // Exchange Prices
 // Quote exchange Price
 pub mod sep38 {
-    use bigdecimal::BigDecimal;
     use diesel::prelude::*;
     use reqwest::Client; // This is the correct import for Client
     use serde::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, BTreeSet};
     use thiserror::Error;
-    // Import the FromStr trait to use from_str
     use crate::common::get_anchor_config_details;
     use helpers::{auth::authenticate, keypair::generate_keypair};
-    use std::str::FromStr;
 
+    use crate::rate_alert::rate_alert::evaluate_alerts;
+    use crate::sep31::sep31::get_info as get_sep31_info;
+    use crate::sep6::sep6::get_anchor_info;
+    use crate::webhook::webhook::{enqueue_event, SEP38_ASSETS_CHANGED, SEP38_QUOTE_EXPIRED};
+    use chrono::{DateTime, Duration, NaiveDateTime, Utc};
     use models::{
-        common::establish_connection, schema::offramp_service::sep38_quotes, sep38::NewSep38Quote,
+        common::establish_connection,
+        money::{Amount, Money},
+        schema::offramp_service::{sep38_assets, sep38_price_samples, sep38_quotes, sep6_transactions},
+        sep38::{
+            NewSep38Asset, NewSep38PriceSample, NewSep38Quote, Sep38Asset, Sep38PriceSample,
+            Sep38Quote,
+        },
     };
+    use uuid::Uuid;
+
+    pub const QUOTE_ACTIVE: &str = "active";
+    pub const QUOTE_USED: &str = "used";
+    pub const QUOTE_EXPIRED: &str = "expired";
 
     #[derive(Error, Debug)]
     pub enum Sep38Error {
@@ -38,13 +52,25 @@ pub mod sep38 {
         #[error("Asset not supported")]
         AssetNotSupported,
 
+        #[error("Quote {0} has expired")]
+        QuoteExpired(String),
+
+        #[error("Quote {0} has already been used")]
+        QuoteAlreadyUsed(String),
+
         #[error("Database error: {0}")]
         DatabaseError(String),
     }
 
+    impl From<diesel::result::Error> for Sep38Error {
+        fn from(e: diesel::result::Error) -> Self {
+            Sep38Error::DatabaseError(e.to_string())
+        }
+    }
+
     #[derive(Debug, Serialize, Deserialize)]
     pub struct FeeDetail {
-        pub total: String,
+        pub total: Amount,
         #[serde(skip)]
         pub asset: String,
         #[serde(rename = "asset")]
@@ -64,10 +90,10 @@ pub mod sep38 {
         pub name: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub description: Option<String>,
-        pub amount: String,
+        pub amount: Amount,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct AssetInfo {
         pub asset: String,
         pub sell_delivery_methods: Option<Vec<DeliveryMethod>>,
@@ -75,7 +101,7 @@ pub mod sep38 {
         pub country_codes: Option<Vec<String>>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Clone, Serialize, Deserialize)]
     pub struct DeliveryMethod {
         pub name: String,
         pub description: String,
@@ -89,9 +115,9 @@ pub mod sep38 {
         #[serde(rename = "buy_asset")]
         pub buy_asset: String,
         #[serde(rename = "sell_amount", skip_serializing_if = "Option::is_none")]
-        pub sell_amount: Option<String>,
+        pub sell_amount: Option<Amount>,
         #[serde(rename = "buy_amount", skip_serializing_if = "Option::is_none")]
-        pub buy_amount: Option<String>,
+        pub buy_amount: Option<Amount>,
         #[serde(
             rename = "sell_delivery_method",
             skip_serializing_if = "Option::is_none"
@@ -111,19 +137,19 @@ pub mod sep38 {
     #[derive(Debug, Serialize, Deserialize)]
     pub struct PriceResponse {
         #[serde(rename = "total_price")]
-        pub total_price: String,
-        pub price: String,
+        pub total_price: Amount,
+        pub price: Amount,
         #[serde(rename = "sell_amount")]
-        pub sell_amount: String,
+        pub sell_amount: Amount,
         #[serde(rename = "buy_amount")]
-        pub buy_amount: String,
+        pub buy_amount: Amount,
         pub fee: FeeDetail,
     }
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct PriceAsset {
         pub asset: String,
-        pub price: String,
+        pub price: Amount,
         pub decimals: i32,
     }
 
@@ -131,20 +157,20 @@ pub mod sep38 {
     pub struct QuoteResponse {
         pub id: String,
         pub expires_at: String,
-        pub total_price: String,
-        pub price: String,
+        pub total_price: Amount,
+        pub price: Amount,
         pub sell_asset: String,
-        pub sell_amount: String,
+        pub sell_amount: Amount,
         pub sell_delivery_method: Option<String>,
         pub buy_asset: String,
-        pub buy_amount: String,
+        pub buy_amount: Amount,
         pub buy_delivery_method: Option<String>,
         pub fee: Fee,
     }
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct Fee {
-        pub total: String,
+        pub total: Amount,
         pub asset: String,
         pub details: Option<Vec<FeeDetail>>,
     }
@@ -156,9 +182,9 @@ pub mod sep38 {
         #[serde(rename = "buy_asset")]
         pub buy_asset: String,
         #[serde(rename = "sell_amount", skip_serializing_if = "Option::is_none")]
-        pub sell_amount: Option<String>,
+        pub sell_amount: Option<Amount>,
         #[serde(rename = "buy_amount", skip_serializing_if = "Option::is_none")]
-        pub buy_amount: Option<String>,
+        pub buy_amount: Option<Amount>,
         #[serde(rename = "expire_after", skip_serializing_if = "Option::is_none")]
         pub expire_after: Option<String>,
         #[serde(
@@ -176,12 +202,31 @@ pub mod sep38 {
         pub context: String,
     }
 
-    // 1. GET /exchange_info
+    // 1. GET /info, persisted per anchor and served from the last snapshot if the anchor is down
     pub async fn get_exchange_info(slug: String) -> Result<Vec<AssetInfo>, Sep38Error> {
+        match fetch_exchange_info(&slug).await {
+            Ok(assets) => {
+                if let Err(e) = save_asset_catalogue(&slug, &assets) {
+                    eprintln!("Error saving SEP-38 assets for {}: {:?}", slug, e);
+                }
+                Ok(assets)
+            }
+            Err(fetch_error) => {
+                let cached = cached_exchange_info(&slug)?;
+                if cached.is_empty() {
+                    Err(fetch_error)
+                } else {
+                    Ok(cached)
+                }
+            }
+        }
+    }
+
+    async fn fetch_exchange_info(slug: &str) -> Result<Vec<AssetInfo>, Sep38Error> {
         let client = Client::new();
 
         let anchor_config =
-            get_anchor_config_details(&helpers::stellartoml::AnchorService::new(), &slug)
+            get_anchor_config_details(&helpers::stellartoml::AnchorService::new(), slug)
                 .await
                 .map_err(|_| Sep38Error::AuthFailed)?;
 
@@ -254,13 +299,244 @@ pub mod sep38 {
         }
     }
 
-    // 2. GET /exchange_prices
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    pub struct AssetChanges {
+        pub asset: String,
+        pub added_sell_methods: Vec<String>,
+        pub removed_sell_methods: Vec<String>,
+        pub added_buy_methods: Vec<String>,
+        pub removed_buy_methods: Vec<String>,
+        pub added_country_codes: Vec<String>,
+        pub removed_country_codes: Vec<String>,
+    }
+
+    impl AssetChanges {
+        pub fn is_empty(&self) -> bool {
+            self.added_sell_methods.is_empty()
+                && self.removed_sell_methods.is_empty()
+                && self.added_buy_methods.is_empty()
+                && self.removed_buy_methods.is_empty()
+                && self.added_country_codes.is_empty()
+                && self.removed_country_codes.is_empty()
+        }
+    }
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    pub struct AssetCatalogueDiff {
+        pub added_assets: Vec<String>,
+        pub removed_assets: Vec<String>,
+        pub changed_assets: Vec<AssetChanges>,
+    }
+
+    impl AssetCatalogueDiff {
+        pub fn is_empty(&self) -> bool {
+            self.added_assets.is_empty()
+                && self.removed_assets.is_empty()
+                && self.changed_assets.is_empty()
+        }
+    }
+
+    fn method_names(methods: &Option<Vec<DeliveryMethod>>) -> BTreeSet<String> {
+        methods
+            .iter()
+            .flatten()
+            .map(|method| method.name.clone())
+            .collect()
+    }
+
+    // (added, removed) going from previous to current
+    fn set_changes(
+        previous: &BTreeSet<String>,
+        current: &BTreeSet<String>,
+    ) -> (Vec<String>, Vec<String>) {
+        (
+            current.difference(previous).cloned().collect(),
+            previous.difference(current).cloned().collect(),
+        )
+    }
+
+    fn asset_from_row(row: &Sep38Asset) -> AssetInfo {
+        let decode_methods = |value: &Option<serde_json::Value>| {
+            value
+                .clone()
+                .and_then(|v| serde_json::from_value::<Vec<DeliveryMethod>>(v).ok())
+        };
+
+        AssetInfo {
+            asset: row.asset.clone(),
+            sell_delivery_methods: decode_methods(&row.sell_delivery_methods),
+            buy_delivery_methods: decode_methods(&row.buy_delivery_methods),
+            country_codes: row
+                .country_codes
+                .clone()
+                .and_then(|v| serde_json::from_value::<Vec<String>>(v).ok()),
+        }
+    }
+
+    /// Compares an anchor's previous /info snapshot with a freshly fetched one.
+    pub fn diff_asset_catalogue(previous: &[AssetInfo], current: &[AssetInfo]) -> AssetCatalogueDiff {
+        let mut diff = AssetCatalogueDiff::default();
+
+        for asset in current {
+            let Some(old) = previous.iter().find(|p| p.asset == asset.asset) else {
+                diff.added_assets.push(asset.asset.clone());
+                continue;
+            };
+
+            let (added_sell_methods, removed_sell_methods) = set_changes(
+                &method_names(&old.sell_delivery_methods),
+                &method_names(&asset.sell_delivery_methods),
+            );
+            let (added_buy_methods, removed_buy_methods) = set_changes(
+                &method_names(&old.buy_delivery_methods),
+                &method_names(&asset.buy_delivery_methods),
+            );
+            let (added_country_codes, removed_country_codes) = set_changes(
+                &old.country_codes.iter().flatten().cloned().collect(),
+                &asset.country_codes.iter().flatten().cloned().collect(),
+            );
+
+            let changes = AssetChanges {
+                asset: asset.asset.clone(),
+                added_sell_methods,
+                removed_sell_methods,
+                added_buy_methods,
+                removed_buy_methods,
+                added_country_codes,
+                removed_country_codes,
+            };
+            if !changes.is_empty() {
+                diff.changed_assets.push(changes);
+            }
+        }
+
+        diff.removed_assets = previous
+            .iter()
+            .filter(|p| !current.iter().any(|c| c.asset == p.asset))
+            .map(|p| p.asset.clone())
+            .collect();
+
+        diff
+    }
+
+    /// Assets last persisted for an anchor.
+    pub fn cached_exchange_info(slug: &str) -> Result<Vec<AssetInfo>, Sep38Error> {
+        let mut conn =
+            establish_connection().map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+        let rows: Vec<Sep38Asset> = sep38_assets::table
+            .filter(sep38_assets::anchor_slug.eq(slug))
+            .order(sep38_assets::asset.asc())
+            .load(&mut conn)
+            .map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+        Ok(rows.iter().map(asset_from_row).collect())
+    }
+
+    /// Replaces an anchor's persisted catalogue with `assets` and emits an event if it changed.
+    pub fn save_asset_catalogue(
+        slug: &str,
+        assets: &[AssetInfo],
+    ) -> Result<AssetCatalogueDiff, Sep38Error> {
+        let mut conn =
+            establish_connection().map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, Sep38Error, _>(|conn| {
+            let previous: Vec<Sep38Asset> = sep38_assets::table
+                .filter(sep38_assets::anchor_slug.eq(slug))
+                .load(conn)?;
+            let previous: Vec<AssetInfo> = previous.iter().map(asset_from_row).collect();
+            let diff = diff_asset_catalogue(&previous, assets);
+
+            let now = Utc::now().naive_utc();
+            for asset in assets {
+                let sell_methods = asset
+                    .sell_delivery_methods
+                    .as_ref()
+                    .and_then(|m| serde_json::to_value(m).ok());
+                let buy_methods = asset
+                    .buy_delivery_methods
+                    .as_ref()
+                    .and_then(|m| serde_json::to_value(m).ok());
+                let country_codes = asset
+                    .country_codes
+                    .as_ref()
+                    .and_then(|c| serde_json::to_value(c).ok());
+
+                diesel::insert_into(sep38_assets::table)
+                    .values(&NewSep38Asset {
+                        asset: &asset.asset,
+                        sell_delivery_methods: sell_methods.as_ref(),
+                        buy_delivery_methods: buy_methods.as_ref(),
+                        country_codes: country_codes.as_ref(),
+                        anchor_slug: slug,
+                    })
+                    .on_conflict((sep38_assets::anchor_slug, sep38_assets::asset))
+                    .do_update()
+                    .set((
+                        sep38_assets::sell_delivery_methods.eq(&sell_methods),
+                        sep38_assets::buy_delivery_methods.eq(&buy_methods),
+                        sep38_assets::country_codes.eq(&country_codes),
+                        sep38_assets::updated_at.eq(now),
+                    ))
+                    .execute(conn)?;
+            }
+
+            diesel::delete(
+                sep38_assets::table
+                    .filter(sep38_assets::anchor_slug.eq(slug))
+                    .filter(sep38_assets::asset.eq_any(&diff.removed_assets)),
+            )
+            .execute(conn)?;
+
+            if !diff.is_empty() {
+                enqueue_event(
+                    conn,
+                    SEP38_ASSETS_CHANGED,
+                    serde_json::json!({
+                        "slug": slug,
+                        "added_assets": diff.added_assets,
+                        "removed_assets": diff.removed_assets,
+                        "changed_assets": diff.changed_assets,
+                    }),
+                )
+                .map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+            }
+
+            Ok(diff)
+        })
+    }
+
+    /// Fetches /info from an anchor and persists it, without falling back to the cache.
+    pub async fn refresh_asset_catalogue(slug: &str) -> Result<AssetCatalogueDiff, Sep38Error> {
+        let assets = fetch_exchange_info(slug).await?;
+        save_asset_catalogue(slug, &assets)
+    }
+
+    /// Refreshes every supported anchor's catalogue forever; spawned once at launch.
+    pub async fn run_asset_refresh_worker() {
+        let interval = std::env::var("SEP38_ASSET_REFRESH_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(3600);
+
+        loop {
+            for slug in helpers::stellartoml::AnchorService::new().supported_slugs() {
+                if let Err(e) = refresh_asset_catalogue(&slug).await {
+                    eprintln!("Error refreshing SEP-38 assets for {}: {:?}", slug, e);
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+        }
+    }
+
+    // 2. GET /price
     pub async fn get_exchange_prices(
         slug: String,
         sell_asset: String,
         buy_asset: String,
-        sell_amount: String,
-        buy_amount: String,
+        sell_amount: Option<Amount>,
+        buy_amount: Option<Amount>,
         sell_delivery_method: Option<String>,
         buy_delivery_method: Option<String>,
         country_code: Option<String>,
@@ -279,7 +555,7 @@ pub mod sep38 {
             |s| s.to_string(), // Use the string value if Some
         );
 
-        let mut request = client.get(&format!("{}/prices", quote_server_str));
+        let mut request = client.get(&format!("{}/price", quote_server_str));
 
         if !sell_asset.is_empty() {
             request = request.query(&[("sell_asset", &sell_asset)]);
@@ -289,12 +565,12 @@ pub mod sep38 {
             request = request.query(&[("buy_asset", &buy_asset)]);
         }
 
-        if !sell_amount.is_empty() {
-            request = request.query(&[("sell_amount", &sell_amount)]);
+        if let Some(amount) = &sell_amount {
+            request = request.query(&[("sell_amount", amount.to_string())]);
         }
 
-        if !buy_amount.is_empty() {
-            request = request.query(&[("buy_amount", &buy_amount)]);
+        if let Some(amount) = &buy_amount {
+            request = request.query(&[("buy_amount", amount.to_string())]);
         }
 
         if let Some(method) = &sell_delivery_method {
@@ -334,8 +610,8 @@ pub mod sep38 {
         account: String,
         sell_asset: String,
         buy_asset: String,
-        sell_amount: Option<String>,
-        buy_amount: Option<String>,
+        sell_amount: Option<Amount>,
+        buy_amount: Option<Amount>,
         expire_after: Option<String>,
         sell_delivery_method: Option<String>,
         buy_delivery_method: Option<String>,
@@ -395,16 +671,11 @@ pub mod sep38 {
                 original_quote_id: quote.id.clone(),
                 sell_asset: quote.sell_asset.clone(),
                 buy_asset: quote.buy_asset.clone(),
-                sell_amount: BigDecimal::from_str(&quote.sell_amount)
-                    .map_err(|_| Sep38Error::InvalidRequest("Invalid sell amount".to_string()))?,
-                buy_amount: BigDecimal::from_str(&quote.buy_amount)
-                    .map_err(|_| Sep38Error::InvalidRequest("Invalid buy amount".to_string()))?,
-                price: BigDecimal::from_str(&quote.price)
-                    .map_err(|_| Sep38Error::InvalidRequest("Invalid price".to_string()))?,
-                total_price: BigDecimal::from_str(&quote.total_price)
-                    .map_err(|_| Sep38Error::InvalidRequest("Invalid total price".to_string()))?,
-                fee_total: BigDecimal::from_str(&quote.fee.total)
-                    .map_err(|_| Sep38Error::InvalidRequest("Invalid fee total".to_string()))?,
+                sell_amount: quote.sell_amount.clone(),
+                buy_amount: quote.buy_amount.clone(),
+                price: quote.price.clone(),
+                total_price: quote.total_price.clone(),
+                fee_total: quote.fee.total.clone(),
                 fee_asset: quote.fee.asset.clone(),
                 fee_details: quote
                     .fee
@@ -418,6 +689,8 @@ pub mod sep38 {
                     .naive_utc(),
                 context: context.to_string(),
                 transaction_id: None,
+                account: Some(keypair.public_key().account_id()),
+                anchor_slug: Some(slug.clone()),
             };
 
             diesel::insert_into(sep38_quotes::table)
@@ -482,4 +755,1138 @@ pub mod sep38 {
             )))
         }
     }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct RankedPrice {
+        pub rank: usize,
+        pub slug: String,
+        pub sell_amount: Amount,
+        pub buy_amount: Amount,
+        pub price: Amount,
+        pub total_price: Amount,
+        pub fee_total: Amount,
+        pub fee_asset: String,
+        /// The fee restated in the sell asset, so anchors charging in different assets compare.
+        pub fee_in_sell_asset: Option<Amount>,
+        /// Average completion time of past SEP-6 transactions that used this anchor's quotes
+        /// for the same pair; `None` until we have history.
+        pub eta_seconds: Option<i64>,
+        pub min_amount: Option<Amount>,
+        pub max_amount: Option<Amount>,
+        pub within_limits: bool,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct UnavailableAnchor {
+        pub slug: String,
+        pub reason: String,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct BestPriceResponse {
+        pub prices: Vec<RankedPrice>,
+        pub unavailable: Vec<UnavailableAnchor>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct BestQuoteResponse {
+        pub slug: String,
+        pub quote: QuoteResponse,
+        pub prices: Vec<RankedPrice>,
+    }
+
+    fn offers_asset(assets: &[AssetInfo], asset: &str, method: Option<&str>, sell: bool) -> bool {
+        assets.iter().any(|info| {
+            info.asset == asset
+                && method.filter(|m| !m.is_empty()).is_none_or(|m| {
+                    let methods = if sell {
+                        &info.sell_delivery_methods
+                    } else {
+                        &info.buy_delivery_methods
+                    };
+                    methods
+                        .as_ref()
+                        .is_some_and(|methods| methods.iter().any(|d| d.name == m))
+                })
+        })
+    }
+
+    // Transfer limits for the sell asset, from SEP-31 /info for sep31 quotes and SEP-6 /info otherwise
+    async fn anchor_limits(
+        slug: &str,
+        sell_asset: &str,
+        context: &str,
+    ) -> (Option<Amount>, Option<Amount>) {
+        let Some(code) = sell_asset
+            .strip_prefix("stellar:")
+            .and_then(|rest| rest.split(':').next())
+        else {
+            return (None, None);
+        };
+
+        if context == "sep31" {
+            match get_sep31_info(slug).await {
+                Ok(info) => info
+                    .receive
+                    .get(code)
+                    .map(|asset| (None, asset.max_amount.clone()))
+                    .unwrap_or_default(),
+                Err(_) => (None, None),
+            }
+        } else {
+            match get_anchor_info(slug).await {
+                Ok(info) => info
+                    .withdraw
+                    .get(code)
+                    .map(|asset| (asset.min_amount.clone(), asset.max_amount.clone()))
+                    .unwrap_or_default(),
+                Err(_) => (None, None),
+            }
+        }
+    }
+
+    fn historical_eta(
+        conn: &mut PgConnection,
+        slug: &str,
+        sell_asset: &str,
+        buy_asset: &str,
+    ) -> Result<Option<i64>, Sep38Error> {
+        let transaction_ids: Vec<Option<Uuid>> = sep38_quotes::table
+            .filter(sep38_quotes::anchor_slug.eq(slug))
+            .filter(sep38_quotes::sell_asset.eq(sell_asset))
+            .filter(sep38_quotes::buy_asset.eq(buy_asset))
+            .filter(sep38_quotes::used_by.eq("sep6"))
+            .order(sep38_quotes::used_at.desc())
+            .limit(50)
+            .select(sep38_quotes::transaction_id)
+            .load(conn)
+            .map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+        let transaction_ids: Vec<Uuid> = transaction_ids.into_iter().flatten().collect();
+        if transaction_ids.is_empty() {
+            return Ok(None);
+        }
+
+        let timings: Vec<(Option<NaiveDateTime>, Option<NaiveDateTime>)> =
+            sep6_transactions::table
+                .filter(sep6_transactions::id.eq_any(&transaction_ids))
+                .filter(sep6_transactions::completed_at.is_not_null())
+                .select((sep6_transactions::started_at, sep6_transactions::completed_at))
+                .load(conn)
+                .map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+        Ok(average_duration(timings))
+    }
+
+    // Mean of the complete, non-negative (started, completed) spans, in seconds
+    fn average_duration(
+        timings: Vec<(Option<NaiveDateTime>, Option<NaiveDateTime>)>,
+    ) -> Option<i64> {
+        let durations: Vec<i64> = timings
+            .into_iter()
+            .filter_map(|(started, completed)| Some((completed? - started?).num_seconds()))
+            .filter(|secs| *secs >= 0)
+            .collect();
+
+        if durations.is_empty() {
+            return None;
+        }
+        Some(durations.iter().sum::<i64>() / durations.len() as i64)
+    }
+
+    // Returns the sort key alongside the price: higher is better
+    async fn price_from_anchor(
+        slug: &str,
+        request: &PriceRequest,
+    ) -> Result<Option<(bigdecimal::BigDecimal, RankedPrice)>, Sep38Error> {
+        let assets = get_exchange_info(slug.to_string()).await?;
+        if !offers_asset(
+            &assets,
+            &request.sell_asset,
+            request.sell_delivery_method.as_deref(),
+            true,
+        ) || !offers_asset(
+            &assets,
+            &request.buy_asset,
+            request.buy_delivery_method.as_deref(),
+            false,
+        ) {
+            return Ok(None);
+        }
+
+        let price = get_exchange_prices(
+            slug.to_string(),
+            request.sell_asset.clone(),
+            request.buy_asset.clone(),
+            request.sell_amount.clone(),
+            request.buy_amount.clone(),
+            request.sell_delivery_method.clone(),
+            request.buy_delivery_method.clone(),
+            request.country_code.clone(),
+            request.context.clone(),
+        )
+        .await?;
+
+        // price is sell units per buy unit
+        let fee_in_sell_asset = if price.fee.asset_string == request.sell_asset {
+            Some(price.fee.total.clone())
+        } else if price.fee.asset_string == request.buy_asset {
+            Some(Money::new(&price.fee.total * &price.price, &request.sell_asset).amount)
+        } else {
+            None
+        };
+
+        let (min_amount, max_amount) =
+            anchor_limits(slug, &request.sell_asset, &request.context).await;
+        let within_limits = min_amount.as_ref().is_none_or(|min| &price.sell_amount >= min)
+            && max_amount.as_ref().is_none_or(|max| &price.sell_amount <= max);
+
+        // Selling a fixed amount: most bought wins. Buying a fixed amount: least sold wins.
+        let key = if request.sell_amount.is_some() {
+            price.buy_amount.0.clone()
+        } else {
+            -price.sell_amount.0.clone()
+        };
+
+        Ok(Some((
+            key,
+            RankedPrice {
+                rank: 0,
+                slug: slug.to_string(),
+                sell_amount: price.sell_amount,
+                buy_amount: price.buy_amount,
+                price: price.price,
+                total_price: price.total_price,
+                fee_total: price.fee.total,
+                fee_asset: price.fee.asset_string,
+                fee_in_sell_asset,
+                eta_seconds: None,
+                min_amount,
+                max_amount,
+                within_limits,
+            },
+        )))
+    }
+
+    // 6. GET /price on every supported anchor, best first
+    pub async fn get_best_prices(request: PriceRequest) -> Result<BestPriceResponse, Sep38Error> {
+        if request.sell_amount.is_some() == request.buy_amount.is_some() {
+            return Err(Sep38Error::InvalidRequest(
+                "Exactly one of sell_amount or buy_amount is required".to_string(),
+            ));
+        }
+
+        let mut ranked = Vec::new();
+        let mut unavailable = Vec::new();
+        for slug in helpers::stellartoml::AnchorService::new().supported_slugs() {
+            match price_from_anchor(&slug, &request).await {
+                Ok(Some(entry)) => ranked.push(entry),
+                Ok(None) => unavailable.push(UnavailableAnchor {
+                    slug,
+                    reason: "Asset pair or delivery method not supported".to_string(),
+                }),
+                Err(e) => unavailable.push(UnavailableAnchor {
+                    slug,
+                    reason: e.to_string(),
+                }),
+            }
+        }
+
+        let mut conn =
+            establish_connection().map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+        for (_, price) in ranked.iter_mut() {
+            price.eta_seconds =
+                historical_eta(&mut conn, &price.slug, &request.sell_asset, &request.buy_asset)?;
+        }
+
+        Ok(BestPriceResponse {
+            prices: rank_prices(ranked),
+            unavailable,
+        })
+    }
+
+    // Within limits first, then by sort key (higher is better), then fastest historical ETA
+    fn rank_prices(mut ranked: Vec<(bigdecimal::BigDecimal, RankedPrice)>) -> Vec<RankedPrice> {
+        ranked.sort_by(|(a_key, a), (b_key, b)| {
+            b.within_limits
+                .cmp(&a.within_limits)
+                .then_with(|| b_key.cmp(a_key))
+                .then_with(|| {
+                    a.eta_seconds
+                        .unwrap_or(i64::MAX)
+                        .cmp(&b.eta_seconds.unwrap_or(i64::MAX))
+                })
+        });
+
+        ranked
+            .into_iter()
+            .enumerate()
+            .map(|(i, (_, price))| RankedPrice {
+                rank: i + 1,
+                ..price
+            })
+            .collect()
+    }
+
+    // 7. POST /quote against the best-priced anchor
+    pub async fn quote_best_price(
+        account: String,
+        request: QuoteRequest,
+    ) -> Result<BestQuoteResponse, Sep38Error> {
+        let best = get_best_prices(PriceRequest {
+            sell_asset: request.sell_asset.clone(),
+            buy_asset: request.buy_asset.clone(),
+            sell_amount: request.sell_amount.clone(),
+            buy_amount: request.buy_amount.clone(),
+            sell_delivery_method: request.sell_delivery_method.clone(),
+            buy_delivery_method: request.buy_delivery_method.clone(),
+            country_code: request.country_code.clone(),
+            context: request.context.clone(),
+        })
+        .await?;
+
+        let slug = best
+            .prices
+            .iter()
+            .find(|price| price.within_limits)
+            .map(|price| price.slug.clone())
+            .ok_or_else(|| {
+                Sep38Error::InvalidRequest("No anchor can price this request".to_string())
+            })?;
+
+        let quote = quote_exchange_price(
+            slug.clone(),
+            account,
+            request.sell_asset,
+            request.buy_asset,
+            request.sell_amount,
+            request.buy_amount,
+            request.expire_after,
+            request.sell_delivery_method,
+            request.buy_delivery_method,
+            request.country_code,
+            request.context,
+        )
+        .await?;
+
+        Ok(BestQuoteResponse {
+            slug,
+            quote,
+            prices: best.prices,
+        })
+    }
+
+    fn find_local_quote(
+        conn: &mut PgConnection,
+        quote_id: &str,
+    ) -> Result<Option<Sep38Quote>, Sep38Error> {
+        sep38_quotes::table
+            .filter(sep38_quotes::original_quote_id.eq(quote_id))
+            .order(sep38_quotes::created_at.desc())
+            .first::<Sep38Quote>(conn)
+            .optional()
+            .map_err(|e| Sep38Error::DatabaseError(e.to_string()))
+    }
+
+    /// Rejects a quote we stored that has expired, was used, or belongs to another account.
+    /// Quotes we never stored are left for the anchor to validate.
+    pub fn ensure_quote_usable(
+        conn: &mut PgConnection,
+        quote_id: &str,
+        account: &str,
+    ) -> Result<(), Sep38Error> {
+        let Some(quote) = find_local_quote(conn, quote_id)? else {
+            return Ok(());
+        };
+
+        if quote.account.as_deref().is_some_and(|owner| owner != account) {
+            return Err(Sep38Error::InvalidRequest(format!(
+                "Quote {} was issued to a different account",
+                quote_id
+            )));
+        }
+
+        if quote.status == QUOTE_USED || quote.transaction_id.is_some() {
+            return Err(Sep38Error::QuoteAlreadyUsed(quote_id.to_string()));
+        }
+
+        if quote.status == QUOTE_EXPIRED || quote.expires_at <= Utc::now().naive_utc() {
+            return Err(Sep38Error::QuoteExpired(quote_id.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Links a stored quote to the transaction row that consumed it. `used_by` names the
+    /// table `transaction_id` points into (`sep6` or `sep31`). Returns false when the quote is
+    /// unknown locally or already linked.
+    pub fn mark_quote_used(
+        conn: &mut PgConnection,
+        quote_id: &str,
+        transaction_id: Uuid,
+        used_by: &str,
+    ) -> Result<bool, Sep38Error> {
+        let updated = diesel::update(
+            sep38_quotes::table
+                .filter(sep38_quotes::original_quote_id.eq(quote_id))
+                .filter(sep38_quotes::transaction_id.is_null()),
+        )
+        .set((
+            sep38_quotes::status.eq(QUOTE_USED),
+            sep38_quotes::used_at.eq(Some(Utc::now().naive_utc())),
+            sep38_quotes::used_by.eq(Some(used_by)),
+            sep38_quotes::transaction_id.eq(Some(transaction_id)),
+        ))
+        .execute(conn)
+        .map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+        Ok(updated > 0)
+    }
+
+    /// Marks every active quote past its expiry as expired and emits a webhook event per quote.
+    pub fn expire_quotes() -> Result<usize, Sep38Error> {
+        let mut conn =
+            establish_connection().map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+        conn.transaction::<_, Sep38Error, _>(|conn| {
+            let expired: Vec<Sep38Quote> = diesel::update(
+                sep38_quotes::table
+                    .filter(sep38_quotes::status.eq(QUOTE_ACTIVE))
+                    .filter(sep38_quotes::expires_at.le(Utc::now().naive_utc())),
+            )
+            .set(sep38_quotes::status.eq(QUOTE_EXPIRED))
+            .get_results(conn)
+            .map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+            // Subscribers get the quote's G-address; the column can't hold anything else
+            for quote in &expired {
+                enqueue_event(
+                    conn,
+                    SEP38_QUOTE_EXPIRED,
+                    serde_json::json!({
+                        "quote_id": quote.original_quote_id,
+                        "sell_asset": quote.sell_asset,
+                        "buy_asset": quote.buy_asset,
+                        "sell_amount": quote.sell_amount.to_string(),
+                        "buy_amount": quote.buy_amount.to_string(),
+                        "context": quote.context,
+                        "account": quote.account,
+                        "expires_at": quote.expires_at,
+                    }),
+                )
+                .map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+            }
+
+            Ok(expired.len())
+        })
+    }
+
+    /// Sweeps expired quotes forever; spawned once at launch.
+    pub async fn run_quote_expiry_worker() {
+        let interval = std::env::var("QUOTE_EXPIRY_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60);
+
+        loop {
+            if let Err(e) = expire_quotes() {
+                eprintln!("Error expiring quotes: {:?}", e);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+        }
+    }
+
+    /// Caps how many candles a single history request may produce.
+    const MAX_HISTORY_BUCKETS: i64 = 1000;
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct SampledPair {
+        pub slug: String,
+        pub sell_asset: String,
+        pub buy_asset: String,
+        pub sell_amount: Amount,
+        pub sell_delivery_method: Option<String>,
+        pub buy_delivery_method: Option<String>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct PriceCandle {
+        pub bucket_start: NaiveDateTime,
+        pub open: Amount,
+        pub high: Amount,
+        pub low: Amount,
+        pub close: Amount,
+        pub total_price_close: Amount,
+        pub fee_average: Amount,
+        pub samples: usize,
+    }
+
+    /// Candles for one sampled configuration; prices from different anchors, amounts or
+    /// delivery methods aren't comparable, so each gets its own series.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct PriceSeries {
+        pub slug: String,
+        pub sell_amount: Amount,
+        pub sell_delivery_method: Option<String>,
+        pub buy_delivery_method: Option<String>,
+        pub candles: Vec<PriceCandle>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct PriceHistory {
+        pub sell_asset: String,
+        pub buy_asset: String,
+        pub slug: Option<String>,
+        pub from: NaiveDateTime,
+        pub to: NaiveDateTime,
+        pub interval_secs: i64,
+        pub series: Vec<PriceSeries>,
+    }
+
+    /// Reads `SEP38_PRICE_PAIRS`: `;`-separated entries of
+    /// `slug,sell_asset,buy_asset,sell_amount[,sell_delivery_method[,buy_delivery_method]]`.
+    pub fn sampled_pairs() -> Vec<SampledPair> {
+        let optional = |value: Option<&str>| {
+            value
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+
+        std::env::var("SEP38_PRICE_PAIRS")
+            .unwrap_or_default()
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let mut parts = entry.split(',').map(str::trim);
+                let pair = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                    (Some(slug), Some(sell_asset), Some(buy_asset), Some(sell_amount))
+                        if !slug.is_empty() && Amount::parse_positive(sell_amount).is_ok() =>
+                    {
+                        SampledPair {
+                            slug: slug.to_string(),
+                            sell_asset: sell_asset.to_string(),
+                            buy_asset: buy_asset.to_string(),
+                            sell_amount: Amount::parse_positive(sell_amount).unwrap_or_default(),
+                            sell_delivery_method: optional(parts.next()),
+                            buy_delivery_method: optional(parts.next()),
+                        }
+                    }
+                    _ => {
+                        eprintln!("Ignoring malformed SEP38_PRICE_PAIRS entry: {}", entry);
+                        return None;
+                    }
+                };
+                Some(pair)
+            })
+            .collect()
+    }
+
+    async fn sample_pair(conn: &mut PgConnection, pair: &SampledPair) -> Result<(), Sep38Error> {
+        let price = get_exchange_prices(
+            pair.slug.clone(),
+            pair.sell_asset.clone(),
+            pair.buy_asset.clone(),
+            Some(pair.sell_amount.clone()),
+            None,
+            pair.sell_delivery_method.clone(),
+            pair.buy_delivery_method.clone(),
+            None,
+            "sep6".to_string(),
+        )
+        .await?;
+
+        let sample = diesel::insert_into(sep38_price_samples::table)
+            .values(&NewSep38PriceSample {
+                anchor_slug: pair.slug.clone(),
+                sell_asset: pair.sell_asset.clone(),
+                buy_asset: pair.buy_asset.clone(),
+                sell_delivery_method: pair.sell_delivery_method.clone(),
+                buy_delivery_method: pair.buy_delivery_method.clone(),
+                sell_amount: price.sell_amount,
+                buy_amount: price.buy_amount,
+                price: price.price,
+                total_price: price.total_price,
+                fee_total: price.fee.total,
+                fee_asset: price.fee.asset_string,
+            })
+            .get_result::<Sep38PriceSample>(conn)
+            .map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+        // A failing alert shouldn't stop the sample being recorded
+        if let Err(e) = evaluate_alerts(conn, &sample).await {
+            eprintln!("Error evaluating rate alerts: {:?}", e);
+        }
+
+        Ok(())
+    }
+
+    /// Records one indicative price per configured pair; returns how many were stored.
+    pub async fn sample_prices() -> Result<usize, Sep38Error> {
+        let mut conn =
+            establish_connection().map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+        let mut stored = 0;
+        for pair in sampled_pairs() {
+            match sample_pair(&mut conn, &pair).await {
+                Ok(()) => stored += 1,
+                Err(e) => eprintln!(
+                    "Error sampling {} -> {} on {}: {:?}",
+                    pair.sell_asset, pair.buy_asset, pair.slug, e
+                ),
+            }
+        }
+        Ok(stored)
+    }
+
+    /// Samples configured pairs forever; spawned once at launch.
+    pub async fn run_price_sampler() {
+        let interval = std::env::var("SEP38_PRICE_SAMPLE_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+
+        loop {
+            if let Err(e) = sample_prices().await {
+                eprintln!("Error sampling SEP-38 prices: {:?}", e);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+        }
+    }
+
+    /// Accepts plain seconds or a number suffixed with s, m, h or d.
+    pub fn parse_interval(interval: &str) -> Result<i64, Sep38Error> {
+        let interval = interval.trim();
+        let (number, unit) = match interval.char_indices().last() {
+            Some((i, c)) if c.is_ascii_alphabetic() => (&interval[..i], c),
+            _ => (interval, 's'),
+        };
+        let multiplier = match unit {
+            's' => 1,
+            'm' => 60,
+            'h' => 3_600,
+            'd' => 86_400,
+            _ => 0,
+        };
+
+        number
+            .parse::<i64>()
+            .ok()
+            .and_then(|n| n.checked_mul(multiplier))
+            .filter(|secs| *secs > 0)
+            .ok_or_else(|| Sep38Error::InvalidRequest(format!("Invalid interval: {}", interval)))
+    }
+
+    fn parse_history_time(value: &str, field: &str) -> Result<NaiveDateTime, Sep38Error> {
+        DateTime::parse_from_rfc3339(value)
+            .map(|dt| dt.naive_utc())
+            .map_err(|_| Sep38Error::InvalidRequest(format!("Invalid {}: expected RFC 3339", field)))
+    }
+
+    fn bucket_start(at: NaiveDateTime, interval_secs: i64) -> NaiveDateTime {
+        let secs = at.and_utc().timestamp();
+        DateTime::from_timestamp(secs - secs.rem_euclid(interval_secs), 0)
+            .map(|dt| dt.naive_utc())
+            .unwrap_or(at)
+    }
+
+    fn candle(bucket_start: NaiveDateTime, samples: &[Sep38PriceSample]) -> PriceCandle {
+        let prices = samples.iter().map(|s| &s.price);
+        let fee_sum: Amount = samples.iter().map(|s| &s.fee_total).sum();
+        let first = &samples[0];
+        let last = &samples[samples.len() - 1];
+
+        PriceCandle {
+            bucket_start,
+            open: first.price.clone(),
+            high: prices.clone().max().cloned().unwrap_or_default(),
+            low: prices.min().cloned().unwrap_or_default(),
+            close: last.price.clone(),
+            total_price_close: last.total_price.clone(),
+            fee_average: Money::new(
+                Amount(fee_sum.into_inner() / bigdecimal::BigDecimal::from(samples.len() as i64)),
+                &first.fee_asset,
+            )
+            .amount,
+            samples: samples.len(),
+        }
+    }
+
+    // Splits time-ordered samples into series and each series into interval buckets
+    fn price_series(samples: Vec<Sep38PriceSample>, interval_secs: i64) -> Vec<PriceSeries> {
+        type SeriesKey = (String, Amount, Option<String>, Option<String>);
+        let mut grouped: BTreeMap<SeriesKey, Vec<Sep38PriceSample>> = BTreeMap::new();
+        for sample in samples {
+            let key = (
+                sample.anchor_slug.clone(),
+                sample.sell_amount.clone(),
+                sample.sell_delivery_method.clone(),
+                sample.buy_delivery_method.clone(),
+            );
+            grouped.entry(key).or_default().push(sample);
+        }
+
+        grouped
+            .into_iter()
+            .map(
+                |((slug, sell_amount, sell_delivery_method, buy_delivery_method), samples)| {
+                    let candles = samples
+                        .chunk_by(|a, b| {
+                            bucket_start(a.sampled_at, interval_secs)
+                                == bucket_start(b.sampled_at, interval_secs)
+                        })
+                        .map(|bucket| {
+                            candle(bucket_start(bucket[0].sampled_at, interval_secs), bucket)
+                        })
+                        .collect();
+                    PriceSeries {
+                        slug,
+                        sell_amount,
+                        sell_delivery_method,
+                        buy_delivery_method,
+                        candles,
+                    }
+                },
+            )
+            .collect()
+    }
+
+    /// OHLC candles of sampled prices for `pair` (`sell_asset/buy_asset`), one series per
+    /// anchor and sampled configuration. Defaults to the last 24 hours in hourly buckets.
+    pub fn get_price_history(
+        pair: &str,
+        from: Option<&str>,
+        to: Option<&str>,
+        interval: Option<&str>,
+        slug: Option<&str>,
+    ) -> Result<PriceHistory, Sep38Error> {
+        let (sell_asset, buy_asset) = pair
+            .split_once('/')
+            .filter(|(sell, buy)| !sell.is_empty() && !buy.is_empty())
+            .ok_or_else(|| {
+                Sep38Error::InvalidRequest("pair must be sell_asset/buy_asset".to_string())
+            })?;
+
+        let to = match to {
+            Some(value) => parse_history_time(value, "to")?,
+            None => Utc::now().naive_utc(),
+        };
+        let from = match from {
+            Some(value) => parse_history_time(value, "from")?,
+            None => to - Duration::hours(24),
+        };
+        if from >= to {
+            return Err(Sep38Error::InvalidRequest(
+                "from must be before to".to_string(),
+            ));
+        }
+
+        let interval_secs = parse_interval(interval.unwrap_or("1h"))?;
+        if (to - from).num_seconds() / interval_secs > MAX_HISTORY_BUCKETS {
+            return Err(Sep38Error::InvalidRequest(format!(
+                "Range covers more than {} intervals",
+                MAX_HISTORY_BUCKETS
+            )));
+        }
+
+        let mut conn =
+            establish_connection().map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+        let mut query = sep38_price_samples::table
+            .filter(sep38_price_samples::sell_asset.eq(sell_asset))
+            .filter(sep38_price_samples::buy_asset.eq(buy_asset))
+            .filter(sep38_price_samples::sampled_at.ge(from))
+            .filter(sep38_price_samples::sampled_at.lt(to))
+            .into_boxed();
+        if let Some(slug) = slug {
+            query = query.filter(sep38_price_samples::anchor_slug.eq(slug));
+        }
+
+        let samples: Vec<Sep38PriceSample> = query
+            .order(sep38_price_samples::sampled_at.asc())
+            .load(&mut conn)
+            .map_err(|e| Sep38Error::DatabaseError(e.to_string()))?;
+
+        Ok(PriceHistory {
+            sell_asset: sell_asset.to_string(),
+            buy_asset: buy_asset.to_string(),
+            slug: slug.map(str::to_string),
+            from,
+            to,
+            interval_secs,
+            series: price_series(samples, interval_secs),
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::str::FromStr;
+
+        fn at(value: &str) -> NaiveDateTime {
+            parse_history_time(value, "at").unwrap()
+        }
+
+        fn sample(
+            slug: &str,
+            sell_amount: &str,
+            price: &str,
+            sampled_at: &str,
+        ) -> Sep38PriceSample {
+            let amount = |value: &str| Amount::from_str(value).unwrap();
+            Sep38PriceSample {
+                id: Uuid::new_v4(),
+                anchor_slug: slug.to_string(),
+                sell_asset: "stellar:USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN"
+                    .to_string(),
+                buy_asset: "iso4217:EUR".to_string(),
+                sell_delivery_method: None,
+                buy_delivery_method: Some("SEPA".to_string()),
+                sell_amount: amount(sell_amount),
+                buy_amount: amount("90"),
+                price: amount(price),
+                total_price: amount(price),
+                fee_total: amount("1"),
+                fee_asset: "iso4217:EUR".to_string(),
+                sampled_at: at(sampled_at),
+            }
+        }
+
+        #[test]
+        fn parses_intervals() {
+            assert_eq!(parse_interval("90").unwrap(), 90);
+            assert_eq!(parse_interval("30s").unwrap(), 30);
+            assert_eq!(parse_interval("15m").unwrap(), 900);
+            assert_eq!(parse_interval(" 4h ").unwrap(), 14_400);
+            assert_eq!(parse_interval("1d").unwrap(), 86_400);
+        }
+
+        #[test]
+        fn rejects_invalid_intervals() {
+            for interval in ["", "0", "-1h", "1w", "h", "1.5h", "abc"] {
+                assert!(
+                    matches!(parse_interval(interval), Err(Sep38Error::InvalidRequest(_))),
+                    "{} should be rejected",
+                    interval
+                );
+            }
+        }
+
+        #[test]
+        fn rejects_overflowing_intervals() {
+            let huge = format!("{}d", i64::MAX / 1000);
+            assert!(matches!(
+                parse_interval(&huge),
+                Err(Sep38Error::InvalidRequest(_))
+            ));
+        }
+
+        #[test]
+        fn buckets_align_to_the_interval() {
+            assert_eq!(
+                bucket_start(at("2026-10-19T10:47:12Z"), 3_600),
+                at("2026-10-19T10:00:00Z")
+            );
+            assert_eq!(
+                bucket_start(at("2026-10-19T10:47:12Z"), 900),
+                at("2026-10-19T10:45:00Z")
+            );
+            assert_eq!(
+                bucket_start(at("2026-10-19T10:00:00Z"), 3_600),
+                at("2026-10-19T10:00:00Z")
+            );
+        }
+
+        #[test]
+        fn candles_track_open_high_low_close() {
+            let samples = vec![
+                sample("mykobo", "100", "0.91", "2026-10-19T10:05:00Z"),
+                sample("mykobo", "100", "0.95", "2026-10-19T10:20:00Z"),
+                sample("mykobo", "100", "0.89", "2026-10-19T10:35:00Z"),
+                sample("mykobo", "100", "0.92", "2026-10-19T10:50:00Z"),
+                sample("mykobo", "100", "0.93", "2026-10-19T11:05:00Z"),
+            ];
+            let series = price_series(samples, 3_600);
+            assert_eq!(series.len(), 1);
+
+            let candles = &series[0].candles;
+            assert_eq!(candles.len(), 2);
+            assert_eq!(candles[0].bucket_start, at("2026-10-19T10:00:00Z"));
+            assert_eq!(candles[0].open.to_string(), "0.91");
+            assert_eq!(candles[0].high.to_string(), "0.95");
+            assert_eq!(candles[0].low.to_string(), "0.89");
+            assert_eq!(candles[0].close.to_string(), "0.92");
+            assert_eq!(candles[0].samples, 4);
+            assert_eq!(candles[0].fee_average.to_string(), "1");
+            assert_eq!(candles[1].bucket_start, at("2026-10-19T11:00:00Z"));
+            assert_eq!(candles[1].samples, 1);
+        }
+
+        #[test]
+        fn anchors_and_amounts_get_separate_series() {
+            let samples = vec![
+                sample("mykobo", "100", "0.91", "2026-10-19T10:05:00Z"),
+                sample("other", "100", "0.50", "2026-10-19T10:06:00Z"),
+                sample("mykobo", "1000", "0.97", "2026-10-19T10:07:00Z"),
+                sample("mykobo", "100", "0.93", "2026-10-19T10:08:00Z"),
+            ];
+            let series = price_series(samples, 3_600);
+            assert_eq!(series.len(), 3);
+
+            let mykobo_100 = series
+                .iter()
+                .find(|s| s.slug == "mykobo" && s.sell_amount.to_string() == "100")
+                .unwrap();
+            assert_eq!(mykobo_100.candles.len(), 1);
+            assert_eq!(mykobo_100.candles[0].samples, 2);
+            assert_eq!(mykobo_100.candles[0].low.to_string(), "0.91");
+            assert_eq!(mykobo_100.candles[0].close.to_string(), "0.93");
+
+            let other = series.iter().find(|s| s.slug == "other").unwrap();
+            assert_eq!(other.candles[0].high.to_string(), "0.5");
+        }
+
+        fn delivery(names: &[&str]) -> Option<Vec<DeliveryMethod>> {
+            Some(
+                names
+                    .iter()
+                    .map(|name| DeliveryMethod {
+                        name: name.to_string(),
+                        description: String::new(),
+                    })
+                    .collect(),
+            )
+        }
+
+        fn eur_info() -> AssetInfo {
+            AssetInfo {
+                asset: "iso4217:EUR".to_string(),
+                sell_delivery_methods: delivery(&["SEPA"]),
+                buy_delivery_methods: delivery(&["SEPA", "SWIFT"]),
+                country_codes: Some(vec!["DE".to_string()]),
+            }
+        }
+
+        fn ranked(
+            slug: &str,
+            key: &str,
+            within_limits: bool,
+            eta_seconds: Option<i64>,
+        ) -> (bigdecimal::BigDecimal, RankedPrice) {
+            let amount = |value: &str| Amount::from_str(value).unwrap();
+            (
+                bigdecimal::BigDecimal::from_str(key).unwrap(),
+                RankedPrice {
+                    rank: 0,
+                    slug: slug.to_string(),
+                    sell_amount: amount("100"),
+                    buy_amount: amount("90"),
+                    price: amount("1.1"),
+                    total_price: amount("1.1"),
+                    fee_total: amount("1"),
+                    fee_asset: "iso4217:EUR".to_string(),
+                    fee_in_sell_asset: None,
+                    eta_seconds,
+                    min_amount: None,
+                    max_amount: None,
+                    within_limits,
+                },
+            )
+        }
+
+        #[test]
+        fn offers_asset_checks_the_delivery_method_for_the_side() {
+            let assets = vec![eur_info()];
+            assert!(offers_asset(&assets, "iso4217:EUR", None, true));
+            assert!(offers_asset(&assets, "iso4217:EUR", Some(""), false));
+            assert!(offers_asset(&assets, "iso4217:EUR", Some("SEPA"), true));
+            assert!(offers_asset(&assets, "iso4217:EUR", Some("SWIFT"), false));
+            assert!(!offers_asset(&assets, "iso4217:EUR", Some("SWIFT"), true));
+            assert!(!offers_asset(&assets, "iso4217:USD", None, true));
+        }
+
+        #[test]
+        fn offers_asset_rejects_methods_when_none_are_listed() {
+            let assets = vec![AssetInfo {
+                sell_delivery_methods: None,
+                ..eur_info()
+            }];
+            assert!(offers_asset(&assets, "iso4217:EUR", None, true));
+            assert!(!offers_asset(&assets, "iso4217:EUR", Some("SEPA"), true));
+        }
+
+        #[test]
+        fn average_duration_skips_incomplete_and_negative_spans() {
+            let timings = vec![
+                (
+                    Some(at("2026-10-19T10:00:00Z")),
+                    Some(at("2026-10-19T10:10:00Z")),
+                ),
+                (
+                    Some(at("2026-10-19T11:00:00Z")),
+                    Some(at("2026-10-19T11:20:00Z")),
+                ),
+                (None, Some(at("2026-10-19T12:00:00Z"))),
+                (Some(at("2026-10-19T13:00:00Z")), None),
+                (
+                    Some(at("2026-10-19T14:00:00Z")),
+                    Some(at("2026-10-19T13:00:00Z")),
+                ),
+            ];
+            assert_eq!(average_duration(timings), Some(900));
+            assert_eq!(average_duration(vec![]), None);
+            assert_eq!(average_duration(vec![(None, None)]), None);
+        }
+
+        #[test]
+        fn ranks_by_limits_then_key_then_eta() {
+            let prices = rank_prices(vec![
+                ranked("slow", "95", true, Some(3_600)),
+                ranked("over_limit", "99", false, Some(60)),
+                ranked("best", "96", true, None),
+                ranked("fast", "95", true, Some(60)),
+                ranked("unknown_eta", "95", true, None),
+            ]);
+
+            let order: Vec<(&str, usize)> = prices
+                .iter()
+                .map(|price| (price.slug.as_str(), price.rank))
+                .collect();
+            assert_eq!(
+                order,
+                vec![
+                    ("best", 1),
+                    ("fast", 2),
+                    ("slow", 3),
+                    ("unknown_eta", 4),
+                    ("over_limit", 5),
+                ]
+            );
+        }
+
+        #[test]
+        fn buying_a_fixed_amount_prefers_the_least_sold() {
+            // price_from_anchor negates the sell amount when buying a fixed amount
+            let prices = rank_prices(vec![
+                ranked("dear", "-105", true, None),
+                ranked("cheap", "-101", true, None),
+            ]);
+            assert_eq!(prices[0].slug, "cheap");
+            assert_eq!(prices[1].slug, "dear");
+        }
+
+        fn usdc_info() -> AssetInfo {
+            AssetInfo {
+                asset: "stellar:USDC:GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN"
+                    .to_string(),
+                sell_delivery_methods: None,
+                buy_delivery_methods: None,
+                country_codes: None,
+            }
+        }
+
+        fn names(values: &[&str]) -> BTreeSet<String> {
+            values.iter().map(|value| value.to_string()).collect()
+        }
+
+        #[test]
+        fn method_names_handles_missing_lists() {
+            assert!(method_names(&None).is_empty());
+            assert_eq!(
+                method_names(&delivery(&["SWIFT", "SEPA", "SEPA"])),
+                names(&["SEPA", "SWIFT"])
+            );
+        }
+
+        #[test]
+        fn set_changes_reports_added_and_removed() {
+            let (added, removed) =
+                set_changes(&names(&["SEPA", "ACH"]), &names(&["SEPA", "SWIFT"]));
+            assert_eq!(added, vec!["SWIFT".to_string()]);
+            assert_eq!(removed, vec!["ACH".to_string()]);
+
+            let (added, removed) = set_changes(&names(&["SEPA"]), &names(&["SEPA"]));
+            assert!(added.is_empty() && removed.is_empty());
+        }
+
+        #[test]
+        fn unchanged_catalogue_has_an_empty_diff() {
+            let assets = vec![eur_info(), usdc_info()];
+            assert!(diff_asset_catalogue(&assets, &assets).is_empty());
+            assert!(diff_asset_catalogue(&[], &[]).is_empty());
+        }
+
+        #[test]
+        fn diff_reports_added_and_removed_assets() {
+            let diff = diff_asset_catalogue(&[eur_info()], &[usdc_info()]);
+            assert_eq!(diff.added_assets, vec![usdc_info().asset]);
+            assert_eq!(diff.removed_assets, vec!["iso4217:EUR".to_string()]);
+            assert!(diff.changed_assets.is_empty());
+        }
+
+        #[test]
+        fn diff_reports_method_and_country_changes() {
+            let current = AssetInfo {
+                sell_delivery_methods: delivery(&["SEPA", "SWIFT"]),
+                buy_delivery_methods: None,
+                country_codes: Some(vec!["FR".to_string()]),
+                ..eur_info()
+            };
+            let diff = diff_asset_catalogue(&[eur_info(), usdc_info()], &[current, usdc_info()]);
+            assert!(diff.added_assets.is_empty());
+            assert!(diff.removed_assets.is_empty());
+            assert_eq!(diff.changed_assets.len(), 1);
+
+            let changes = &diff.changed_assets[0];
+            assert_eq!(changes.asset, "iso4217:EUR");
+            assert_eq!(changes.added_sell_methods, vec!["SWIFT".to_string()]);
+            assert!(changes.removed_sell_methods.is_empty());
+            assert!(changes.added_buy_methods.is_empty());
+            assert_eq!(
+                changes.removed_buy_methods,
+                vec!["SEPA".to_string(), "SWIFT".to_string()]
+            );
+            assert_eq!(changes.added_country_codes, vec!["FR".to_string()]);
+            assert_eq!(changes.removed_country_codes, vec!["DE".to_string()]);
+        }
+
+        #[test]
+        fn rows_decode_back_to_asset_info() {
+            let info = eur_info();
+            let row = Sep38Asset {
+                id: 1,
+                asset: info.asset.clone(),
+                sell_delivery_methods: Some(
+                    serde_json::to_value(&info.sell_delivery_methods).unwrap(),
+                ),
+                buy_delivery_methods: Some(serde_json::json!("not a list")),
+                country_codes: Some(serde_json::to_value(&info.country_codes).unwrap()),
+                created_at: at("2026-10-19T10:00:00Z"),
+                updated_at: at("2026-10-19T10:00:00Z"),
+                anchor_slug: "mykobo".to_string(),
+            };
+
+            let decoded = asset_from_row(&row);
+            assert_eq!(decoded.asset, "iso4217:EUR");
+            assert_eq!(
+                method_names(&decoded.sell_delivery_methods),
+                names(&["SEPA"])
+            );
+            assert!(decoded.buy_delivery_methods.is_none());
+            assert_eq!(decoded.country_codes, Some(vec!["DE".to_string()]));
+
+            // A round trip through the stored form must not read as a change
+            assert!(
+                diff_asset_catalogue(
+                    &[decoded],
+                    &[AssetInfo {
+                        buy_delivery_methods: None,
+                        ..info
+                    }]
+                )
+                .is_empty()
+            );
+        }
+    }
 }
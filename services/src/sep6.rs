@@ -1,21 +1,38 @@
 pub mod sep6 {
     use bigdecimal::BigDecimal;
-    use chrono::Utc;
-    use diesel::RunQueryDsl;
+    use chrono::{NaiveDateTime, Utc};
+    use diesel::prelude::*;
     use reqwest::Client;
     use serde::{Deserialize, Serialize};
-    use std::collections::HashMap;
+    use std::collections::{BTreeSet, HashMap};
     use std::str::FromStr;
     use thiserror::Error;
+    use uuid::Uuid;
 
-    use helpers::{auth::authenticate, keypair::generate_keypair};
+    use helpers::{
+        auth::authenticate,
+        horizon::HorizonClient,
+        keypair::{generate_keypair, public_account_id},
+        payment::{PaymentDetails, build_payment_envelope},
+    };
+    use stellar_base::{KeyPair, Network};
+    use stellar_base::transaction::MIN_BASE_FEE;
 
     use crate::common::get_anchor_config_details;
+    use crate::sep12::sep12::{Sep12Error, find_customer_by_account, onboard_to_anchor};
+    use crate::sep38::sep38::{
+        Sep38Error, ensure_quote_usable, get_exchange_prices, mark_quote_used,
+    };
+    use crate::webhook::webhook::{enqueue_event, SEP6_STATUS_CHANGED};
 
     use models::{
         common::establish_connection,
-        schema::offramp_service::sep6_transactions,
-        sep6::{NewSep6Transaction, Sep6Transaction},
+        money::{Amount, Money, STELLAR_DECIMALS},
+        schema::offramp_service::{sep6_refund_payments, sep6_refunds, sep6_transactions},
+        sep6::{
+            FieldSpec, NewSep6Refund, NewSep6RefundPayment, NewSep6Transaction, Refunds,
+            RequiredInfoUpdates, Sep6Refund, Sep6RefundPayment, Sep6Transaction,
+        },
     };
 
     #[derive(Error, Debug)]
@@ -37,6 +54,82 @@ pub mod sep6 {
 
         #[error("Database error: {0}")]
         DatabaseError(String),
+
+        #[error("Payment failed: {0}")]
+        PaymentFailed(String),
+
+        #[error("Payment already submitted: {0}")]
+        PaymentAlreadySubmitted(String),
+
+        #[error("Payment {hash} submitted but not recorded: {reason}")]
+        PaymentNotRecorded { hash: String, reason: String },
+
+        #[error("Insufficient balance: {0}")]
+        InsufficientBalance(String),
+
+        #[error("Quote rejected: {0}")]
+        QuoteError(#[from] Sep38Error),
+
+        #[error("Customer info needed: {}", .0.join(", "))]
+        CustomerInfoNeeded(Vec<String>),
+
+        #[error("Customer info status: {}", .0.status)]
+        CustomerInfoStatus(CustomerInfoStatus),
+
+        #[error("KYC error: {0}")]
+        KycError(#[from] Sep12Error),
+    }
+
+    // Lets `conn.transaction` bodies use `?` on queries and still return Sep6Error
+    impl From<diesel::result::Error> for Sep6Error {
+        fn from(e: diesel::result::Error) -> Self {
+            Sep6Error::DatabaseError(e.to_string())
+        }
+    }
+
+    // SEP-6 `customer_info_status`: KYC is with the anchor but not accepted yet
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct CustomerInfoStatus {
+        pub status: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub more_info_url: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub eta: Option<i64>,
+    }
+
+    // Body of a 403 from /withdraw or /withdraw-exchange
+    #[derive(Debug, Deserialize)]
+    #[serde(tag = "type")]
+    enum ForbiddenResponse {
+        #[serde(rename = "non_interactive_customer_info_needed")]
+        CustomerInfoNeeded {
+            #[serde(default)]
+            fields: Vec<String>,
+        },
+        #[serde(rename = "customer_info_status")]
+        CustomerInfoStatus(CustomerInfoStatus),
+        #[serde(other)]
+        Other,
+    }
+
+    // Turn a non-success withdraw response into the matching typed error
+    async fn withdraw_rejection(response: reqwest::Response) -> Sep6Error {
+        let status = response.status();
+        if status == reqwest::StatusCode::FORBIDDEN
+            && let Ok(body) = response.json::<ForbiddenResponse>().await
+        {
+            match body {
+                ForbiddenResponse::CustomerInfoNeeded { fields } => {
+                    return Sep6Error::CustomerInfoNeeded(fields);
+                }
+                ForbiddenResponse::CustomerInfoStatus(info) => {
+                    return Sep6Error::CustomerInfoStatus(info);
+                }
+                ForbiddenResponse::Other => {}
+            }
+        }
+
+        Sep6Error::InvalidRequest(format!("Status: {}", status))
     }
 
     #[derive(Debug, Serialize, Deserialize)]
@@ -45,10 +138,10 @@ pub mod sep6 {
         pub instructions: Option<HashMap<String, InstructionField>>,
         pub id: Option<String>,
         pub eta: Option<i32>,
-        pub min_amount: Option<f64>,
-        pub max_amount: Option<f64>,
-        pub fee_fixed: Option<f64>,
-        pub fee_percent: Option<f64>,
+        pub min_amount: Option<Amount>,
+        pub max_amount: Option<Amount>,
+        pub fee_fixed: Option<Amount>,
+        pub fee_percent: Option<Amount>,
         pub extra_info: Option<ExtraInfo>,
     }
     #[derive(Error, Debug)]
@@ -90,41 +183,57 @@ pub mod sep6 {
         pub memo: Option<String>,
         pub id: Option<String>,
         pub eta: Option<i32>,
-        pub min_amount: Option<f64>,
-        pub max_amount: Option<f64>,
-        pub fee_fixed: Option<f64>,
-        pub fee_percent: Option<f64>,
+        pub min_amount: Option<Amount>,
+        pub max_amount: Option<Amount>,
+        pub fee_fixed: Option<Amount>,
+        pub fee_percent: Option<Amount>,
         pub extra_info: Option<ExtraInfo>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub stellar_transaction_id: Option<String>,
     }
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct InfoResponse {
+        #[serde(default)]
         pub deposit: HashMap<String, DepositAssetInfo>,
-        // #[serde(rename = "deposit-exchange")]
-        // pub deposit_exchange: HashMap<String, ExchangeAssetInfo>,
+        #[serde(rename = "deposit-exchange", default)]
+        pub deposit_exchange: HashMap<String, ExchangeAssetInfo>,
+        #[serde(default)]
         pub withdraw: HashMap<String, WithdrawAssetInfo>,
-        // #[serde(rename = "withdraw-exchange")]
-        // pub withdraw_exchange: HashMap<String, ExchangeAssetInfo>,
+        #[serde(rename = "withdraw-exchange", default)]
+        pub withdraw_exchange: HashMap<String, ExchangeAssetInfo>,
+        #[serde(default)]
         pub fee: FeeInfo,
+        #[serde(default)]
         pub transactions: EndpointInfo,
+        #[serde(default)]
         pub transaction: EndpointInfo,
+        #[serde(default)]
         pub features: Features,
     }
 
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct WithdrawTypeInfo {
+        #[serde(default)]
+        pub fields: HashMap<String, FieldSpec>,
+    }
+
     #[derive(Debug, Serialize, Deserialize)]
     pub struct DepositAssetInfo {
         pub enabled: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub authentication_required: Option<bool>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub min_amount: Option<f64>,
+        pub min_amount: Option<Amount>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub max_amount: Option<f64>,
+        pub max_amount: Option<Amount>,
         pub funding_methods: Option<Vec<String>>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub fee_fixed: Option<f64>,
+        pub fee_fixed: Option<Amount>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub fee_percent: Option<f64>,
+        pub fee_percent: Option<Amount>,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        pub fields: HashMap<String, FieldSpec>,
     }
 
     #[derive(Debug, Serialize, Deserialize)]
@@ -133,31 +242,41 @@ pub mod sep6 {
         #[serde(skip_serializing_if = "Option::is_none")]
         pub authentication_required: Option<bool>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub min_amount: Option<f64>,
+        pub min_amount: Option<Amount>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub max_amount: Option<f64>,
+        pub max_amount: Option<Amount>,
         pub funding_methods: Option<Vec<String>>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub fee_fixed: Option<f64>,
+        pub fee_fixed: Option<Amount>,
         #[serde(skip_serializing_if = "Option::is_none")]
-        pub fee_percent: Option<f64>,
+        pub fee_percent: Option<Amount>,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        pub types: HashMap<String, WithdrawTypeInfo>,
     }
 
+    // deposit-exchange assets describe their fields directly, withdraw-exchange assets per type
     #[derive(Debug, Serialize, Deserialize)]
     pub struct ExchangeAssetInfo {
+        #[serde(default)]
+        pub enabled: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub authentication_required: Option<bool>,
+        #[serde(default)]
         pub funding_methods: Vec<String>,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        pub fields: HashMap<String, FieldSpec>,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        pub types: HashMap<String, WithdrawTypeInfo>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Default, Serialize, Deserialize)]
     pub struct FeeInfo {
         pub enabled: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
         pub description: Option<String>,
     }
 
-    #[derive(Debug, Serialize, Deserialize)]
+    #[derive(Debug, Default, Serialize, Deserialize)]
     pub struct EndpointInfo {
         pub enabled: bool,
         #[serde(skip_serializing_if = "Option::is_none")]
@@ -166,10 +285,168 @@ pub mod sep6 {
 
     #[derive(Debug, Serialize, Deserialize)]
     pub struct Features {
+        #[serde(default = "default_true")]
         pub account_creation: bool,
+        #[serde(default)]
         pub claimable_balances: bool,
     }
 
+    // SEP-6 defaults account_creation to true when the anchor omits it
+    impl Default for Features {
+        fn default() -> Self {
+            Features {
+                account_creation: true,
+                claimable_balances: false,
+            }
+        }
+    }
+
+    fn default_true() -> bool {
+        true
+    }
+
+    /// A transaction the anchor has parked in `pending_transaction_info_update`, with the
+    /// fields it wants corrected.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct PendingInfoUpdate {
+        pub transaction_id: String,
+        pub kind: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub user_action_required_by: Option<NaiveDateTime>,
+        pub fields: Vec<FormField>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct FormField {
+        pub name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub description: Option<String>,
+        pub optional: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub choices: Option<Vec<String>>,
+    }
+
+    /// What the UI has to collect for one asset and funding method before starting a
+    /// withdrawal.
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct WithdrawFormSchema {
+        pub asset_code: String,
+        pub kind: String,
+        pub funding_method: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub authentication_required: Option<bool>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub min_amount: Option<Amount>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub max_amount: Option<Amount>,
+        pub fields: Vec<FormField>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct TransactionRefunds {
+        pub transaction_id: String,
+        pub amount_refunded: Amount,
+        pub amount_fee: Amount,
+        pub payments: Vec<Sep6RefundPayment>,
+    }
+
+    // Mirror the refunds object into sep6_refunds/sep6_refund_payments. Totals follow the
+    // anchor; payments are keyed by their anchor payment id so re-polling adds nothing twice.
+    fn save_refunds(
+        conn: &mut PgConnection,
+        row_id: Uuid,
+        refunds: &Refunds,
+    ) -> Result<(), Sep6Error> {
+        let new_refund = NewSep6Refund {
+            transaction_id: row_id,
+            amount_refunded: refunds.amount_refunded.clone(),
+            amount_fee: refunds.amount_fee.clone(),
+        };
+        let refund_id: Uuid = diesel::insert_into(sep6_refunds::table)
+            .values(&new_refund)
+            .on_conflict(sep6_refunds::transaction_id)
+            .do_update()
+            .set(&new_refund)
+            .returning(sep6_refunds::id)
+            .get_result(conn)
+            .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+
+        let payments: Vec<NewSep6RefundPayment> = refunds
+            .payments
+            .iter()
+            .map(|p| NewSep6RefundPayment {
+                refund_id,
+                payment_id: p.id.clone(),
+                id_type: p.id_type.clone(),
+                amount: p.amount.clone(),
+                fee: p.fee.clone(),
+            })
+            .collect();
+        if !payments.is_empty() {
+            diesel::insert_into(sep6_refund_payments::table)
+                .values(&payments)
+                .on_conflict(sep6_refund_payments::payment_id)
+                .do_nothing()
+                .execute(conn)
+                .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    // Upsert a transaction polled from the anchor and publish a status change event
+    fn save_transaction(
+        conn: &mut PgConnection,
+        new_tx: &NewSep6Transaction,
+    ) -> Result<(), Sep6Error> {
+        conn.transaction::<_, Sep6Error, _>(|conn| {
+            let previous_status: Option<String> = sep6_transactions::table
+                .filter(sep6_transactions::transaction_id.eq(&new_tx.transaction_id))
+                .select(sep6_transactions::status)
+                .first(conn)
+                .optional()
+                .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+
+            let row_id: Uuid = diesel::insert_into(sep6_transactions::table)
+                .values(new_tx)
+                .on_conflict(sep6_transactions::transaction_id)
+                .do_update()
+                .set(new_tx)
+                .returning(sep6_transactions::id)
+                .get_result(conn)
+                .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+
+            // Transactions started outside this service still consume the quote they reference
+            if let Some(qid) = &new_tx.quote_id {
+                mark_quote_used(conn, qid, row_id, "sep6")?;
+            }
+
+            if let Some(refunds) = &new_tx.refunds {
+                save_refunds(conn, row_id, refunds)?;
+            }
+
+            if previous_status.as_deref() != Some(new_tx.status.as_str()) {
+                enqueue_event(
+                    conn,
+                    SEP6_STATUS_CHANGED,
+                    serde_json::json!({
+                        "transaction_id": new_tx.transaction_id,
+                        "kind": new_tx.kind,
+                        "previous_status": previous_status,
+                        "status": new_tx.status,
+                        "stellar_transaction_id": new_tx.stellar_transaction_id,
+                        "external_transaction_id": new_tx.external_transaction_id,
+                    }),
+                )
+                .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+            }
+
+            Ok(())
+        })
+    }
+
     // 1. GET /info
     pub async fn get_anchor_info(slug: &str) -> Result<InfoResponse, Sep6Error> {
         let client = Client::new();
@@ -304,15 +581,10 @@ pub mod sep6 {
                     required_info_updates: tx.required_info_updates.clone(),
                     instructions: tx.instructions.clone(),
                     claimable_balance_id: tx.claimable_balance_id.clone(),
+                    account: Some(keypair.public_key().account_id()),
                 };
 
-                diesel::insert_into(sep6_transactions::table)
-                    .values(&new_tx)
-                    .on_conflict(sep6_transactions::transaction_id)
-                    .do_update()
-                    .set(&new_tx)
-                    .execute(&mut conn)
-                    .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+                save_transaction(&mut conn, &new_tx)?;
             }
 
             Ok(transactions)
@@ -418,15 +690,10 @@ pub mod sep6 {
                 required_info_updates: tx.required_info_updates.clone(),
                 instructions: tx.instructions.clone(),
                 claimable_balance_id: tx.claimable_balance_id.clone(),
+                account: Some(keypair.public_key().account_id()),
             };
 
-            diesel::insert_into(sep6_transactions::table)
-                .values(&new_tx)
-                .on_conflict(sep6_transactions::transaction_id)
-                .do_update()
-                .set(&new_tx)
-                .execute(&mut conn)
-                .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+            save_transaction(&mut conn, &new_tx)?;
 
             Ok(tx)
         } else if response.status() == 404 {
@@ -445,7 +712,7 @@ pub mod sep6 {
         account: &str,
         source_asset: &str,
         destination_asset: &str,
-        amount: &str,
+        amount: &Amount,
         quote_id: Option<&str>,
         funding_method: &str,
         memo: Option<&str>,
@@ -476,13 +743,19 @@ pub mod sep6 {
                 Err(_) => return Err(Sep6Error::AuthFailed),
             };
 
+        if let Some(qid) = quote_id {
+            let mut conn =
+                establish_connection().map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+            ensure_quote_usable(&mut conn, qid, account)?;
+        }
+
         let mut request = client
             .get(&format!("{}/withdraw-exchange", transfer_server_str))
             .bearer_auth(jwt)
             .query(&[
                 ("source_asset", source_asset),
                 ("destination_asset", destination_asset),
-                ("amount", amount),
+                ("amount", &amount.to_string()),
                 ("funding_method", funding_method),
             ]);
 
@@ -526,10 +799,7 @@ pub mod sep6 {
                     status: "pending_user_transfer_start".to_string(), // Default status
                     status_eta: None,
                     more_info_url: None,
-                    amount_in: Some(
-                        BigDecimal::from_str(amount)
-                            .map_err(|_| Sep6Error::InvalidRequest("Invalid amount".to_string()))?,
-                    ),
+                    amount_in: Some(amount.clone()),
                     amount_in_asset: Some(source_asset.to_string()),
                     amount_out: None,
                     amount_out_asset: Some(destination_asset.to_string()),
@@ -559,32 +829,104 @@ pub mod sep6 {
                     required_info_updates: None,
                     instructions: None,
                     claimable_balance_id: None,
+                    account: Some(keypair.public_key().account_id()),
                 };
 
-                diesel::insert_into(sep6_transactions::table)
+                let row_id: Uuid = diesel::insert_into(sep6_transactions::table)
                     .values(&new_tx)
-                    .execute(&mut conn)
+                    .returning(sep6_transactions::id)
+                    .get_result(&mut conn)
                     .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+
+                if let Some(qid) = quote_id {
+                    mark_quote_used(&mut conn, qid, row_id, "sep6")?;
+                }
             }
 
             Ok(withdraw_response)
         } else {
-            Err(Sep6Error::InvalidRequest(format!(
-                "Status: {}",
-                response.status()
-            )))
+            Err(withdraw_rejection(response).await)
+        }
+    }
+
+    /// Sends the KYC held for `account` to the anchor after a withdrawal was refused with
+    /// `non_interactive_customer_info_needed`. Requested fields we hold no value for come
+    /// back as still needed, so the caller knows what to ask the user for.
+    pub async fn submit_customer_info(
+        slug: &str,
+        account: &str,
+        memo: Option<&str>,
+        fields: &[String],
+    ) -> Result<(), Sep6Error> {
+        let customer = find_customer_by_account(account, memo)?
+            .ok_or_else(|| Sep6Error::CustomerInfoNeeded(fields.to_vec()))?;
+
+        let submission = onboard_to_anchor(&customer.id.to_string(), slug).await?;
+        let submitted: Vec<String> =
+            serde_json::from_str(&submission.submitted_fields).unwrap_or_default();
+
+        let missing: Vec<String> = fields
+            .iter()
+            .filter(|f| !submitted.contains(f))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            return Err(Sep6Error::CustomerInfoNeeded(missing));
+        }
+
+        Ok(())
+    }
+
+    // Make sure the account can cover the withdrawal before asking the anchor for instructions
+    async fn check_withdraw_balance<H: HorizonClient>(
+        horizon: &H,
+        account_id: &str,
+        asset_code: &str,
+        asset_issuer: Option<&str>,
+        amount: &Amount,
+    ) -> Result<(), Sep6Error> {
+        if amount.round_stellar() != *amount {
+            return Err(Sep6Error::InvalidRequest(format!(
+                "amount has more than {} decimal places",
+                STELLAR_DECIMALS
+            )));
         }
+
+        let stellar_account = horizon
+            .load_account(account_id)
+            .await
+            .map_err(|e| Sep6Error::InvalidRequest(e.to_string()))?;
+
+        let balance = stellar_account
+            .balance_for(asset_code, asset_issuer)
+            .ok_or_else(|| {
+                Sep6Error::InsufficientBalance(format!("no trustline for {}", asset_code))
+            })?;
+
+        let available = Amount::from_str(&balance.balance)
+            .map_err(|e| Sep6Error::InvalidRequest(e.to_string()))?;
+
+        if &available < amount {
+            return Err(Sep6Error::InsufficientBalance(format!(
+                "{} {} available, {} requested",
+                balance.balance, asset_code, amount
+            )));
+        }
+
+        Ok(())
     }
 
     // 5. GET /withdraw
-    pub async fn get_withdraw(
+    pub async fn get_withdraw<H: HorizonClient>(
+        horizon: &H,
         slug: &str,
         account: &str,
         asset_code: &str,
+        asset_issuer: Option<&str>,
         funding_method: &str,
         memo: Option<&str>,
         on_change_callback: Option<&str>,
-        amount: Option<&str>,
+        amount: Option<&Amount>,
         country_code: Option<&str>,
         refund_memo: Option<&str>,
         refund_memo_type: Option<&str>,
@@ -594,6 +936,18 @@ pub mod sep6 {
             Ok(kp) => kp,
             Err(_) => return Err(Sep6Error::AuthFailed),
         };
+
+        if let Some(amt) = amount {
+            check_withdraw_balance(
+                horizon,
+                &keypair.public_key().account_id(),
+                asset_code,
+                asset_issuer,
+                amt,
+            )
+            .await?;
+        }
+
         let anchor_config =
             get_anchor_config_details(&helpers::stellartoml::AnchorService::new(), slug)
                 .await
@@ -627,7 +981,7 @@ pub mod sep6 {
         }
 
         if let Some(amt) = amount {
-            request = request.query(&[("amount", amt)]);
+            request = request.query(&[("amount", amt.to_string())]);
         }
 
         if let Some(code) = country_code {
@@ -658,13 +1012,7 @@ pub mod sep6 {
                     status: "pending_user_transfer_start".to_string(), // Default status
                     status_eta: None,
                     more_info_url: None,
-                    amount_in: amount
-                        .map(|a| {
-                            BigDecimal::from_str(a).map_err(|_| {
-                                Sep6Error::InvalidRequest("Invalid amount".to_string())
-                            })
-                        })
-                        .transpose()?,
+                    amount_in: amount.cloned(),
                     amount_in_asset: Some(asset_code.to_string()),
                     amount_out: None,
                     amount_out_asset: None,
@@ -694,6 +1042,7 @@ pub mod sep6 {
                     required_info_updates: None,
                     instructions: None,
                     claimable_balance_id: None,
+                    account: Some(keypair.public_key().account_id()),
                 };
 
                 diesel::insert_into(sep6_transactions::table)
@@ -703,6 +1052,224 @@ pub mod sep6 {
             }
 
             Ok(withdraw_response)
+        } else {
+            Err(withdraw_rejection(response).await)
+        }
+    }
+
+    // Builds, signs and submits the payment unless one was already recorded for the withdrawal
+    async fn pay_withdrawal<H: HorizonClient>(
+        horizon: &H,
+        keypair: &KeyPair,
+        network: &Network,
+        details: &PaymentDetails<'_>,
+        previous_hash: Option<&str>,
+    ) -> Result<String, Sep6Error> {
+        if let Some(hash) = previous_hash {
+            return Err(Sep6Error::PaymentAlreadySubmitted(hash.to_string()));
+        }
+
+        let source = keypair.public_key().account_id();
+        let sequence = horizon
+            .fetch_account_sequence(&source)
+            .await
+            .map_err(|e| Sep6Error::PaymentFailed(e.to_string()))?;
+
+        let envelope = build_payment_envelope(keypair, sequence, network, details)
+            .map_err(|e| Sep6Error::PaymentFailed(e.to_string()))?;
+
+        let submitted = horizon
+            .submit_transaction(&envelope)
+            .await
+            .map_err(|e| Sep6Error::PaymentFailed(e.to_string()))?;
+
+        Ok(submitted.hash)
+    }
+
+    // 6. Send the withdrawal payment to the anchor's account
+    pub async fn submit_withdraw_payment<H: HorizonClient>(
+        horizon: &H,
+        slug: &str,
+        account: &str,
+        asset_code: &str,
+        asset_issuer: Option<&str>,
+        amount: &Amount,
+        withdraw: &WithdrawResponse,
+    ) -> Result<String, Sep6Error> {
+        let destination = withdraw.account_id.as_deref().ok_or_else(|| {
+            Sep6Error::InvalidRequest("Anchor did not return an account_id".to_string())
+        })?;
+        // Without a transaction to record the hash against, a retry could pay twice
+        let tx_id = withdraw.id.as_deref().ok_or_else(|| {
+            Sep6Error::InvalidRequest("Anchor did not return a transaction id".to_string())
+        })?;
+        let keypair = match generate_keypair(account) {
+            Ok(kp) => kp,
+            Err(_) => return Err(Sep6Error::AuthFailed),
+        };
+        let anchor_config =
+            get_anchor_config_details(&helpers::stellartoml::AnchorService::new(), slug)
+                .await
+                .map_err(|_| Sep6Error::AnchorNotSupported)?;
+        let network = Network::new(anchor_config.general_info.network_passphrase.clone());
+
+        let mut conn =
+            establish_connection().map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+        let previous_hash: Option<String> = sep6_transactions::table
+            .filter(sep6_transactions::transaction_id.eq(tx_id))
+            .select(sep6_transactions::stellar_transaction_id)
+            .first::<Option<String>>(&mut conn)
+            .optional()
+            .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?
+            .ok_or(Sep6Error::TransactionNotFound)?;
+
+        let amount = amount.to_string();
+        let hash = pay_withdrawal(
+            horizon,
+            &keypair,
+            &network,
+            &PaymentDetails {
+                destination,
+                asset_code,
+                asset_issuer,
+                amount: &amount,
+                memo_type: withdraw.memo_type.as_deref(),
+                memo: withdraw.memo.as_deref(),
+            },
+            previous_hash.as_deref(),
+        )
+        .await?;
+
+        // The payment is on-chain now; the stored hash is what stops a retry paying twice
+        record_payment_hash(&mut conn, tx_id, &hash).await?;
+
+        Ok(hash)
+    }
+
+    const RECORD_PAYMENT_ATTEMPTS: u32 = 3;
+
+    // Retry transient failures, then hand the hash back in the error so it isn't lost
+    async fn record_payment_hash(
+        conn: &mut PgConnection,
+        tx_id: &str,
+        hash: &str,
+    ) -> Result<(), Sep6Error> {
+        let mut attempt = 1;
+        loop {
+            let result = diesel::update(
+                sep6_transactions::table.filter(sep6_transactions::transaction_id.eq(tx_id)),
+            )
+            .set((
+                sep6_transactions::stellar_transaction_id.eq(hash),
+                sep6_transactions::updated_at.eq(Some(Utc::now().naive_utc())),
+            ))
+            .execute(conn);
+
+            match result {
+                Ok(_) => return Ok(()),
+                Err(e) if attempt >= RECORD_PAYMENT_ATTEMPTS => {
+                    return Err(Sep6Error::PaymentNotRecorded {
+                        hash: hash.to_string(),
+                        reason: e.to_string(),
+                    });
+                }
+                Err(_) => {
+                    tokio::time::sleep(std::time::Duration::from_millis(250 * attempt as u64))
+                        .await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct EstimateLineItem {
+        pub name: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub description: Option<String>,
+        pub amount: Amount,
+        pub asset: String,
+        /// False for charges paid on top of the amount sent, like the network fee in XLM.
+        pub deducted: bool,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct WithdrawEstimate {
+        pub send_amount: Amount,
+        pub send_asset: String,
+        pub anchor_fee: Amount,
+        pub anchor_fee_asset: String,
+        pub fee_source: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub price: Option<Amount>,
+        /// Paid in XLM by the sending account on top of `send_amount`; never deducted.
+        pub network_fee: Amount,
+        pub network_fee_asset: String,
+        pub receive_amount: Amount,
+        pub receive_asset: String,
+        pub line_items: Vec<EstimateLineItem>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct FeeResponse {
+        fee: Amount,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct FeeQuote {
+        pub fee: Amount,
+        pub asset_code: String,
+        pub source: String,
+    }
+
+    // Where the anchor fee in an estimate came from
+    pub const FEE_SOURCE_INFO: &str = "info";
+    pub const FEE_SOURCE_ENDPOINT: &str = "fee_endpoint";
+    pub const FEE_SOURCE_QUOTE: &str = "quote";
+    pub const FEE_SOURCE_NONE: &str = "none";
+
+    // GET /fee (authenticated)
+    async fn request_anchor_fee(
+        slug: &str,
+        account: &str,
+        operation: &str,
+        kind: Option<&str>,
+        asset_code: &str,
+        amount: &Amount,
+    ) -> Result<Amount, Sep6Error> {
+        let client = Client::new();
+        let keypair = generate_keypair(account).map_err(|_| Sep6Error::AuthFailed)?;
+
+        let anchor_config =
+            get_anchor_config_details(&helpers::stellartoml::AnchorService::new(), slug)
+                .await
+                .map_err(|_| Sep6Error::AuthFailed)?;
+        let transfer_server = anchor_config
+            .general_info
+            .transfer_server
+            .unwrap_or_default();
+        let jwt = authenticate(&helpers::stellartoml::AnchorService::new(), slug, &keypair)
+            .await
+            .map_err(|_| Sep6Error::AuthFailed)?;
+
+        let mut request = client
+            .get(format!("{}/fee", transfer_server))
+            .bearer_auth(jwt)
+            .query(&[
+                ("operation", operation),
+                ("asset_code", asset_code),
+                ("amount", &amount.to_string()),
+            ]);
+
+        if let Some(k) = kind {
+            request = request.query(&[("type", k)]);
+        }
+
+        let response = request.send().await?;
+
+        if response.status().is_success() {
+            let fee: FeeResponse = response.json().await?;
+            Ok(fee.fee)
         } else {
             Err(Sep6Error::InvalidRequest(format!(
                 "Status: {}",
@@ -710,4 +1277,801 @@ pub mod sep6 {
             )))
         }
     }
+
+    // fee_fixed + amount * fee_percent / 100, or None when the anchor publishes neither
+    fn fee_from_rates(
+        fee_fixed: Option<&Amount>,
+        fee_percent: Option<&Amount>,
+        amount: &Amount,
+    ) -> Option<Amount> {
+        if fee_fixed.is_none() && fee_percent.is_none() {
+            return None;
+        }
+
+        let fixed = fee_fixed.cloned().unwrap_or_default();
+        let percent = fee_percent
+            .map(|p| Amount(&(amount * p).0 / BigDecimal::from(100)))
+            .unwrap_or_default();
+        Some(&fixed + &percent)
+    }
+
+    // Ask the anchor's /fee endpoint when it is enabled, otherwise compute from the asset's
+    // published fee_fixed/fee_percent
+    async fn resolve_fee(
+        info: &InfoResponse,
+        slug: &str,
+        account: &str,
+        operation: &str,
+        kind: Option<&str>,
+        asset_code: &str,
+        amount: &Amount,
+    ) -> Result<(Amount, &'static str), Sep6Error> {
+        let rates = match operation {
+            "deposit" => info
+                .deposit
+                .get(asset_code)
+                .filter(|a| a.enabled)
+                .map(|a| (a.fee_fixed.as_ref(), a.fee_percent.as_ref())),
+            "withdraw" => info
+                .withdraw
+                .get(asset_code)
+                .filter(|a| a.enabled)
+                .map(|a| (a.fee_fixed.as_ref(), a.fee_percent.as_ref())),
+            _ => {
+                return Err(Sep6Error::InvalidRequest(
+                    "operation must be deposit or withdraw".to_string(),
+                ));
+            }
+        };
+        let (fee_fixed, fee_percent) = rates.ok_or_else(|| {
+            Sep6Error::InvalidRequest(format!("{} of {} is not supported", operation, asset_code))
+        })?;
+
+        if info.fee.enabled {
+            let fee =
+                request_anchor_fee(slug, account, operation, kind, asset_code, amount).await?;
+            return Ok((fee, FEE_SOURCE_ENDPOINT));
+        }
+
+        Ok(match fee_from_rates(fee_fixed, fee_percent, amount) {
+            Some(fee) => (fee, FEE_SOURCE_INFO),
+            None => (Amount::zero(), FEE_SOURCE_NONE),
+        })
+    }
+
+    // 7. GET /fee
+    pub async fn get_fee(
+        slug: &str,
+        account: &str,
+        operation: &str,
+        kind: Option<&str>,
+        asset_code: &str,
+        amount: &Amount,
+    ) -> Result<FeeQuote, Sep6Error> {
+        if !amount.is_positive() {
+            return Err(Sep6Error::InvalidRequest(
+                "amount must be positive".to_string(),
+            ));
+        }
+
+        let info = get_anchor_info(slug).await?;
+        let (fee, source) =
+            resolve_fee(&info, slug, account, operation, kind, asset_code, amount).await?;
+
+        Ok(FeeQuote {
+            fee: Money::new(fee, asset_code).amount,
+            asset_code: asset_code.to_string(),
+            source: source.to_string(),
+        })
+    }
+
+    // Fee for a single-operation payment at the network minimum base fee, in XLM
+    fn network_fee() -> Amount {
+        Amount(BigDecimal::new(MIN_BASE_FEE.to_i64().into(), STELLAR_DECIMALS))
+    }
+
+    // Anchor fee and payout, both rounded to the asset; the network fee is not part of this
+    fn withdrawal_payout(
+        amount: &Amount,
+        fee: Amount,
+        asset_code: &str,
+    ) -> Result<(Amount, Amount), Sep6Error> {
+        let fee = Money::new(fee, asset_code).amount;
+        let receive_amount = Money::new(amount - &fee, asset_code).amount;
+        if !receive_amount.is_positive() {
+            return Err(Sep6Error::InvalidRequest(format!(
+                "amount does not cover the anchor fee of {} {}",
+                fee, asset_code
+            )));
+        }
+        Ok((fee, receive_amount))
+    }
+
+    /// Expected payout of a withdrawal before it is started. Plain withdrawals are charged
+    /// the same way as `get_fee`; exchange withdrawals use the SEP-38 indicative price,
+    /// whose fee is already part of the quoted buy amount. The network fee is paid in XLM on top of
+    /// the amount sent, so it is listed but not deducted.
+    pub async fn estimate_withdrawal(
+        slug: &str,
+        account: &str,
+        asset_code: &str,
+        asset_issuer: Option<&str>,
+        amount: &Amount,
+        funding_method: Option<&str>,
+        destination_asset: Option<&str>,
+        buy_delivery_method: Option<&str>,
+        country_code: Option<&str>,
+    ) -> Result<WithdrawEstimate, Sep6Error> {
+        if !amount.is_positive() {
+            return Err(Sep6Error::InvalidRequest(
+                "amount must be positive".to_string(),
+            ));
+        }
+
+        // Accept either a bare code or a SEP-38 `stellar:CODE:ISSUER` identifier
+        let (code, issuer) = match asset_code.strip_prefix("stellar:") {
+            Some(rest) => {
+                let mut parts = rest.splitn(2, ':');
+                (parts.next().unwrap_or_default(), parts.next().or(asset_issuer))
+            }
+            None => (asset_code, asset_issuer),
+        };
+
+        let info = get_anchor_info(slug).await?;
+        let asset_info = info
+            .withdraw
+            .get(code)
+            .filter(|a| a.enabled)
+            .ok_or_else(|| {
+                Sep6Error::InvalidRequest(format!("withdrawals of {} are not supported", code))
+            })?;
+
+        if let Some(min) = &asset_info.min_amount
+            && amount < min
+        {
+            return Err(Sep6Error::InvalidRequest(format!(
+                "amount is below the minimum of {} {}",
+                min, code
+            )));
+        }
+        if let Some(max) = &asset_info.max_amount
+            && amount > max
+        {
+            return Err(Sep6Error::InvalidRequest(format!(
+                "amount is above the maximum of {} {}",
+                max, code
+            )));
+        }
+
+        let network_fee = network_fee();
+        let mut line_items = vec![EstimateLineItem {
+            name: "Network fee".to_string(),
+            description: Some("Stellar base fee, paid in XLM on top of the amount".to_string()),
+            amount: network_fee.clone(),
+            asset: "native".to_string(),
+            deducted: false,
+        }];
+
+        let estimate = match destination_asset {
+            Some(destination) => {
+                let issuer = issuer.ok_or_else(|| {
+                    Sep6Error::InvalidRequest(
+                        "asset_issuer is required to price an exchange withdrawal".to_string(),
+                    )
+                })?;
+                let sell_asset = format!("stellar:{}:{}", code, issuer);
+
+                let price = get_exchange_prices(
+                    slug.to_string(),
+                    sell_asset.clone(),
+                    destination.to_string(),
+                    Some(amount.clone()),
+                    None,
+                    None,
+                    buy_delivery_method.map(str::to_string),
+                    country_code.map(str::to_string),
+                    "sep6".to_string(),
+                )
+                .await?;
+
+                match &price.fee.details {
+                    Some(details) if !details.is_empty() => {
+                        line_items.extend(details.iter().map(|d| EstimateLineItem {
+                            name: d.name.clone(),
+                            description: d.description.clone(),
+                            amount: d.amount.clone(),
+                            asset: price.fee.asset_string.clone(),
+                            deducted: true,
+                        }))
+                    }
+                    _ => line_items.push(EstimateLineItem {
+                        name: "Anchor fee".to_string(),
+                        description: None,
+                        amount: price.fee.total.clone(),
+                        asset: price.fee.asset_string.clone(),
+                        deducted: true,
+                    }),
+                }
+
+                WithdrawEstimate {
+                    send_amount: amount.clone(),
+                    send_asset: sell_asset,
+                    anchor_fee: price.fee.total.clone(),
+                    anchor_fee_asset: price.fee.asset_string.clone(),
+                    fee_source: FEE_SOURCE_QUOTE.to_string(),
+                    price: Some(price.price.clone()),
+                    network_fee,
+                    network_fee_asset: "native".to_string(),
+                    receive_amount: Money::new(price.buy_amount.clone(), destination).amount,
+                    receive_asset: destination.to_string(),
+                    line_items,
+                }
+            }
+            None => {
+                let (fee, fee_source) =
+                    resolve_fee(&info, slug, account, "withdraw", funding_method, code, amount)
+                        .await?;
+                let (fee, receive_amount) = withdrawal_payout(amount, fee, code)?;
+
+                line_items.push(EstimateLineItem {
+                    name: "Anchor fee".to_string(),
+                    description: None,
+                    amount: fee.clone(),
+                    asset: code.to_string(),
+                    deducted: true,
+                });
+
+                WithdrawEstimate {
+                    send_amount: amount.clone(),
+                    send_asset: code.to_string(),
+                    anchor_fee: fee,
+                    anchor_fee_asset: code.to_string(),
+                    fee_source: fee_source.to_string(),
+                    price: None,
+                    network_fee,
+                    network_fee_asset: "native".to_string(),
+                    receive_amount,
+                    receive_asset: code.to_string(),
+                    line_items,
+                }
+            }
+        };
+
+        Ok(estimate)
+    }
+
+    // Required fields first, then by name
+    fn form_fields(fields: &HashMap<String, FieldSpec>) -> Vec<FormField> {
+        let mut form: Vec<FormField> = fields
+            .iter()
+            .map(|(name, spec)| FormField {
+                name: name.clone(),
+                description: spec.description.clone(),
+                optional: spec.optional,
+                choices: spec.choices.clone(),
+            })
+            .collect();
+        form.sort_by(|a, b| a.optional.cmp(&b.optional).then(a.name.cmp(&b.name)));
+        form
+    }
+
+    // Funding methods are the union of `funding_methods` and the legacy `types` keys; each
+    // gets the fields its type declares
+    fn form_schemas(
+        asset_code: &str,
+        kind: &str,
+        funding_methods: &[String],
+        types: &HashMap<String, WithdrawTypeInfo>,
+        authentication_required: Option<bool>,
+        min_amount: Option<&Amount>,
+        max_amount: Option<&Amount>,
+    ) -> Vec<WithdrawFormSchema> {
+        let methods: BTreeSet<&String> = funding_methods.iter().chain(types.keys()).collect();
+
+        methods
+            .into_iter()
+            .map(|method| WithdrawFormSchema {
+                asset_code: asset_code.to_string(),
+                kind: kind.to_string(),
+                funding_method: method.clone(),
+                authentication_required,
+                min_amount: min_amount.cloned(),
+                max_amount: max_amount.cloned(),
+                fields: types
+                    .get(method)
+                    .map(|t| form_fields(&t.fields))
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Derives the withdrawal forms for an anchor from its /info, optionally narrowed to one
+    /// asset and funding method. Disabled assets are left out.
+    pub async fn get_withdraw_form_schema(
+        slug: &str,
+        asset_code: Option<&str>,
+        funding_method: Option<&str>,
+    ) -> Result<Vec<WithdrawFormSchema>, Sep6Error> {
+        let info = get_anchor_info(slug).await?;
+        let wanted = |code: &String| asset_code.is_none_or(|c| c == code);
+
+        let mut schemas = Vec::new();
+        for (code, asset) in info.withdraw.iter().filter(|(c, a)| a.enabled && wanted(c)) {
+            schemas.extend(form_schemas(
+                code,
+                "withdraw",
+                asset.funding_methods.as_deref().unwrap_or_default(),
+                &asset.types,
+                asset.authentication_required,
+                asset.min_amount.as_ref(),
+                asset.max_amount.as_ref(),
+            ));
+        }
+        for (code, asset) in info
+            .withdraw_exchange
+            .iter()
+            .filter(|(c, a)| a.enabled && wanted(c))
+        {
+            schemas.extend(form_schemas(
+                code,
+                "withdraw-exchange",
+                &asset.funding_methods,
+                &asset.types,
+                asset.authentication_required,
+                None,
+                None,
+            ));
+        }
+
+        schemas.retain(|s| funding_method.is_none_or(|m| m == s.funding_method));
+        schemas.sort_by(|a, b| {
+            (&a.asset_code, &a.kind, &a.funding_method).cmp(&(
+                &b.asset_code,
+                &b.kind,
+                &b.funding_method,
+            ))
+        });
+
+        Ok(schemas)
+    }
+
+    pub const PENDING_TRANSACTION_INFO_UPDATE: &str = "pending_transaction_info_update";
+
+    fn required_info_fields(updates: Option<&RequiredInfoUpdates>) -> Vec<FormField> {
+        updates.map(|u| form_fields(&u.0)).unwrap_or_default()
+    }
+
+    /// Transactions of `account` waiting on the user to correct information for the anchor.
+    pub fn list_pending_info_updates(account: &str) -> Result<Vec<PendingInfoUpdate>, Sep6Error> {
+        let public_account = public_account_id(account).map_err(|_| Sep6Error::AuthFailed)?;
+        let mut conn =
+            establish_connection().map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+
+        let transactions: Vec<Sep6Transaction> = sep6_transactions::table
+            .filter(sep6_transactions::account.eq(&public_account))
+            .filter(sep6_transactions::status.eq(PENDING_TRANSACTION_INFO_UPDATE))
+            .order(sep6_transactions::user_action_required_by.asc().nulls_last())
+            .select(Sep6Transaction::as_select())
+            .load(&mut conn)
+            .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+
+        Ok(transactions
+            .into_iter()
+            .map(|tx| PendingInfoUpdate {
+                fields: required_info_fields(tx.required_info_updates.as_ref()),
+                transaction_id: tx.transaction_id,
+                kind: tx.kind,
+                message: tx.required_info_message,
+                user_action_required_by: tx.user_action_required_by,
+            })
+            .collect())
+    }
+
+    // 8. PATCH /transactions/:id
+    pub async fn update_transaction_info(
+        slug: &str,
+        account: &str,
+        transaction_id: &str,
+        fields: &HashMap<String, String>,
+    ) -> Result<Sep6Transaction, Sep6Error> {
+        let keypair = generate_keypair(account).map_err(|_| Sep6Error::AuthFailed)?;
+        let mut conn =
+            establish_connection().map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+
+        let tx: Sep6Transaction = sep6_transactions::table
+            .filter(sep6_transactions::transaction_id.eq(transaction_id))
+            .filter(sep6_transactions::account.eq(keypair.public_key().account_id()))
+            .select(Sep6Transaction::as_select())
+            .first(&mut conn)
+            .optional()
+            .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?
+            .ok_or(Sep6Error::TransactionNotFound)?;
+
+        if tx.status != PENDING_TRANSACTION_INFO_UPDATE {
+            return Err(Sep6Error::InvalidRequest(format!(
+                "transaction is {}, not {}",
+                tx.status, PENDING_TRANSACTION_INFO_UPDATE
+            )));
+        }
+
+        let missing: Vec<String> = required_info_fields(tx.required_info_updates.as_ref())
+            .into_iter()
+            .filter(|f| !f.optional && fields.get(&f.name).is_none_or(|v| v.trim().is_empty()))
+            .map(|f| f.name)
+            .collect();
+        if !missing.is_empty() {
+            return Err(Sep6Error::InvalidRequest(format!(
+                "missing required fields: {}",
+                missing.join(", ")
+            )));
+        }
+
+        let anchor_config =
+            get_anchor_config_details(&helpers::stellartoml::AnchorService::new(), slug)
+                .await
+                .map_err(|_| Sep6Error::AuthFailed)?;
+        let transfer_server = anchor_config
+            .general_info
+            .transfer_server
+            .unwrap_or_default();
+        let jwt = authenticate(&helpers::stellartoml::AnchorService::new(), slug, &keypair)
+            .await
+            .map_err(|_| Sep6Error::AuthFailed)?;
+
+        let response = Client::new()
+            .patch(format!("{}/transactions/{}", transfer_server, transaction_id))
+            .bearer_auth(jwt)
+            .json(&serde_json::json!({ "transaction": fields }))
+            .send()
+            .await?;
+
+        if response.status() == 404 {
+            return Err(Sep6Error::TransactionNotFound);
+        }
+        if !response.status().is_success() {
+            return Err(Sep6Error::InvalidRequest(format!(
+                "Status: {}",
+                response.status()
+            )));
+        }
+
+        // Store the status the anchor reports now instead of guessing where it moved the
+        // transaction; `save_transaction` publishes the change
+        let refreshed = get_transaction(slug, account, Some(transaction_id), None, None).await?;
+
+        // The upsert skips absent fields, so clear the request once the anchor has moved on
+        if refreshed.status != PENDING_TRANSACTION_INFO_UPDATE {
+            diesel::update(sep6_transactions::table.find(tx.id))
+                .set((
+                    sep6_transactions::required_info_message.eq(None::<String>),
+                    sep6_transactions::required_info_updates.eq(None::<RequiredInfoUpdates>),
+                ))
+                .execute(&mut conn)
+                .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+        }
+
+        sep6_transactions::table
+            .find(tx.id)
+            .select(Sep6Transaction::as_select())
+            .first(&mut conn)
+            .map_err(|e| Sep6Error::DatabaseError(e.to_string()))
+    }
+
+    /// Refunds recorded for `account`'s transactions, newest first, optionally for a single
+    /// transaction.
+    pub fn list_refunds(
+        account: &str,
+        transaction_id: Option<&str>,
+    ) -> Result<Vec<TransactionRefunds>, Sep6Error> {
+        let public_account = public_account_id(account).map_err(|_| Sep6Error::AuthFailed)?;
+        let mut conn =
+            establish_connection().map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+
+        let mut query = sep6_refunds::table
+            .inner_join(sep6_transactions::table)
+            .filter(sep6_transactions::account.eq(public_account))
+            .into_boxed();
+        if let Some(id) = transaction_id {
+            query = query.filter(sep6_transactions::transaction_id.eq(id));
+        }
+
+        let refunds: Vec<(String, Sep6Refund)> = query
+            .order(sep6_refunds::created_at.desc())
+            .select((sep6_transactions::transaction_id, sep6_refunds::all_columns))
+            .load(&mut conn)
+            .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+
+        let refund_ids: Vec<Uuid> = refunds.iter().map(|(_, r)| r.id).collect();
+        let payments: Vec<Sep6RefundPayment> = sep6_refund_payments::table
+            .filter(sep6_refund_payments::refund_id.eq_any(&refund_ids))
+            .order(sep6_refund_payments::created_at.asc())
+            .load(&mut conn)
+            .map_err(|e| Sep6Error::DatabaseError(e.to_string()))?;
+
+        let mut by_refund: HashMap<Uuid, Vec<Sep6RefundPayment>> = HashMap::new();
+        for payment in payments {
+            by_refund.entry(payment.refund_id).or_default().push(payment);
+        }
+
+        Ok(refunds
+            .into_iter()
+            .map(|(transaction_id, refund)| TransactionRefunds {
+                transaction_id,
+                payments: by_refund.remove(&refund.id).unwrap_or_default(),
+                amount_refunded: refund.amount_refunded,
+                amount_fee: refund.amount_fee,
+            })
+            .collect())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use helpers::horizon::{
+            AccountResponse, HorizonError, MockHorizonClient, SubmitTransactionResponse,
+        };
+
+        const USDC_ISSUER: &str = "GA5ZSEJYB37JRC5AVCIA5MOP4RHTM335X2KGX3IHOJAPP5RE34K4KZVN";
+
+        fn account_with(balances: serde_json::Value) -> AccountResponse {
+            serde_json::from_value(serde_json::json!({
+                "id": "GACCOUNT",
+                "sequence": "41",
+                "balances": balances,
+                "signers": [],
+                "thresholds": {"low_threshold": 0, "med_threshold": 0, "high_threshold": 0},
+            }))
+            .unwrap()
+        }
+
+        fn horizon_with(balances: serde_json::Value) -> MockHorizonClient {
+            let account = account_with(balances);
+            let mut horizon = MockHorizonClient::new();
+            horizon
+                .expect_load_account()
+                .returning(move |_| Ok(account.clone()));
+            horizon
+        }
+
+        fn usdc_balance(balance: &str) -> serde_json::Value {
+            serde_json::json!([
+                {"balance": "100.0000000", "asset_type": "native"},
+                {
+                    "balance": balance,
+                    "asset_type": "credit_alphanum4",
+                    "asset_code": "USDC",
+                    "asset_issuer": USDC_ISSUER,
+                    "limit": "922337203685.4775807",
+                },
+            ])
+        }
+
+        fn amount(value: &str) -> Amount {
+            Amount::from_str(value).unwrap()
+        }
+
+        #[tokio::test]
+        async fn balance_check_passes_when_covered() {
+            let horizon = horizon_with(usdc_balance("25.0000000"));
+            let result = check_withdraw_balance(
+                &horizon,
+                "GACCOUNT",
+                "USDC",
+                Some(USDC_ISSUER),
+                &amount("25"),
+            )
+            .await;
+            assert!(result.is_ok());
+        }
+
+        #[tokio::test]
+        async fn balance_check_rejects_shortfall() {
+            let horizon = horizon_with(usdc_balance("24.9999999"));
+            let result = check_withdraw_balance(
+                &horizon,
+                "GACCOUNT",
+                "USDC",
+                Some(USDC_ISSUER),
+                &amount("25"),
+            )
+            .await;
+            assert!(matches!(result, Err(Sep6Error::InsufficientBalance(_))));
+        }
+
+        #[tokio::test]
+        async fn balance_check_requires_trustline() {
+            let horizon = horizon_with(serde_json::json!([
+                {"balance": "100.0000000", "asset_type": "native"},
+            ]));
+            let result = check_withdraw_balance(
+                &horizon,
+                "GACCOUNT",
+                "USDC",
+                Some(USDC_ISSUER),
+                &amount("1"),
+            )
+            .await;
+            assert!(
+                matches!(result, Err(Sep6Error::InsufficientBalance(message)) if message.contains("no trustline"))
+            );
+        }
+
+        #[tokio::test]
+        async fn balance_check_matches_issuer() {
+            let horizon = horizon_with(usdc_balance("50"));
+            let other_issuer = KeyPair::random().unwrap().public_key().account_id();
+            let result = check_withdraw_balance(
+                &horizon,
+                "GACCOUNT",
+                "USDC",
+                Some(&other_issuer),
+                &amount("1"),
+            )
+            .await;
+            assert!(matches!(result, Err(Sep6Error::InsufficientBalance(_))));
+        }
+
+        #[tokio::test]
+        async fn balance_check_supports_native() {
+            let horizon = horizon_with(usdc_balance("0"));
+            let result =
+                check_withdraw_balance(&horizon, "GACCOUNT", "native", None, &amount("99.5")).await;
+            assert!(result.is_ok());
+        }
+
+        #[tokio::test]
+        async fn balance_check_rejects_excess_precision_without_loading() {
+            let mut horizon = MockHorizonClient::new();
+            horizon.expect_load_account().times(0);
+            let result = check_withdraw_balance(
+                &horizon,
+                "GACCOUNT",
+                "USDC",
+                Some(USDC_ISSUER),
+                &amount("1.00000001"),
+            )
+            .await;
+            assert!(matches!(result, Err(Sep6Error::InvalidRequest(_))));
+        }
+
+        fn details(destination: &str) -> PaymentDetails<'_> {
+            PaymentDetails {
+                destination,
+                asset_code: "USDC",
+                asset_issuer: Some(USDC_ISSUER),
+                amount: "10.5",
+                memo_type: Some("text"),
+                memo: Some("withdraw-1"),
+            }
+        }
+
+        #[test]
+        fn fee_from_rates_combines_fixed_and_percent() {
+            assert_eq!(fee_from_rates(None, None, &amount("100")), None);
+            assert_eq!(
+                fee_from_rates(Some(&amount("1")), None, &amount("100")),
+                Some(amount("1"))
+            );
+            assert_eq!(
+                fee_from_rates(None, Some(&amount("0.5")), &amount("100")),
+                Some(amount("0.5"))
+            );
+            assert_eq!(
+                fee_from_rates(Some(&amount("1")), Some(&amount("0.5")), &amount("250")),
+                Some(amount("2.25"))
+            );
+        }
+
+        #[test]
+        fn payout_deducts_only_the_anchor_fee() {
+            let fee = fee_from_rates(Some(&amount("1")), Some(&amount("0.5")), &amount("100"));
+            let (fee, receive) = withdrawal_payout(&amount("100"), fee.unwrap(), "USDC").unwrap();
+            assert_eq!(fee, amount("1.5"));
+            assert_eq!(receive, amount("98.5"));
+
+            // 100 stroops, settled in XLM separately from the withdrawn asset
+            assert_eq!(network_fee(), amount("0.00001"));
+            assert_eq!(&receive + &fee, amount("100"));
+        }
+
+        #[test]
+        fn payout_rounds_to_the_asset_precision() {
+            let fee = fee_from_rates(None, Some(&amount("0.333")), &amount("10"));
+            let (fee, receive) = withdrawal_payout(&amount("10"), fee.unwrap(), "USDC").unwrap();
+            assert_eq!(fee, amount("0.0333"));
+            assert_eq!(receive, amount("9.9667"));
+
+            let fee = fee_from_rates(None, Some(&amount("1")), &amount("0.00000015"));
+            let (fee, _) = withdrawal_payout(&amount("1"), fee.unwrap(), "USDC").unwrap();
+            assert_eq!(fee, amount("0"));
+        }
+
+        #[test]
+        fn payout_rejects_fees_that_eat_the_amount() {
+            assert!(matches!(
+                withdrawal_payout(&amount("5"), amount("5"), "USDC"),
+                Err(Sep6Error::InvalidRequest(_))
+            ));
+            assert!(matches!(
+                withdrawal_payout(&amount("5"), amount("6"), "USDC"),
+                Err(Sep6Error::InvalidRequest(_))
+            ));
+        }
+
+        #[tokio::test]
+        async fn pay_withdrawal_submits_and_returns_hash() {
+            let keypair = KeyPair::random().unwrap();
+            let destination = KeyPair::random().unwrap().public_key().account_id();
+            let mut horizon = MockHorizonClient::new();
+            horizon
+                .expect_fetch_account_sequence()
+                .times(1)
+                .returning(|_| Ok(41));
+            horizon.expect_submit_transaction().times(1).returning(|_| {
+                Ok(SubmitTransactionResponse {
+                    hash: "abc123".to_string(),
+                    ledger: Some(7),
+                    successful: Some(true),
+                })
+            });
+
+            let hash = pay_withdrawal(
+                &horizon,
+                &keypair,
+                &Network::new_test(),
+                &details(&destination),
+                None,
+            )
+            .await
+            .unwrap();
+            assert_eq!(hash, "abc123");
+        }
+
+        #[tokio::test]
+        async fn pay_withdrawal_refuses_to_pay_twice() {
+            let keypair = KeyPair::random().unwrap();
+            let destination = KeyPair::random().unwrap().public_key().account_id();
+            let mut horizon = MockHorizonClient::new();
+            horizon.expect_fetch_account_sequence().times(0);
+            horizon.expect_submit_transaction().times(0);
+
+            let result = pay_withdrawal(
+                &horizon,
+                &keypair,
+                &Network::new_test(),
+                &details(&destination),
+                Some("abc123"),
+            )
+            .await;
+            assert!(
+                matches!(result, Err(Sep6Error::PaymentAlreadySubmitted(hash)) if hash == "abc123")
+            );
+        }
+
+        #[tokio::test]
+        async fn pay_withdrawal_surfaces_submission_failure() {
+            let keypair = KeyPair::random().unwrap();
+            let destination = KeyPair::random().unwrap().public_key().account_id();
+            let mut horizon = MockHorizonClient::new();
+            horizon
+                .expect_fetch_account_sequence()
+                .returning(|_| Ok(41));
+            horizon
+                .expect_submit_transaction()
+                .times(1)
+                .returning(|_| Err(HorizonError::TransactionFailed("tx_bad_seq".to_string())));
+
+            let result = pay_withdrawal(
+                &horizon,
+                &keypair,
+                &Network::new_test(),
+                &details(&destination),
+                None,
+            )
+            .await;
+            assert!(
+                matches!(result, Err(Sep6Error::PaymentFailed(message)) if message.contains("tx_bad_seq"))
+            );
+        }
+    }
 }
@@ -0,0 +1,499 @@
+// SEP9
+// Standard KYC fields:
+// Natural person
+// Organization (organization.*)
+// Financial account
+// Per-field validation
+pub mod sep9 {
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+    use std::net::IpAddr;
+    use thiserror::Error;
+
+    #[derive(Error, Debug)]
+    pub enum Sep9Error {
+        #[error("Invalid value for {0}: {1}")]
+        InvalidField(String, String),
+
+        #[error("Serialization error: {0}")]
+        SerializationError(#[from] serde_json::Error),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum FieldKind {
+        Text,
+        Email,
+        Phone,
+        Date,
+        CountryCode,
+        LanguageCode,
+        Integer,
+        IpAddress,
+        Url,
+        Choice(&'static [&'static str]),
+    }
+
+    /// Every standard SEP-9 text field and how its value is validated.
+    pub const STANDARD_FIELDS: &[(&str, FieldKind)] = &[
+        // Natural person
+        ("last_name", FieldKind::Text),
+        ("first_name", FieldKind::Text),
+        ("additional_name", FieldKind::Text),
+        ("address_country_code", FieldKind::CountryCode),
+        ("state_or_province", FieldKind::Text),
+        ("city", FieldKind::Text),
+        ("postal_code", FieldKind::Text),
+        ("address", FieldKind::Text),
+        ("mobile_number", FieldKind::Phone),
+        (
+            "mobile_number_format",
+            FieldKind::Choice(&["E.164", "hash"]),
+        ),
+        ("email_address", FieldKind::Email),
+        ("birth_date", FieldKind::Date),
+        ("birth_place", FieldKind::Text),
+        ("birth_country_code", FieldKind::CountryCode),
+        ("tax_id", FieldKind::Text),
+        ("tax_id_name", FieldKind::Text),
+        ("occupation", FieldKind::Text),
+        ("employer_name", FieldKind::Text),
+        ("employer_address", FieldKind::Text),
+        ("language_code", FieldKind::LanguageCode),
+        ("id_type", FieldKind::Text),
+        ("id_country_code", FieldKind::CountryCode),
+        ("id_issue_date", FieldKind::Date),
+        ("id_expiration_date", FieldKind::Date),
+        ("id_number", FieldKind::Text),
+        ("ip_address", FieldKind::IpAddress),
+        ("sex", FieldKind::Choice(&["male", "female", "other"])),
+        ("referral_id", FieldKind::Text),
+        // Financial account
+        ("bank_name", FieldKind::Text),
+        (
+            "bank_account_type",
+            FieldKind::Choice(&["checking", "savings"]),
+        ),
+        ("bank_account_number", FieldKind::Text),
+        ("bank_number", FieldKind::Text),
+        ("bank_phone_number", FieldKind::Phone),
+        ("bank_branch_number", FieldKind::Text),
+        ("external_transfer_memo", FieldKind::Text),
+        ("clabe_number", FieldKind::Text),
+        ("cbu_number", FieldKind::Text),
+        ("cbu_alias", FieldKind::Text),
+        ("mobile_money_number", FieldKind::Phone),
+        ("mobile_money_provider", FieldKind::Text),
+        ("crypto_address", FieldKind::Text),
+        ("crypto_memo", FieldKind::Text),
+        // Organization
+        ("organization.name", FieldKind::Text),
+        ("organization.VAT_number", FieldKind::Text),
+        ("organization.registration_number", FieldKind::Text),
+        ("organization.registration_date", FieldKind::Date),
+        ("organization.registered_address", FieldKind::Text),
+        ("organization.number_of_shareholders", FieldKind::Integer),
+        ("organization.shareholder_name", FieldKind::Text),
+        ("organization.address_country_code", FieldKind::CountryCode),
+        ("organization.state_or_province", FieldKind::Text),
+        ("organization.city", FieldKind::Text),
+        ("organization.postal_code", FieldKind::Text),
+        ("organization.director_name", FieldKind::Text),
+        ("organization.website", FieldKind::Url),
+        ("organization.email", FieldKind::Email),
+        ("organization.phone", FieldKind::Phone),
+    ];
+
+    /// Names clients have historically sent instead of the SEP-9 ones.
+    const ALIASES: &[(&str, &str)] = &[
+        ("email", "email_address"),
+        ("phone_number", "mobile_number"),
+    ];
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct NaturalPersonFields {
+        pub last_name: Option<String>,
+        pub first_name: Option<String>,
+        pub additional_name: Option<String>,
+        pub address_country_code: Option<String>,
+        pub state_or_province: Option<String>,
+        pub city: Option<String>,
+        pub postal_code: Option<String>,
+        pub address: Option<String>,
+        pub mobile_number: Option<String>,
+        pub mobile_number_format: Option<String>,
+        pub email_address: Option<String>,
+        pub birth_date: Option<String>,
+        pub birth_place: Option<String>,
+        pub birth_country_code: Option<String>,
+        pub tax_id: Option<String>,
+        pub tax_id_name: Option<String>,
+        pub occupation: Option<String>,
+        pub employer_name: Option<String>,
+        pub employer_address: Option<String>,
+        pub language_code: Option<String>,
+        pub id_type: Option<String>,
+        pub id_country_code: Option<String>,
+        pub id_issue_date: Option<String>,
+        pub id_expiration_date: Option<String>,
+        pub id_number: Option<String>,
+        pub ip_address: Option<String>,
+        pub sex: Option<String>,
+        pub referral_id: Option<String>,
+    }
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct OrganizationFields {
+        #[serde(rename = "organization.name")]
+        pub name: Option<String>,
+        #[serde(rename = "organization.VAT_number")]
+        pub vat_number: Option<String>,
+        #[serde(rename = "organization.registration_number")]
+        pub registration_number: Option<String>,
+        #[serde(rename = "organization.registration_date")]
+        pub registration_date: Option<String>,
+        #[serde(rename = "organization.registered_address")]
+        pub registered_address: Option<String>,
+        #[serde(rename = "organization.number_of_shareholders")]
+        pub number_of_shareholders: Option<String>,
+        #[serde(rename = "organization.shareholder_name")]
+        pub shareholder_name: Option<String>,
+        #[serde(rename = "organization.address_country_code")]
+        pub address_country_code: Option<String>,
+        #[serde(rename = "organization.state_or_province")]
+        pub state_or_province: Option<String>,
+        #[serde(rename = "organization.city")]
+        pub city: Option<String>,
+        #[serde(rename = "organization.postal_code")]
+        pub postal_code: Option<String>,
+        #[serde(rename = "organization.director_name")]
+        pub director_name: Option<String>,
+        #[serde(rename = "organization.website")]
+        pub website: Option<String>,
+        #[serde(rename = "organization.email")]
+        pub email: Option<String>,
+        #[serde(rename = "organization.phone")]
+        pub phone: Option<String>,
+    }
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct FinancialAccountFields {
+        pub bank_name: Option<String>,
+        pub bank_account_type: Option<String>,
+        pub bank_account_number: Option<String>,
+        pub bank_number: Option<String>,
+        pub bank_phone_number: Option<String>,
+        pub bank_branch_number: Option<String>,
+        pub external_transfer_memo: Option<String>,
+        pub clabe_number: Option<String>,
+        pub cbu_number: Option<String>,
+        pub cbu_alias: Option<String>,
+        pub mobile_money_number: Option<String>,
+        pub mobile_money_provider: Option<String>,
+        pub crypto_address: Option<String>,
+        pub crypto_memo: Option<String>,
+    }
+
+    /// A customer's SEP-9 fields, grouped by category.
+    ///
+    /// Serializes to a flat object keyed by the SEP-9 field names. Fields outside the
+    /// standard (anchor-specific extensions) are kept untouched in `other`.
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    pub struct Sep9Fields {
+        #[serde(flatten)]
+        pub natural_person: NaturalPersonFields,
+        #[serde(flatten)]
+        pub organization: OrganizationFields,
+        #[serde(flatten)]
+        pub financial_account: FinancialAccountFields,
+        #[serde(flatten)]
+        pub other: BTreeMap<String, String>,
+    }
+
+    pub fn field_kind(name: &str) -> Option<FieldKind> {
+        STANDARD_FIELDS
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, kind)| *kind)
+    }
+
+    fn canonical_name(name: &str) -> &str {
+        ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == name)
+            .map_or(name, |(_, canonical)| canonical)
+    }
+
+    fn is_phone(value: &str) -> bool {
+        // E.164: a plus sign followed by up to 15 digits, no leading zero
+        let digits = value.strip_prefix('+').unwrap_or_default();
+        (2..=15).contains(&digits.len())
+            && digits.bytes().all(|b| b.is_ascii_digit())
+            && !digits.starts_with('0')
+    }
+
+    fn is_email(value: &str) -> bool {
+        match value.split_once('@') {
+            Some((local, domain)) => {
+                !local.is_empty()
+                    && !domain.contains('@')
+                    && domain.contains('.')
+                    && !domain.starts_with('.')
+                    && !domain.ends_with('.')
+                    && !value.contains(char::is_whitespace)
+            }
+            None => false,
+        }
+    }
+
+    fn is_sha256_hex(value: &str) -> bool {
+        value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit())
+    }
+
+    pub fn validate_field(name: &str, value: &str) -> Result<(), Sep9Error> {
+        let Some(kind) = field_kind(name) else {
+            return Ok(());
+        };
+
+        let invalid = |reason: &str| {
+            Err(Sep9Error::InvalidField(
+                name.to_string(),
+                reason.to_string(),
+            ))
+        };
+
+        match kind {
+            FieldKind::Text if value.trim().is_empty() => invalid("must not be empty"),
+            FieldKind::Text => Ok(()),
+            FieldKind::Email if !is_email(value) => invalid("expected an email address"),
+            // A hashed mobile number is allowed when mobile_number_format says so
+            FieldKind::Phone if !(is_phone(value) || is_sha256_hex(value)) => {
+                invalid("expected an E.164 phone number")
+            }
+            FieldKind::Date if NaiveDate::parse_from_str(value, "%Y-%m-%d").is_err() => {
+                invalid("expected an ISO 8601 date (YYYY-MM-DD)")
+            }
+            FieldKind::CountryCode
+                if value.len() != 3 || !value.bytes().all(|b| b.is_ascii_uppercase()) =>
+            {
+                invalid("expected an ISO 3166-1 alpha-3 country code")
+            }
+            FieldKind::LanguageCode
+                if value.len() != 2 || !value.bytes().all(|b| b.is_ascii_lowercase()) =>
+            {
+                invalid("expected an ISO 639-1 language code")
+            }
+            FieldKind::Integer if value.parse::<u64>().is_err() => {
+                invalid("expected a whole number")
+            }
+            FieldKind::IpAddress if value.parse::<IpAddr>().is_err() => {
+                invalid("expected an IP address")
+            }
+            FieldKind::Url if url::Url::parse(value).is_err() => invalid("expected a URL"),
+            FieldKind::Choice(choices) if !choices.contains(&value) => {
+                invalid(&format!("expected one of {}", choices.join(", ")))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    impl Sep9Fields {
+        /// Validates and groups submitted `(name, value)` pairs.
+        pub fn from_pairs(pairs: &[(String, String)]) -> Result<Self, Sep9Error> {
+            let mut fields = Self::default();
+            fields.merge(pairs)?;
+            Ok(fields)
+        }
+
+        /// Applies submitted values on top of the stored ones; later values win.
+        pub fn merge(&mut self, pairs: &[(String, String)]) -> Result<(), Sep9Error> {
+            let mut values = self.to_map()?;
+
+            for (name, value) in pairs {
+                let name = canonical_name(name);
+                let value = value.trim();
+                validate_field(name, value)?;
+                values.insert(name.to_string(), value.to_string());
+            }
+
+            *self = serde_json::from_value(serde_json::to_value(values)?)?;
+            Ok(())
+        }
+
+        /// Looks a field up by its SEP-9 name, e.g. `organization.name` or `bank_number`.
+        pub fn get(&self, name: &str) -> Option<String> {
+            self.to_map().ok()?.remove(canonical_name(name))
+        }
+
+        pub fn to_map(&self) -> Result<BTreeMap<String, String>, Sep9Error> {
+            let value = serde_json::to_value(self)?;
+            Ok(value
+                .as_object()
+                .map(|object| {
+                    object
+                        .iter()
+                        .filter_map(|(k, v)| v.as_str().map(|s| (k.clone(), s.to_string())))
+                        .collect()
+                })
+                .unwrap_or_default())
+        }
+
+        pub fn to_json(&self) -> Result<String, Sep9Error> {
+            Ok(serde_json::to_string(&self.to_map()?)?)
+        }
+
+        pub fn from_json(json: &str) -> Result<Self, Sep9Error> {
+            Ok(serde_json::from_str(json)?)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn pairs(values: &[(&str, &str)]) -> Vec<(String, String)> {
+            values
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        }
+
+        fn rejects(name: &str, value: &str) -> bool {
+            matches!(validate_field(name, value), Err(Sep9Error::InvalidField(field, _)) if field == name)
+        }
+
+        #[test]
+        fn validates_emails() {
+            assert!(validate_field("email_address", "jane@example.com").is_ok());
+            assert!(rejects("email_address", "jane.example.com"));
+            assert!(rejects("email_address", "@example.com"));
+            assert!(rejects("email_address", "jane@example"));
+            assert!(rejects("email_address", "jane@.example.com"));
+            assert!(rejects("email_address", "jane@@example.com"));
+            assert!(rejects("email_address", "jane doe@example.com"));
+        }
+
+        #[test]
+        fn validates_phone_numbers_or_their_hash() {
+            assert!(validate_field("mobile_number", "+14155550100").is_ok());
+            assert!(validate_field("mobile_number", &"a".repeat(64)).is_ok());
+            assert!(rejects("mobile_number", "14155550100"));
+            assert!(rejects("mobile_number", "+04155550100"));
+            assert!(rejects("mobile_number", "+1415555010012345"));
+            assert!(rejects("mobile_number", "+1 415 555 0100"));
+        }
+
+        #[test]
+        fn validates_dates_and_codes() {
+            assert!(validate_field("birth_date", "1990-02-28").is_ok());
+            assert!(rejects("birth_date", "1990-02-30"));
+            assert!(rejects("birth_date", "28/02/1990"));
+            assert!(validate_field("address_country_code", "NLD").is_ok());
+            assert!(rejects("address_country_code", "NL"));
+            assert!(rejects("address_country_code", "nld"));
+            assert!(validate_field("language_code", "en").is_ok());
+            assert!(rejects("language_code", "EN"));
+        }
+
+        #[test]
+        fn validates_numbers_addresses_urls_and_choices() {
+            assert!(validate_field("organization.number_of_shareholders", "3").is_ok());
+            assert!(rejects("organization.number_of_shareholders", "-3"));
+            assert!(validate_field("ip_address", "2001:db8::1").is_ok());
+            assert!(rejects("ip_address", "300.1.1.1"));
+            assert!(validate_field("organization.website", "https://example.com").is_ok());
+            assert!(rejects("organization.website", "example"));
+            assert!(validate_field("mobile_number_format", "hash").is_ok());
+            assert!(rejects("mobile_number_format", "national"));
+        }
+
+        #[test]
+        fn text_must_not_be_blank() {
+            assert!(validate_field("first_name", "Jane").is_ok());
+            assert!(rejects("first_name", "   "));
+        }
+
+        #[test]
+        fn unknown_fields_are_not_validated() {
+            assert!(field_kind("anchor_specific").is_none());
+            assert!(validate_field("anchor_specific", "").is_ok());
+        }
+
+        #[test]
+        fn groups_fields_and_keeps_extensions() {
+            let fields = Sep9Fields::from_pairs(&pairs(&[
+                ("first_name", "Jane"),
+                ("organization.name", "Acme"),
+                ("bank_number", "123"),
+                ("anchor_specific", "x"),
+            ]))
+            .unwrap();
+
+            assert_eq!(fields.natural_person.first_name.as_deref(), Some("Jane"));
+            assert_eq!(fields.organization.name.as_deref(), Some("Acme"));
+            assert_eq!(fields.financial_account.bank_number.as_deref(), Some("123"));
+            assert_eq!(
+                fields.other.get("anchor_specific").map(String::as_str),
+                Some("x")
+            );
+        }
+
+        #[test]
+        fn aliases_map_to_canonical_names() {
+            let fields = Sep9Fields::from_pairs(&pairs(&[
+                ("email", "jane@example.com"),
+                ("phone_number", "+14155550100"),
+            ]))
+            .unwrap();
+
+            assert_eq!(
+                fields.natural_person.email_address.as_deref(),
+                Some("jane@example.com")
+            );
+            assert_eq!(fields.get("email").as_deref(), Some("jane@example.com"));
+            assert_eq!(fields.get("mobile_number").as_deref(), Some("+14155550100"));
+            assert!(!fields.to_map().unwrap().contains_key("email"));
+        }
+
+        #[test]
+        fn merge_trims_overrides_and_keeps_the_rest() {
+            let mut fields =
+                Sep9Fields::from_pairs(&pairs(&[("first_name", "Jane"), ("city", "Utrecht")]))
+                    .unwrap();
+            fields.merge(&pairs(&[("city", "  Amsterdam ")])).unwrap();
+
+            assert_eq!(fields.get("first_name").as_deref(), Some("Jane"));
+            assert_eq!(fields.get("city").as_deref(), Some("Amsterdam"));
+        }
+
+        #[test]
+        fn merge_rejects_invalid_values_without_applying_them() {
+            let mut fields = Sep9Fields::from_pairs(&pairs(&[("city", "Utrecht")])).unwrap();
+            let result = fields.merge(&pairs(&[("city", "Amsterdam"), ("birth_date", "soon")]));
+
+            assert!(
+                matches!(result, Err(Sep9Error::InvalidField(name, _)) if name == "birth_date")
+            );
+            assert_eq!(fields.get("city").as_deref(), Some("Utrecht"));
+        }
+
+        #[test]
+        fn json_round_trip_is_flat() {
+            let fields = Sep9Fields::from_pairs(&pairs(&[
+                ("first_name", "Jane"),
+                ("organization.name", "Acme"),
+            ]))
+            .unwrap();
+
+            let json = fields.to_json().unwrap();
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+                serde_json::json!({"first_name": "Jane", "organization.name": "Acme"})
+            );
+            assert_eq!(
+                Sep9Fields::from_json(&json).unwrap().to_map().unwrap(),
+                fields.to_map().unwrap()
+            );
+        }
+    }
+}
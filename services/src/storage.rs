@@ -0,0 +1,541 @@
+// Document storage
+// Helper functions:
+// DocumentStore trait with local filesystem and S3-compatible backends
+// Upload validation (size, MIME type, magic bytes)
+// Content hashing
+pub mod storage {
+    use chrono::Utc;
+    use mockall::automock;
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::PKey;
+    use openssl::sha::sha256;
+    use openssl::sign::Signer;
+    use reqwest::Client;
+    use std::path::{Component, Path, PathBuf};
+    use thiserror::Error;
+    use url::Url;
+
+    /// Uploads larger than this are rejected unless `DOCUMENT_MAX_BYTES` says otherwise.
+    pub const DEFAULT_MAX_DOCUMENT_BYTES: usize = 10 * 1024 * 1024;
+
+    pub const ALLOWED_CONTENT_TYPES: [&str; 4] =
+        ["image/jpeg", "image/png", "image/webp", "application/pdf"];
+
+    #[derive(Error, Debug)]
+    pub enum StorageError {
+        #[error("HTTP error: {0}")]
+        HttpError(#[from] reqwest::Error),
+
+        #[error("IO error: {0}")]
+        IoError(#[from] std::io::Error),
+
+        #[error("Signing failed: {0}")]
+        SigningFailed(#[from] openssl::error::ErrorStack),
+
+        #[error("Invalid document: {0}")]
+        InvalidDocument(String),
+
+        #[error("Invalid storage key: {0}")]
+        InvalidKey(String),
+
+        #[error("Document not found: {0}")]
+        NotFound(String),
+
+        #[error("Content hash mismatch for {0}")]
+        IntegrityMismatch(String),
+
+        #[error("Storage backend error: {0}")]
+        BackendError(String),
+
+        #[error("Invalid configuration: {0}")]
+        ConfigError(String),
+    }
+
+    /// Where customer documents live.
+    ///
+    /// Keys are relative, `/`-separated paths such as `customers/<id>/<file>`.
+    #[automock]
+    #[allow(async_fn_in_trait)]
+    pub trait DocumentStore {
+        async fn put(&self, key: &str, bytes: &[u8], content_type: &str)
+        -> Result<(), StorageError>;
+
+        async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError>;
+
+        async fn delete(&self, key: &str) -> Result<(), StorageError>;
+    }
+
+    pub struct LocalDocumentStore {
+        root: PathBuf,
+    }
+
+    impl LocalDocumentStore {
+        pub fn new(root: impl Into<PathBuf>) -> Self {
+            Self { root: root.into() }
+        }
+
+        fn path_for(&self, key: &str) -> Result<PathBuf, StorageError> {
+            let relative = Path::new(key);
+            let safe = relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+            if key.is_empty() || !safe {
+                return Err(StorageError::InvalidKey(key.to_string()));
+            }
+            Ok(self.root.join(relative))
+        }
+    }
+
+    impl DocumentStore for LocalDocumentStore {
+        async fn put(
+            &self,
+            key: &str,
+            bytes: &[u8],
+            _content_type: &str,
+        ) -> Result<(), StorageError> {
+            let path = self.path_for(key)?;
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(path, bytes).await?;
+            Ok(())
+        }
+
+        async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+            match tokio::fs::read(self.path_for(key)?).await {
+                Ok(bytes) => Ok(bytes),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    Err(StorageError::NotFound(key.to_string()))
+                }
+                Err(e) => Err(e.into()),
+            }
+        }
+
+        async fn delete(&self, key: &str) -> Result<(), StorageError> {
+            match tokio::fs::remove_file(self.path_for(key)?).await {
+                Ok(()) => Ok(()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                Err(e) => Err(e.into()),
+            }
+        }
+    }
+
+    /// S3-compatible object storage using path-style URLs, so MinIO and similar work too.
+    pub struct S3DocumentStore {
+        client: Client,
+        endpoint: Url,
+        bucket: String,
+        region: String,
+        access_key_id: String,
+        secret_access_key: String,
+    }
+
+    impl S3DocumentStore {
+        pub fn new(
+            endpoint: &str,
+            bucket: &str,
+            region: &str,
+            access_key_id: &str,
+            secret_access_key: &str,
+        ) -> Result<Self, StorageError> {
+            let endpoint = Url::parse(endpoint)
+                .map_err(|e| StorageError::ConfigError(format!("Invalid S3 endpoint: {}", e)))?;
+
+            Ok(Self {
+                client: Client::new(),
+                endpoint,
+                bucket: bucket.to_string(),
+                region: region.to_string(),
+                access_key_id: access_key_id.to_string(),
+                secret_access_key: secret_access_key.to_string(),
+            })
+        }
+
+        fn object_path(&self, key: &str) -> Result<String, StorageError> {
+            let valid = !key.is_empty()
+                && key.split('/').all(|segment| {
+                    !segment.is_empty()
+                        && segment != ".."
+                        && segment
+                            .bytes()
+                            .all(|b| b.is_ascii_alphanumeric() || b"-_.".contains(&b))
+                });
+            if !valid {
+                return Err(StorageError::InvalidKey(key.to_string()));
+            }
+            Ok(format!("/{}/{}", self.bucket, key))
+        }
+
+        fn host(&self) -> String {
+            let host = self.endpoint.host_str().unwrap_or_default();
+            match self.endpoint.port() {
+                Some(port) => format!("{}:{}", host, port),
+                None => host.to_string(),
+            }
+        }
+
+        fn hmac(key: &[u8], data: &str) -> Result<Vec<u8>, StorageError> {
+            let pkey = PKey::hmac(key)?;
+            let mut signer = Signer::new(MessageDigest::sha256(), &pkey)?;
+            signer.update(data.as_bytes())?;
+            Ok(signer.sign_to_vec()?)
+        }
+
+        // AWS Signature Version 4 over the host, payload hash and date headers
+        fn signed_request(
+            &self,
+            method: reqwest::Method,
+            key: &str,
+            payload: &[u8],
+        ) -> Result<reqwest::RequestBuilder, StorageError> {
+            let path = self.object_path(key)?;
+            let host = self.host();
+            let now = Utc::now();
+            let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+            let date = now.format("%Y%m%d").to_string();
+            let payload_hash = hex::encode(sha256(payload));
+
+            let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+            let canonical_request = format!(
+                "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+                method, path, host, payload_hash, amz_date, signed_headers, payload_hash
+            );
+            let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+            let string_to_sign = format!(
+                "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+                amz_date,
+                scope,
+                hex::encode(sha256(canonical_request.as_bytes()))
+            );
+
+            let k_date = Self::hmac(
+                format!("AWS4{}", self.secret_access_key).as_bytes(),
+                &date,
+            )?;
+            let k_region = Self::hmac(&k_date, &self.region)?;
+            let k_service = Self::hmac(&k_region, "s3")?;
+            let k_signing = Self::hmac(&k_service, "aws4_request")?;
+            let signature = hex::encode(Self::hmac(&k_signing, &string_to_sign)?);
+
+            let authorization = format!(
+                "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                self.access_key_id, scope, signed_headers, signature
+            );
+
+            let mut url = self.endpoint.clone();
+            url.set_path(&path);
+
+            Ok(self
+                .client
+                .request(method, url)
+                .header("x-amz-content-sha256", payload_hash)
+                .header("x-amz-date", amz_date)
+                .header("Authorization", authorization))
+        }
+    }
+
+    impl DocumentStore for S3DocumentStore {
+        async fn put(&self, key: &str, bytes: &[u8], content_type: &str) -> Result<(), StorageError> {
+            let response = self
+                .signed_request(reqwest::Method::PUT, key, bytes)?
+                .header("Content-Type", content_type)
+                .body(bytes.to_vec())
+                .send()
+                .await?;
+
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(StorageError::BackendError(format!(
+                    "PUT {} failed with status {}",
+                    key,
+                    response.status()
+                )))
+            }
+        }
+
+        async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+            let response = self
+                .signed_request(reqwest::Method::GET, key, &[])?
+                .send()
+                .await?;
+
+            if response.status() == 404 {
+                return Err(StorageError::NotFound(key.to_string()));
+            }
+
+            if !response.status().is_success() {
+                return Err(StorageError::BackendError(format!(
+                    "GET {} failed with status {}",
+                    key,
+                    response.status()
+                )));
+            }
+
+            Ok(response.bytes().await?.to_vec())
+        }
+
+        async fn delete(&self, key: &str) -> Result<(), StorageError> {
+            let response = self
+                .signed_request(reqwest::Method::DELETE, key, &[])?
+                .send()
+                .await?;
+
+            if response.status().is_success() || response.status() == 404 {
+                Ok(())
+            } else {
+                Err(StorageError::BackendError(format!(
+                    "DELETE {} failed with status {}",
+                    key,
+                    response.status()
+                )))
+            }
+        }
+    }
+
+    /// The configured backend, selected with `DOCUMENT_STORE` (`local` or `s3`).
+    pub enum DocumentStoreBackend {
+        Local(LocalDocumentStore),
+        S3(S3DocumentStore),
+    }
+
+    impl DocumentStore for DocumentStoreBackend {
+        async fn put(&self, key: &str, bytes: &[u8], content_type: &str) -> Result<(), StorageError> {
+            match self {
+                DocumentStoreBackend::Local(store) => store.put(key, bytes, content_type).await,
+                DocumentStoreBackend::S3(store) => store.put(key, bytes, content_type).await,
+            }
+        }
+
+        async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+            match self {
+                DocumentStoreBackend::Local(store) => store.get(key).await,
+                DocumentStoreBackend::S3(store) => store.get(key).await,
+            }
+        }
+
+        async fn delete(&self, key: &str) -> Result<(), StorageError> {
+            match self {
+                DocumentStoreBackend::Local(store) => store.delete(key).await,
+                DocumentStoreBackend::S3(store) => store.delete(key).await,
+            }
+        }
+    }
+
+    fn required_env(name: &str) -> Result<String, StorageError> {
+        std::env::var(name).map_err(|_| StorageError::ConfigError(format!("{} must be set", name)))
+    }
+
+    pub fn document_store_from_env() -> Result<DocumentStoreBackend, StorageError> {
+        match std::env::var("DOCUMENT_STORE").as_deref().unwrap_or("local") {
+            "local" => {
+                let root =
+                    std::env::var("DOCUMENT_STORE_PATH").unwrap_or_else(|_| "storage".to_string());
+                Ok(DocumentStoreBackend::Local(LocalDocumentStore::new(root)))
+            }
+            "s3" => Ok(DocumentStoreBackend::S3(S3DocumentStore::new(
+                &required_env("S3_ENDPOINT")?,
+                &required_env("S3_BUCKET")?,
+                &std::env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+                &required_env("S3_ACCESS_KEY_ID")?,
+                &required_env("S3_SECRET_ACCESS_KEY")?,
+            )?)),
+            other => Err(StorageError::ConfigError(format!(
+                "Unknown DOCUMENT_STORE {}",
+                other
+            ))),
+        }
+    }
+
+    /// Hex-encoded SHA-256 of the document bytes.
+    pub fn content_hash(bytes: &[u8]) -> String {
+        hex::encode(sha256(bytes))
+    }
+
+    fn matches_magic_bytes(content_type: &str, bytes: &[u8]) -> bool {
+        match content_type {
+            "image/jpeg" => bytes.starts_with(&[0xFF, 0xD8, 0xFF]),
+            "image/png" => bytes.starts_with(&[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]),
+            "image/webp" => bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP",
+            "application/pdf" => bytes.starts_with(b"%PDF-"),
+            _ => false,
+        }
+    }
+
+    /// Checks size, declared MIME type and that the bytes actually look like that type.
+    pub fn validate_document(
+        name: &str,
+        content_type: &str,
+        bytes: &[u8],
+    ) -> Result<(), StorageError> {
+        let max_bytes = std::env::var("DOCUMENT_MAX_BYTES")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(DEFAULT_MAX_DOCUMENT_BYTES);
+
+        if bytes.is_empty() {
+            return Err(StorageError::InvalidDocument(format!("{} is empty", name)));
+        }
+
+        if bytes.len() > max_bytes {
+            return Err(StorageError::InvalidDocument(format!(
+                "{} is {} bytes, limit is {}",
+                name,
+                bytes.len(),
+                max_bytes
+            )));
+        }
+
+        let content_type = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        if !ALLOWED_CONTENT_TYPES.contains(&content_type.as_str()) {
+            return Err(StorageError::InvalidDocument(format!(
+                "{} has unsupported content type {}",
+                name, content_type
+            )));
+        }
+
+        if !matches_magic_bytes(&content_type, bytes) {
+            return Err(StorageError::InvalidDocument(format!(
+                "{} does not look like {}",
+                name, content_type
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Restricts a user-supplied file name to characters that are safe in any backend key.
+    pub fn sanitize_file_name(name: &str) -> String {
+        let sanitized: String = name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        sanitized.trim_start_matches('.').to_string()
+    }
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const PDF: &[u8] = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n";
+        const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 13];
+        const JPEG: &[u8] = &[0xFF, 0xD8, 0xFF, 0xE0, 0, 16, b'J', b'F', b'I', b'F'];
+        const WEBP: &[u8] = b"RIFF\x24\x00\x00\x00WEBPVP8 ";
+
+        fn temp_store() -> (LocalDocumentStore, PathBuf) {
+            let root = std::env::temp_dir().join(format!("documents-{}", uuid::Uuid::new_v4()));
+            (LocalDocumentStore::new(&root), root)
+        }
+
+        #[test]
+        fn accepts_documents_matching_their_type() {
+            assert!(validate_document("passport.pdf", "application/pdf", PDF).is_ok());
+            assert!(validate_document("photo.png", "image/png", PNG).is_ok());
+            assert!(validate_document("photo.jpg", "image/jpeg", JPEG).is_ok());
+            assert!(validate_document("photo.webp", "image/webp", WEBP).is_ok());
+            assert!(validate_document("photo.png", "IMAGE/PNG; charset=binary", PNG).is_ok());
+        }
+
+        #[test]
+        fn rejects_mismatched_magic_bytes() {
+            assert!(matches!(
+                validate_document("photo.png", "image/png", PDF),
+                Err(StorageError::InvalidDocument(_))
+            ));
+            assert!(matches!(
+                validate_document("photo.webp", "image/webp", b"RIFF"),
+                Err(StorageError::InvalidDocument(_))
+            ));
+        }
+
+        #[test]
+        fn rejects_unsupported_content_types() {
+            assert!(matches!(
+                validate_document("script.html", "text/html", b"<html>"),
+                Err(StorageError::InvalidDocument(_))
+            ));
+        }
+
+        #[test]
+        fn enforces_size_limits() {
+            assert!(matches!(
+                validate_document("empty.pdf", "application/pdf", &[]),
+                Err(StorageError::InvalidDocument(_))
+            ));
+
+            let mut oversized = PDF.to_vec();
+            oversized.resize(DEFAULT_MAX_DOCUMENT_BYTES + 1, 0);
+            assert!(matches!(
+                validate_document("large.pdf", "application/pdf", &oversized),
+                Err(StorageError::InvalidDocument(message)) if message.contains("limit")
+            ));
+
+            oversized.truncate(DEFAULT_MAX_DOCUMENT_BYTES);
+            assert!(validate_document("large.pdf", "application/pdf", &oversized).is_ok());
+        }
+
+        #[test]
+        fn sanitizes_file_names() {
+            assert_eq!(sanitize_file_name("passport scan.pdf"), "passport_scan.pdf");
+            assert_eq!(sanitize_file_name("../../etc/passwd"), "_.._etc_passwd");
+            assert_eq!(sanitize_file_name(".hidden"), "hidden");
+            assert_eq!(sanitize_file_name("/abs/path.png"), "_abs_path.png");
+        }
+
+        #[test]
+        fn local_keys_cannot_escape_the_root() {
+            let store = LocalDocumentStore::new("/srv/documents");
+            assert_eq!(
+                store.path_for("customers/1/id.pdf").unwrap(),
+                PathBuf::from("/srv/documents/customers/1/id.pdf")
+            );
+            for key in [
+                "",
+                "../secrets",
+                "customers/../../etc/passwd",
+                "/etc/passwd",
+                "./id.pdf",
+            ] {
+                assert!(
+                    matches!(store.path_for(key), Err(StorageError::InvalidKey(_))),
+                    "{} should be rejected",
+                    key
+                );
+            }
+        }
+
+        #[tokio::test]
+        async fn local_store_round_trip() {
+            let (store, root) = temp_store();
+            let key = "customers/42/passport.pdf";
+
+            store.put(key, PDF, "application/pdf").await.unwrap();
+            assert_eq!(store.get(key).await.unwrap(), PDF);
+
+            store.delete(key).await.unwrap();
+            assert!(matches!(
+                store.get(key).await,
+                Err(StorageError::NotFound(_))
+            ));
+            // Deleting twice is not an error
+            store.delete(key).await.unwrap();
+
+            assert!(matches!(
+                store.put("../outside.pdf", PDF, "application/pdf").await,
+                Err(StorageError::InvalidKey(_))
+            ));
+
+            let _ = std::fs::remove_dir_all(root);
+        }
+    }
+}
@@ -0,0 +1,480 @@
+// Webhooks
+// Helper functions:
+// Subscription registry
+// Outbox enqueue
+// Signed delivery with retries and dead-lettering
+// Replay
+pub mod webhook {
+    use chrono::{Duration, NaiveDateTime, Utc};
+    use diesel::prelude::*;
+    use openssl::hash::MessageDigest;
+    use openssl::pkey::PKey;
+    use openssl::sign::Signer;
+    use rand::RngCore;
+    use reqwest::Client;
+    use serde::{Deserialize, Serialize};
+    use thiserror::Error;
+    use uuid::Uuid;
+
+    use models::{
+        common::establish_connection,
+        schema::offramp_service::{webhook_deliveries, webhook_subscriptions},
+        webhook::{
+            NewWebhookDelivery, NewWebhookSubscription, WebhookDelivery, WebhookSubscription,
+        },
+    };
+
+    pub const SEP6_STATUS_CHANGED: &str = "sep6.status_changed";
+    pub const SEP12_STATUS_CHANGED: &str = "sep12.status_changed";
+    pub const SEP31_STATUS_CHANGED: &str = "sep31.status_changed";
+    pub const SEP38_QUOTE_EXPIRED: &str = "sep38.quote_expired";
+    pub const SEP38_ASSETS_CHANGED: &str = "sep38.assets_changed";
+    pub const SEP38_RATE_ALERT_TRIGGERED: &str = "sep38.rate_alert_triggered";
+
+    pub const SUPPORTED_EVENTS: [&str; 6] = [
+        SEP6_STATUS_CHANGED,
+        SEP12_STATUS_CHANGED,
+        SEP31_STATUS_CHANGED,
+        SEP38_QUOTE_EXPIRED,
+        SEP38_ASSETS_CHANGED,
+        SEP38_RATE_ALERT_TRIGGERED,
+    ];
+
+    pub const STATUS_PENDING: &str = "pending";
+    pub const STATUS_DELIVERED: &str = "delivered";
+    pub const STATUS_DEAD_LETTERED: &str = "dead_lettered";
+
+    /// Deliveries that still fail after this many attempts are dead-lettered.
+    pub const MAX_DELIVERY_ATTEMPTS: i32 = 8;
+
+    const SIGNATURE_HEADER: &str = "X-Offramp-Signature";
+    const EVENT_HEADER: &str = "X-Offramp-Event";
+    const DELIVERY_HEADER: &str = "X-Offramp-Delivery";
+
+    // Each row is leased for a single send, so the lease only has to outlast one timeout
+    const DELIVERY_TIMEOUT_SECS: u64 = 10;
+    const DELIVERY_LEASE_SECS: i64 = 60;
+
+    #[derive(Error, Debug)]
+    pub enum WebhookError {
+        #[error("HTTP error: {0}")]
+        HttpError(#[from] reqwest::Error),
+
+        #[error("Invalid request: {0}")]
+        InvalidRequest(String),
+
+        #[error("Unsupported event type: {0}")]
+        UnsupportedEvent(String),
+
+        #[error("Subscription not found")]
+        SubscriptionNotFound,
+
+        #[error("Delivery not found")]
+        DeliveryNotFound,
+
+        #[error("Signing failed: {0}")]
+        SigningFailed(#[from] openssl::error::ErrorStack),
+
+        #[error("Database error: {0}")]
+        DatabaseError(String),
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct CreatedSubscription {
+        pub id: Uuid,
+        pub url: String,
+        pub secret: String,
+        pub event_types: Vec<String>,
+        pub active: bool,
+    }
+
+    // Body POSTed to subscribers
+    #[derive(Debug, Serialize, Deserialize)]
+    pub struct WebhookEnvelope {
+        pub id: Uuid,
+        pub event: String,
+        pub created_at: NaiveDateTime,
+        pub data: serde_json::Value,
+    }
+
+    // 1. Register a subscription
+    pub fn create_subscription(
+        url: &str,
+        secret: Option<&str>,
+        event_types: Vec<String>,
+    ) -> Result<CreatedSubscription, WebhookError> {
+        let parsed = url::Url::parse(url)
+            .map_err(|_| WebhookError::InvalidRequest("Invalid webhook url".to_string()))?;
+        if parsed.scheme() != "https" && parsed.scheme() != "http" {
+            return Err(WebhookError::InvalidRequest(
+                "Webhook url must be http or https".to_string(),
+            ));
+        }
+
+        if event_types.is_empty() {
+            return Err(WebhookError::InvalidRequest(
+                "At least one event type is required".to_string(),
+            ));
+        }
+
+        if let Some(unsupported) = event_types
+            .iter()
+            .find(|e| !SUPPORTED_EVENTS.contains(&e.as_str()))
+        {
+            return Err(WebhookError::UnsupportedEvent(unsupported.clone()));
+        }
+
+        let secret = match secret {
+            Some(s) if !s.is_empty() => s.to_string(),
+            _ => generate_secret(),
+        };
+
+        let mut conn =
+            establish_connection().map_err(|e| WebhookError::DatabaseError(e.to_string()))?;
+
+        let subscription: WebhookSubscription = diesel::insert_into(webhook_subscriptions::table)
+            .values(&NewWebhookSubscription {
+                url: url.to_string(),
+                secret,
+                event_types,
+            })
+            .get_result(&mut conn)
+            .map_err(|e| WebhookError::DatabaseError(e.to_string()))?;
+
+        Ok(CreatedSubscription {
+            id: subscription.id,
+            url: subscription.url,
+            secret: subscription.secret,
+            event_types: subscription.event_types,
+            active: subscription.active,
+        })
+    }
+
+    // 2. List subscriptions
+    pub fn list_subscriptions() -> Result<Vec<WebhookSubscription>, WebhookError> {
+        let mut conn =
+            establish_connection().map_err(|e| WebhookError::DatabaseError(e.to_string()))?;
+
+        webhook_subscriptions::table
+            .order(webhook_subscriptions::created_at.desc())
+            .load(&mut conn)
+            .map_err(|e| WebhookError::DatabaseError(e.to_string()))
+    }
+
+    // 3. Remove a subscription (pending deliveries are removed with it)
+    pub fn delete_subscription(subscription_id: &str) -> Result<(), WebhookError> {
+        let subscription_uuid = Uuid::parse_str(subscription_id)
+            .map_err(|_| WebhookError::InvalidRequest("Invalid subscription ID".to_string()))?;
+
+        let mut conn =
+            establish_connection().map_err(|e| WebhookError::DatabaseError(e.to_string()))?;
+
+        let deleted = diesel::delete(webhook_subscriptions::table.find(subscription_uuid))
+            .execute(&mut conn)
+            .map_err(|e| WebhookError::DatabaseError(e.to_string()))?;
+
+        if deleted == 0 {
+            return Err(WebhookError::SubscriptionNotFound);
+        }
+
+        Ok(())
+    }
+
+    /// Writes one outbox row per active subscription listening for `event_type`.
+    ///
+    /// Takes the caller's connection; call it inside the same `conn.transaction` as the
+    /// state change that produced the event so both commit or roll back together.
+    pub fn enqueue_event(
+        conn: &mut PgConnection,
+        event_type: &str,
+        payload: serde_json::Value,
+    ) -> Result<usize, WebhookError> {
+        if !SUPPORTED_EVENTS.contains(&event_type) {
+            return Err(WebhookError::UnsupportedEvent(event_type.to_string()));
+        }
+
+        let subscription_ids: Vec<Uuid> = webhook_subscriptions::table
+            .filter(webhook_subscriptions::active.eq(true))
+            .filter(webhook_subscriptions::event_types.contains(vec![event_type.to_string()]))
+            .select(webhook_subscriptions::id)
+            .load(conn)
+            .map_err(|e| WebhookError::DatabaseError(e.to_string()))?;
+
+        let deliveries: Vec<NewWebhookDelivery> = subscription_ids
+            .into_iter()
+            .map(|subscription_id| NewWebhookDelivery {
+                subscription_id,
+                event_type: event_type.to_string(),
+                payload: payload.clone(),
+            })
+            .collect();
+
+        if deliveries.is_empty() {
+            return Ok(0);
+        }
+
+        diesel::insert_into(webhook_deliveries::table)
+            .values(&deliveries)
+            .execute(conn)
+            .map_err(|e| WebhookError::DatabaseError(e.to_string()))
+    }
+
+    /// Computes the hex HMAC-SHA256 of `"{timestamp}.{body}"` with the subscription secret.
+    pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> Result<String, WebhookError> {
+        let key = PKey::hmac(secret.as_bytes())?;
+        let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+        signer.update(format!("{}.{}", timestamp, body).as_bytes())?;
+        Ok(hex::encode(signer.sign_to_vec()?))
+    }
+
+    /// Delay before the next attempt: 30s doubling per attempt, capped at one hour.
+    pub fn retry_backoff(attempts: i32) -> Duration {
+        let exponent = attempts.clamp(0, 10) as u32;
+        Duration::seconds((30_i64 * 2_i64.pow(exponent)).min(3600))
+    }
+
+    // Lease the next due row so concurrent workers skip it while it is in flight
+    fn claim_next(
+        conn: &mut PgConnection,
+    ) -> Result<Option<(WebhookDelivery, WebhookSubscription)>, WebhookError> {
+        let now = Utc::now().naive_utc();
+        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+            let Some(id) = webhook_deliveries::table
+                .filter(webhook_deliveries::status.eq(STATUS_PENDING))
+                .filter(webhook_deliveries::next_attempt_at.le(now))
+                .order(webhook_deliveries::next_attempt_at.asc())
+                .select(webhook_deliveries::id)
+                .for_update()
+                .skip_locked()
+                .first::<Uuid>(conn)
+                .optional()?
+            else {
+                return Ok(None);
+            };
+
+            diesel::update(webhook_deliveries::table.find(id))
+                .set(
+                    webhook_deliveries::next_attempt_at
+                        .eq(now + Duration::seconds(DELIVERY_LEASE_SECS)),
+                )
+                .execute(conn)?;
+
+            webhook_deliveries::table
+                .inner_join(webhook_subscriptions::table)
+                .filter(webhook_deliveries::id.eq(id))
+                .first(conn)
+                .optional()
+        })
+        .map_err(|e| WebhookError::DatabaseError(e.to_string()))
+    }
+
+    // 4. Deliver up to `batch_size` due outbox rows, one lease at a time
+    pub async fn deliver_due(batch_size: i64) -> Result<usize, WebhookError> {
+        let mut conn =
+            establish_connection().map_err(|e| WebhookError::DatabaseError(e.to_string()))?;
+
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .build()?;
+
+        let mut delivered = 0;
+        for _ in 0..batch_size {
+            let Some((delivery, subscription)) = claim_next(&mut conn)? else {
+                break;
+            };
+            let outcome = send_delivery(&client, &delivery, &subscription).await;
+            let now = Utc::now().naive_utc();
+
+            match outcome {
+                Ok(status_code) => {
+                    diesel::update(webhook_deliveries::table.find(delivery.id))
+                        .set((
+                            webhook_deliveries::status.eq(STATUS_DELIVERED),
+                            webhook_deliveries::attempts.eq(delivery.attempts + 1),
+                            webhook_deliveries::last_response_status.eq(Some(status_code)),
+                            webhook_deliveries::last_error.eq(None::<String>),
+                            webhook_deliveries::delivered_at.eq(Some(now)),
+                            webhook_deliveries::updated_at.eq(now),
+                        ))
+                        .execute(&mut conn)
+                        .map_err(|e| WebhookError::DatabaseError(e.to_string()))?;
+                    delivered += 1;
+                }
+                Err((status_code, error)) => {
+                    let attempts = delivery.attempts + 1;
+                    let status = if attempts >= MAX_DELIVERY_ATTEMPTS {
+                        STATUS_DEAD_LETTERED
+                    } else {
+                        STATUS_PENDING
+                    };
+
+                    diesel::update(webhook_deliveries::table.find(delivery.id))
+                        .set((
+                            webhook_deliveries::status.eq(status),
+                            webhook_deliveries::attempts.eq(attempts),
+                            webhook_deliveries::last_response_status.eq(status_code),
+                            webhook_deliveries::last_error.eq(Some(error)),
+                            webhook_deliveries::next_attempt_at.eq(now + retry_backoff(attempts)),
+                            webhook_deliveries::updated_at.eq(now),
+                        ))
+                        .execute(&mut conn)
+                        .map_err(|e| WebhookError::DatabaseError(e.to_string()))?;
+                }
+            }
+        }
+
+        Ok(delivered)
+    }
+
+    // POST a single delivery, returning the response status or the failure reason
+    async fn send_delivery(
+        client: &Client,
+        delivery: &WebhookDelivery,
+        subscription: &WebhookSubscription,
+    ) -> Result<i32, (Option<i32>, String)> {
+        let envelope = WebhookEnvelope {
+            id: delivery.id,
+            event: delivery.event_type.clone(),
+            created_at: delivery.created_at,
+            data: delivery.payload.clone(),
+        };
+        let body = serde_json::to_string(&envelope).map_err(|e| (None, e.to_string()))?;
+
+        let timestamp = Utc::now().timestamp();
+        let signature =
+            sign_payload(&subscription.secret, timestamp, &body).map_err(|e| (None, e.to_string()))?;
+
+        let response = client
+            .post(&subscription.url)
+            .header("Content-Type", "application/json")
+            .header(SIGNATURE_HEADER, format!("t={},v1={}", timestamp, signature))
+            .header(EVENT_HEADER, delivery.event_type.as_str())
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| (None, e.to_string()))?;
+
+        let status_code = response.status().as_u16() as i32;
+        if response.status().is_success() {
+            Ok(status_code)
+        } else {
+            Err((Some(status_code), format!("Status: {}", response.status())))
+        }
+    }
+
+    // 5. List deliveries, e.g. the dead-letter queue
+    pub fn list_deliveries(
+        status: Option<&str>,
+        subscription_id: Option<&str>,
+        limit: Option<i64>,
+    ) -> Result<Vec<WebhookDelivery>, WebhookError> {
+        let mut conn =
+            establish_connection().map_err(|e| WebhookError::DatabaseError(e.to_string()))?;
+
+        let mut query = webhook_deliveries::table.into_boxed();
+
+        if let Some(s) = status {
+            query = query.filter(webhook_deliveries::status.eq(s.to_string()));
+        }
+
+        if let Some(id) = subscription_id {
+            let subscription_uuid = Uuid::parse_str(id)
+                .map_err(|_| WebhookError::InvalidRequest("Invalid subscription ID".to_string()))?;
+            query = query.filter(webhook_deliveries::subscription_id.eq(subscription_uuid));
+        }
+
+        query
+            .order(webhook_deliveries::created_at.desc())
+            .limit(limit.unwrap_or(50))
+            .load(&mut conn)
+            .map_err(|e| WebhookError::DatabaseError(e.to_string()))
+    }
+
+    // 6. Replay a delivery from scratch
+    pub fn replay_delivery(delivery_id: &str) -> Result<WebhookDelivery, WebhookError> {
+        let delivery_uuid = Uuid::parse_str(delivery_id)
+            .map_err(|_| WebhookError::InvalidRequest("Invalid delivery ID".to_string()))?;
+
+        let mut conn =
+            establish_connection().map_err(|e| WebhookError::DatabaseError(e.to_string()))?;
+
+        let now = Utc::now().naive_utc();
+        diesel::update(webhook_deliveries::table.find(delivery_uuid))
+            .set((
+                webhook_deliveries::status.eq(STATUS_PENDING),
+                webhook_deliveries::attempts.eq(0),
+                webhook_deliveries::next_attempt_at.eq(now),
+                webhook_deliveries::last_error.eq(None::<String>),
+                webhook_deliveries::updated_at.eq(now),
+            ))
+            .get_result(&mut conn)
+            .optional()
+            .map_err(|e| WebhookError::DatabaseError(e.to_string()))?
+            .ok_or(WebhookError::DeliveryNotFound)
+    }
+
+    /// Polls the outbox forever; spawned once at launch.
+    pub async fn run_delivery_worker() {
+        let interval = std::env::var("WEBHOOK_WORKER_INTERVAL_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(10);
+
+        loop {
+            if let Err(e) = deliver_due(50).await {
+                eprintln!("Error delivering webhooks: {:?}", e);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+        }
+    }
+
+    fn generate_secret() -> String {
+        let mut bytes = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        hex::encode(bytes)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn sign_payload_matches_known_hmac() {
+            let signature = sign_payload("whsec_test", 1_700_000_000, r#"{"event":"ping"}"#).unwrap();
+            assert_eq!(
+                signature,
+                "aa8efe37b751e71157c508c5ac4acb1e9fe5225db98355dfc00f4b680afbc447"
+            );
+        }
+
+        #[test]
+        fn sign_payload_depends_on_every_input() {
+            let base = sign_payload("secret", 1, "body").unwrap();
+            assert_eq!(base, sign_payload("secret", 1, "body").unwrap());
+            assert_ne!(base, sign_payload("other", 1, "body").unwrap());
+            assert_ne!(base, sign_payload("secret", 2, "body").unwrap());
+            assert_ne!(base, sign_payload("secret", 1, "body2").unwrap());
+        }
+
+        #[test]
+        fn retry_backoff_doubles_up_to_an_hour() {
+            assert_eq!(retry_backoff(0), Duration::seconds(30));
+            assert_eq!(retry_backoff(1), Duration::seconds(60));
+            assert_eq!(retry_backoff(2), Duration::seconds(120));
+            assert_eq!(retry_backoff(6), Duration::seconds(1920));
+            assert_eq!(retry_backoff(7), Duration::seconds(3600));
+            assert_eq!(retry_backoff(50), Duration::seconds(3600));
+        }
+
+        #[test]
+        fn retry_backoff_clamps_negative_attempts() {
+            assert_eq!(retry_backoff(-3), Duration::seconds(30));
+        }
+
+        #[test]
+        fn lease_outlasts_a_single_delivery() {
+            assert!(DELIVERY_LEASE_SECS > DELIVERY_TIMEOUT_SECS as i64);
+        }
+    }
+}